@@ -0,0 +1,234 @@
+# Welsh messages (Cymraeg). Any message missing from this catalogue is shown in English. These translations
+# should be checked by a native speaker before they are relied upon in clinics.
+
+## Shared by every page
+
+site-title = Prawf Craffter yn y Cartref
+site-heading = Cymorth Asesu Golwg Pell yn y Cartref
+return-home = ← Yn ôl i'r hafan
+language-name = Cymraeg
+
+## Homepage
+
+index-intro = Nod prosiect y Cymorth Asesu Golwg Pell yn y Cartref yw datblygu ffordd gywir a rhad o fesur golwg gartref. Offeryn ymchwil ydyw ar hyn o bryd – cofiwch nad yw eto'n brawf golwg wedi'i ddilysu. Dylech ddefnyddio'r wefan hon dim ond os cawsoch eich cyfeirio ati.
+index-contents = Cynnwys
+index-about = Amdanom
+index-downloads = Lawrlwythiadau
+index-patient-instructions = Cyfarwyddiadau i gleifion
+index-clinician-instructions = Cyfarwyddiadau i glinigwyr
+index-feedback-link = Adborth/ymholiadau
+index-feedback = Adborth ac ymholiadau
+index-references = Cyfeiriadau
+index-privacy-policy = Polisi preifatrwydd
+index-about-1 = Mae mesur golwg pell gartref yn anodd, a phrin yw'r dulliau dibynadwy sydd wedi'u dilysu'n glinigol. Mae atebion digidol, gan gynnwys apiau ffôn clyfar a llechen, yn cynnig ateb posibl, ond maent mewn perygl o eithrio'r rhai heb y dyfeisiau hyn. Ein nod yn y pen draw yw creu prawf golwg y gall y claf ei argraffu ar bapur A4, neu y gall y darparwr gofal ei argraffu a'i bostio at y claf.
+index-about-2 = Cofiwch fod y profion y gallwch eu creu ar y wefan hon yn cael eu datblygu, ac nad ydynt wedi'u dilysu at ddefnydd clinigol. Rydym ar hyn o bryd yn archwilio pa mor gyson yw'r prawf ar wahanol argraffwyr, a gobeithiwn wedyn ganfod pa mor dda y mae golwg a fesurir fel hyn yn cyfateb i olwg a fesurir â phrofion golwg traddodiadol. Rydym yn ddiolchgar am adborth ar ba mor gyson yw maint argraffu'r prawf, drwy gymharu'r amlinell ar dudalen 2 â cherdyn credyd.
+index-about-3 = Os penderfynwch ddefnyddio'r allbrintiau hyn yn ystod ymgynghoriad fideo, er enghraifft, ystyriwch hwy fel cymorth yn unig, fel y gallech ofyn i glaf yn y clinig ddarllen testun i asesu ei olwg. <span style="font-weight: bold">Nid ydynt yn brofion craffter wedi'u dilysu.</span>
+index-downloads-intro = Mae'r wefan hon yn caniatáu creu dau gymorth gwahanol:
+index-downloads-standardised = prawf safonol - lawrlwythiad syml a fydd yr un fath bob tro. Yn y pen draw bydd y dull hwn yn ddefnyddiol i gleifion sy'n gwneud y prawf unwaith neu ddwywaith yn unig,
+index-downloads-randomised = <span style="font-style: italic">a;</span> gallai prawf ar hap fod yn ddefnyddiol i gleifion sy'n monitro eu golwg yn rheolaidd, fel nad yw atgofion o ddarlleniadau blaenorol yn cymylu eu barn.
+index-downloads-symbols = Os na all y sawl sy'n gwneud y prawf adnabod llythrennau'n ddibynadwy, dylech lawrlwytho'r prawf symbolau. Os ydych yn defnyddio'r prawf symbolau, dylech ymarfer y symbolau gan ddefnyddio <a href="/naming-sheet/aukland" target="_blank">y ddogfen hon.</a>
+index-standardised-heading = 1. Cymhorthion asesu safonol
+index-download-standardised = Lawrlwytho ({ $label }, Safonol)
+standard-chart-letters = Llythrennau
+standard-chart-symbols = Symbolau
+index-randomised-heading = 2. Cymhorthion asesu ar hap
+index-randomised-intro = <span style="font-weight: bold">Bob tro y mae angen ailadrodd y prawf, cliciwch y ddolen gywir isod i lawrlwytho fersiwn newydd o'r daflen brawf.</span> Mae hyn yn golygu y bydd trefn y llythrennau'n newid bob tro y gwnewch y prawf.
+index-telephone-heading = Asesiad dros y ffôn
+index-telephone-intro = Gellir adnabod y cymhorthion asesu hyn â chod syml 6 digid y gellir ei roi'n hawdd dros y ffôn. Nid ydynt yn cynnwys cynifer o gyfuniadau ar hap o lythrennau â'r profion drwy'r post/hunanasesu, ond disgwylir bod digon o gyfuniadau i bara am sawl blwyddyn o brofi wythnosol.
+index-download-letters-telephone = Lawrlwytho (Llythrennau, Ffôn)
+index-download-symbols-telephone = Lawrlwytho (Symbolau, Ffôn)
+index-self-heading = Asesiad drwy'r post/hunanasesiad
+index-self-intro = Mae gan y cymhorthion asesu hyn dros biliwn o drefniadau llythrennau posibl. Caiff pob taflen ei hadnabod â chod hwy (16 digid). Mae'r cod hwn yn cynnwys gwybodaeth am y math o brawf, yr atebion cywir, a chod sy'n ein galluogi i wirio am wallau.
+index-self-check = Os gofynnwyd i chi wirio eich atebion eich hun, defnyddiwch <a href="/self-check">y dudalen hon</a>. Mae'n gofyn beth a ddarllenoch ar bob rhes cyn dangos yr atebion i chi.
+index-download-letters-self = Lawrlwytho (Llythrennau, Hunanasesu)
+index-download-symbols-self = Lawrlwytho (Symbolau, Hunanasesu)
+index-monitoring = Os ydych yn monitro golwg dros amser, gallwch <a href="/monitor">gymharu canlyniadau profion blaenorol</a> i weld a yw'r golwg wedi newid fwy nag y gall y prawf ei fesur yn ddibynadwy.
+index-patient-download = <span style="font-weight: bold">Lawrlwythwch y fersiwn o'r prawf y mae eich clinigwr yn dweud wrthych amdano, a'i argraffu.</span>
+index-patient-print = Gwnewch yn siŵr eich bod yn dewis "Maint Llawn" neu "100%" yn y gosodiadau argraffu wrth argraffu.
+index-patient-step-1 = Gwiriwch fod maint y siart yn gywir drwy osod cerdyn banc yn y blwch ar yr ail dudalen. Os gallwch guddio'r blwch llwyd ond dal i weld yr holl amlinell ddu, mae'r maint yn gywir.
+index-patient-step-2 = Gosodwch y siart hwn ar wal gyda Blu-Tac, tua lefel y llygad
+index-patient-step-3 = Sefwch { $distance } cm i ffwrdd o'r siart. (Defnyddiwch y llinyn a ddarparwyd i fesur y pellter hwn)
+index-patient-step-4 = Dilynwch y cyfarwyddiadau a roddir gan y clinigwr ar y ffôn
+index-clinician-intro = Mae strwythur sylfaenol y prawf yr un fath, pa opsiwn bynnag a ddewiswch.
+index-choosing-heading = 1. Dewis cymorth asesu
+index-choosing-once = <span style="font-weight: bold">Os ydych yn profi claf unwaith neu ddwywaith yn unig:</span> dylech ddefnyddio lawrlwythiadau'r prawf safonol uchod. Mae'r rhain yr un fath bob amser, felly gallwch wybod yr ateb cywir.
+index-choosing-telephone = <span style="font-weight: bold">Os ydych yn profi claf sawl gwaith AC yn gwirio ei atebion yn ystod y prawf dros alwad ffôn/fideo:</span> dylech chi neu'r claf ddefnyddio'r asesiad ffôn uchod. Gyda'r asesiad hwn, gall y claf roi cod 6 nod i chi dros y ffôn, y gallwch ei roi yn <a href="/answers">y dudalen we hon</a> i weld yr atebion cywir. Mae hyn yn caniatáu i chi gael yr atebion cywir o'ch blaen pan fydd y claf yn darllen y siart dros y ffôn.
+index-choosing-self = <span style="font-weight: bold">Os ydych yn profi claf sawl gwaith AC yn gofyn iddo wirio ei atebion ei hun, neu bostio ei atebion:</span> dylech ddefnyddio lawrlwythiadau'r prawf hunanasesu uchod. Caiff pob prawf ei adnabod â chod 16 nod. Mae'r cod hwn yn eich galluogi i ganfod yr atebion cywir. I weld yr atebion cywir o god, rhowch y cod yn <a href="/answers">y dudalen we hon</a>.
+index-using-heading = 2. Defnyddio'r cymorth asesu
+index-using-distance = Fel arfer dylech weinyddu'r prawf o { $distance } cm, gan roi llinyn wedi'i fesur ymlaen llaw i'r claf i fesur y pellter hwn.
+index-using-lines = Ar { $distance } cm, mae'r llinellau'n cyfateb i 3/60, 6/60, 6/30, 6/15, 6/7.5.
+index-using-190 = Ar 190cm, mae'r llinellau'n cyfateb i 6/96, 6/48, 6/24, 6/12, 6/6.
+index-using-string = Os yw gwahanol rannau o'ch clinig yn defnyddio pellteroedd gwahanol ar gyfer profi, argymhellir eich bod yn codio lliw'r llinyn a roddir i gleifion, gan ddefnyddio un lliw i bob pellter. Dylid gwirio hyn cyn pob prawf.
+index-answers-heading = 3. Gweld taflenni ateb
+index-answers-intro = I farcio atebion claf, defnyddiwch y ddolen isod a rhowch god ei brawf i weld yr atebion cywir.
+index-answers-button = Gweld taflenni ateb
+index-scoring-heading = 4. Sgorio profion
+index-feedback-email = Eich e-bost:
+index-feedback-name = Eich enw:
+index-feedback-message = Eich neges:
+index-feedback-send = Anfon
+index-privacy-intro = Rydym wedi ymrwymo i barchu eich preifatrwydd. Dim ond yr isafswm rhesymol o ddata sy'n ein galluogi i ddeall effaith y wefan hon ("y gwasanaeth") a monitro camddefnydd a gasglwn. Mae hyn yn golygu:
+index-privacy-cookies = Nid yw'r gwasanaeth yn defnyddio cwcis, ac eithrio i gadw clinigwyr wedi mewngofnodi i'r canlyniadau sydd wedi'u storio ar weinyddion sy'n eu cadw.
+index-privacy-personal = Nid yw'r gwasanaeth yn casglu unrhyw wybodaeth bersonol fel enwau, cyfeiriadau a chyfeiriadau e-bost.
+index-privacy-downloads = Pan gaiff cymorth asesu ei lawrlwytho, mae'r gwasanaeth yn storio tri darn o wybodaeth: (a) dynodwr unigryw (cyfeiriad IP y defnyddiwr), (b) y math o daflen a lawrlwythwyd, ac (c) dyddiad ac amser y lawrlwythiad. Mae hyn i helpu i ganfod camddefnydd o'r gwasanaeth, ac ni chaiff ei rannu ag unrhyw drydydd parti.
+index-privacy-aggregated = Gellir rhannu data defnydd cyfanredol nad yw'n adnabod unigolion (er enghraifft, nifer y defnyddwyr sy'n defnyddio'r gwasanaeth mewn cyfnod penodol) yn breifat neu'n gyhoeddus. Mae'r rhesymau am hyn yn cynnwys, ond nid ydynt yn gyfyngedig i: (a) deall sut y defnyddir y gwasanaeth, (b) monitro a rhannu effaith y gwasanaeth, ac (c) deall sut i wella'r gwasanaeth.
+index-privacy-formspree = Mae'r gwasanaeth yn defnyddio Formspree.io i brosesu'r hyn a gyflwynir drwy'r ffurflen gyswllt. Felly mae cyflwyno'r ffurflen gyswllt yn golygu eich bod yn cytuno i'w polisi preifatrwydd, sydd i'w weld <a href="https://docs.formspree.io/files/privacy.pdf">yma</a>.
+index-privacy-agreement = Drwy ddefnyddio'r gwasanaeth, rydych yn cytuno i gasglu a defnyddio gwybodaeth yn unol â'r polisi hwn.
+
+## Answer sheet form
+
+code-form-invalid = Nid oedd y cod a roesoch yn ddilys. Gwiriwch ef a rhowch gynnig arall arni.
+code-form-contact = Os ydych yn credu bod hwn yn wall, cysylltwch â'r awdur gan ddyfynnu eich cod a'r neges gwall isod.
+code-form-heading = Gweld taflen ateb
+code-form-intro = I weld yr ateb cywir ar gyfer prawf penodol, rhowch god y prawf isod.
+code-form-lengths = Mae cod y prawf naill ai'n 6 nod o hyd (e.e. RFD-CAM) neu'n 16 nod o hyd (FFT7-CVBJ-8ZV8-ALWE).
+code-form-code = Cod:
+code-form-help = Does dim ots a ydych yn rhoi'r llinellau toriad/bylchau ai peidio.
+code-form-submit = Cyflwyno
+
+## Scoring
+
+scoring-intro = I sgorio golwg eich claf, rhowch isod y pellter gwylio a ddefnyddiwyd ar gyfer y prawf (gan gynnwys y pellter i unrhyw ddrych ac yn ôl), a'r pellter yr argraffwyd y siart ar ei gyfer. Yna, rhowch nifer y llythrennau a ddarllenwyd yn gywir.
+scoring-number-read = Nifer y llythrennau a ddarllenwyd yn gywir
+scoring-correct = Cywir:
+scoring-submit = Sgorio
+distances-viewing = Pellter gwylio (cm)
+distances-chart = Y pellter yr argraffwyd y siart ar ei gyfer (cm)
+distances-chart-help = Dangosir hwn ar frig y siart a argraffwyd. Caiff siartiau eu hargraffu ar gyfer { $distance } cm oni bai bod pellter arall wedi'i ddewis wrth eu lawrlwytho.
+score-heading = Sgorio prawf
+score-not-a-test = Nid prawf golwg yw hwn.
+score-code = Yn sgorio'r prawf â'r cod "{ $code }". I weld yr atebion cywir, <a href="{ $answers }">cliciwch yma</a>.
+score-result = Sgôr am { $correct } yn gywir o { $distance } cm:
+score-letter-by-letter = Sgôr llythyren wrth lythyren:
+score-missing-values = Gwall: Rhowch y pellter gwylio a nifer yr optoteipiau a ddarllenwyd yn gywir.
+score-worse-than = Roedd yr amcangyfrif o'r golwg yn y cartref yn gyson â chraffter o lai na { $acuity }
+score-at-least = Roedd yr amcangyfrif o'r golwg yn y cartref yn gyson â chraffter o { $acuity } o leiaf
+score-between = Roedd yr amcangyfrif o'r golwg yn y cartref yn gyson â chraffter o { $acuity } o leiaf, er nad cystal â { $next }
+
+## Self-checking
+
+self-check-heading = Gwirio eich atebion
+self-check-intro = Defnyddiwch y dudalen hon ar ôl i chi ddarllen eich siart. Byddwch yn teipio'r hyn a ddarllenoch ar bob rhes o'r siart, gan ddechrau o'r brig, a dim ond ar ôl i chi orffen y caiff yr atebion eu dangos.
+self-check-code = Cod (wedi'i argraffu ar eich siart):
+self-check-start = Dechrau
+self-check-short-code = Gwall: Mae'r cod hwn ar gyfer prawf dros y ffôn, felly bydd y person sy'n eich profi yn gwirio eich atebion.
+self-check-missing-distance = Gwall: Dewiswch y pellter gwylio.
+self-check-row = Rhes { $row } o { $rows }
+self-check-row-instructions = Edrychwch ar res { $row } o'ch siart, a theipiwch yr hyn a welwch o'r chwith i'r dde, gyda bwlch rhwng pob un. Teipiwch "-" ar gyfer unrhyw beth na allwch ei ddarllen.
+self-check-row-count = Nifer y symbolau ar y rhes hon: { $count }
+self-check-too-many = Gwall: Fe wnaethoch deipio { $typed } o atebion, ond dim ond { $count } o symbolau sydd ar y rhes hon.
+self-check-names = Gallwch hefyd glicio ar enw symbol i'w deipio:
+self-check-next = Y rhes nesaf
+self-check-finish = Ni allaf ddarllen rhagor
+self-check-results = Eich canlyniadau
+self-check-stopped = Fe wnaethoch stopio ar ôl rhes { $row }, felly ni ddarllenwyd y rhesi oddi tani.
+self-check-again = Gwirio siart arall
+
+## Comparing tests over time, and the stored results
+
+change-overall-worsened = Wedi gwaethygu ers y prawf cyntaf
+change-overall-improved = Wedi gwella ers y prawf cyntaf
+change-overall-stable = Sefydlog ers y prawf cyntaf
+change-overall = Newidiodd y craffter { $difference } logMAR rhwng { $from } a { $to }.
+change-limit = Mae newidiadau o { $limit } logMAR neu lai o fewn amrywioldeb prawf-ailbrawf y siart, felly cânt eu nodi'n sefydlog.
+change-limit-label = Terfyn prawf-ailbrawf (logMAR)
+change-worsened = Wedi gwaethygu
+change-improved = Wedi gwella
+change-stable = Sefydlog
+change-date = Dyddiad
+change-acuity = Craffter
+change-since-previous = Newid ers y prawf blaenorol
+monitor-heading = Cymharu profion dros amser
+monitor-instructions = Rhowch ganlyniadau dau brawf neu fwy o'r un llygad, gydag un prawf ar bob llinell. Dylai pob llinell ddechrau gyda dyddiad y prawf (BBBB-MM-DD), ac yna'r craffter mewn logMAR (e.e. "0.3"), fel ffracsiwn Snellen (e.e. "6/12" neu "20/40"), fel craffter degol (e.e. "0.5 decimal"), fel Sgôr Craffter Gweledol (e.e. "85 VAR") neu fel sgôr llythrennau ETDRS (e.e. "70 letters").
+monitor-eye = Llygad
+monitor-right = De
+monitor-left = Chwith
+monitor-both = Y ddau
+monitor-results = Canlyniadau
+monitor-submit = Cymharu
+patients-heading = Cleifion
+patients-intro = Mae gan y cleifion hyn ganlyniadau wedi'u storio. Dewiswch glaf i weld eu canlyniadau ar gyfer pob llygad dros amser.
+patients-none = Nid oes unrhyw ganlyniadau wedi'u storio eto.
+patients-sign-out = Allgofnodi
+patient-return = ← Yn ôl i'r rhestr o gleifion
+patient-heading = Canlyniadau ar gyfer { $patient }
+patient-limit = Amlygir newidiadau o fwy na { $limit } logMAR ers y prawf blaenorol. Mae newidiadau llai o fewn amrywioldeb prawf-ailbrawf y siart, felly cânt eu nodi'n sefydlog.
+patient-right-eye = Llygad dde
+patient-left-eye = Llygad chwith
+patient-both-eyes = Y ddau lygad
+patient-chart = Siart
+patient-distance = Pellter
+patient-correct = Cywir
+patient-unscored = Gwaeth na'r rhes uchaf
+patient-issued-heading = Siartiau a gyhoeddwyd
+patient-issued-date = Cyhoeddwyd
+patient-eye = Llygad
+patient-update = Diweddaru
+
+## Signing in to the stored results
+
+login-heading = Mewngofnodi i'r canlyniadau sydd wedi'u storio
+login-intro = Rhowch y tocyn canlyniadau i weld y canlyniadau sydd wedi'u storio ar gyfer eich cleifion. Byddwch yn aros wedi mewngofnodi nes i chi gau eich porwr.
+login-token = Tocyn canlyniadau:
+login-failed = Nid oedd y tocyn hwnnw'n gywir. Gwiriwch ef a rhowch gynnig arall arni.
+login-submit = Mewngofnodi
+
+## Errors in codes, by the kind of error
+
+code-error-invalid-character = Gwall: Roedd y cod yn cynnwys nodau na ddefnyddir mewn codau. Gwiriwch ef a rhowch gynnig arall arni.
+code-error-invalid-length = Gwall: Mae hyd y cod yn annilys.
+code-error-out-of-range = Gwall: Rhoddwyd y cod yn anghywir, gan ei fod y tu allan i'r ystod a ganiateir.
+code-error-invalid-field = Gwall: Rhoddwyd y cod yn anghywir, gan ei fod yn cynnwys gwerth annisgwyl.
+code-error-crc-mismatch = Gwall: Rhoddwyd y cod yn anghywir (nid yw'r digidau gwirio'n cyfateb).
+code-error-optotype-out-of-range = Gwall: Mae'r cod yn cyfeirio at symbol nad yw yn ei set o symbolau.
+code-error-repeated-optotype = Gwall: Mae'r cod yn gosod yr un symbol fwy nag unwaith ar res.
+
+## Errors when scoring or marking a chart, and when comparing sessions
+
+scoring-error-too-many-correct = Gwall: Rhaid i nifer yr optoteipiau a ddarllenwyd yn gywir fod rhwng 0 a { $optotypes } (yn gynhwysol).
+scoring-error-unexpected-layout = Gwall: Nid oes gan y siart y nifer disgwyliedig o resi ac optoteipiau.
+scoring-error-distance-out-of-range = Gwall: Rhaid i'r pellter gwylio fod rhwng { $min } cm a { $max } cm.
+scoring-error-missing-rows = Gwall: Mae angen sgôr ar gyfer pob rhes o'r siart.
+scoring-error-too-many-correct-on-row = Gwall: Dim ond { $optotypes } optoteip sydd yn rhes { $row }, ond darllenwyd { $correct } yn gywir.
+scoring-error-too-many-rows = Gwall: Rhoddwyd ymatebion ar gyfer { $responses } rhes, ond dim ond { $rows } rhes sydd yn y siart.
+scoring-error-too-many-responses = Gwall: Dim ond { $optotypes } optoteip sydd yn rhes { $row }, ond rhoddwyd { $responses } ymateb.
+mark-missing-distance = Gwall: Dewiswch y pellter gwylio.
+monitoring-error-unknown-eye = Gwall: Ni chafodd y llygad ei adnabod.
+monitoring-error-too-few-sessions = Gwall: Mae angen o leiaf dwy sesiwn i chwilio am newid yn y golwg.
+monitoring-error-invalid-limit = Gwall: Rhaid i'r terfyn prawf-ailbrawf fod yn sero neu'n fwy.
+monitoring-error-mixed-eyes = Gwall: Rhaid i bob sesiwn fod ar gyfer yr un llygad.
+monitoring-error-incomplete-line = Gwall: Mae angen dyddiad a chraffter ar linell { $line }.
+monitoring-error-invalid-date = Gwall: Nid yw llinell { $line } yn dechrau gyda dyddiad yn y fformat BBBB-MM-DD.
+monitoring-error-invalid-acuity = Gwall: Ni chafodd y craffter ar linell { $line } ei adnabod.
+
+## Error pages
+
+error-contact = Os yw hyn yn dal i ddigwydd, <a href="/#feedback">cysylltwch â ni</a>, gan ddyfynnu'r cod gwall <strong>{ $status }</strong> a dynodwr y cais <code>{ $request_id }</code>.
+error-bad-request-title = Cais gwael
+error-bad-request = Nid oedd y cais yn ddilys. Os dilynoch ddolen i siart, gwiriwch fod y cod yn gywir.
+error-unauthorized-title = Dim awdurdod
+error-unauthorized = Dim ond staff sydd â thocyn mynediad sy'n gallu gweld y dudalen hon.
+error-not-found-title = Heb ddod o hyd i'r dudalen
+error-not-found = Nid oedd modd dod o hyd i'r dudalen yr oeddech yn chwilio amdani. Efallai ei bod wedi symud, neu fod y ddolen yn anghyflawn.
+error-unprocessable-title = Nid oedd modd prosesu'r cais
+error-unprocessable = Deallwyd y cais, ond nid oedd rhywfaint o'r wybodaeth ynddo'n ddilys.
+error-too-many-requests-title = Gormod o geisiadau
+error-too-many-requests = Gofynnwyd am ormod o daflenni o'ch cyfeiriad. Arhoswch funud a rhowch gynnig arall arni.
+error-internal-title = Aeth rhywbeth o'i le
+error-internal = Aeth rhywbeth o'i le ar ein hochr ni wrth drin eich cais. Rhowch gynnig arall arni yn nes ymlaen.
+
+## PDFs
+
+pdf-chart-title = Siart golwg ({ $code })
+pdf-chart-printed-for = Wedi'i argraffu ar gyfer pellter gwylio o { $distance } cm. Argraffwch ar raddfa 100% ("maint gwirioneddol").
+pdf-chart-range = O { $distance } cm, mae'r rhesi'n amrywio o { $top } i { $bottom }.
+pdf-chart-range-clamped = Nid yw siart ar gyfer { $distance } cm yn ffitio ar y dudalen. O { $distance } cm, mae'r rhesi'n amrywio o { $top } i { $bottom }.
+pdf-chart-calibration = Dylai'r llinell hon fesur { $length } cm. Os nad yw, nid argraffwyd y siart i raddfa.
+pdf-naming-title = Taflen enwi ({ $optotypes })
+pdf-naming-instructions = Ymarferwch enwi pob un o'r symbolau isod cyn gwneud y prawf.
+pdf-answers-title = Allwedd atebion ({ $code })
+pdf-answers-instructions = Dyma'r allwedd atebion ar gyfer y siart, ac ni ddylid ei dangos i'r claf.
+pdf-answers-duplicate-rows = Rhybudd: mae rhai o resi'r siart hwn yn cael eu hailadrodd, felly mae'n haws ei gofio na'r arfer.
+pdf-answers-repeated-optotypes = Rhybudd: mae rhai rhesi'n cynnwys yr un symbol fwy nag unwaith. Gwiriwch y cod.
+pdf-answers-breaks-constraints = Rhybudd: mae'r siart hwn yn gosod symbolau sy'n hawdd eu drysu wrth ymyl ei gilydd, neu mae ganddo resi anghytbwys.
+pdf-answers-row = Rhes { $row } - { $acuity } ar { $distance } cm
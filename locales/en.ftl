@@ -0,0 +1,234 @@
+# English messages. This is the reference catalogue: every message must be defined here, and any message
+# which is missing from another catalogue is shown in English.
+
+## Shared by every page
+
+site-title = Home Acuity Test
+site-heading = Home Distance Vision Assessment Aid
+return-home = ← Return to the homepage
+language-name = English
+
+## Homepage
+
+index-intro = The aim of the Home Distance Vision Assessment Aid project is to develop an accurate and low cost way to measure vision at home. It is currently a research tool – be aware that it is not yet a validated test of vision. You should only access this website if you have been directed to.
+index-contents = Contents
+index-about = About
+index-downloads = Downloads
+index-patient-instructions = Patient instructions
+index-clinician-instructions = Clinician instructions
+index-feedback-link = Feedback/queries
+index-feedback = Feedback and queries
+index-references = References
+index-privacy-policy = Privacy policy
+index-about-1 = Measuring distance vision at home is difficult, and there are few clinically validated and reliable methods of doing so. Digital solutions, including smartphone and tablet apps, provide a potential solution, but risk excluding those without access to these devices. We aim eventually to produce a vision test that can be printed on A4 paper by the patient, or by the care provider and posted to the patient.
+index-about-2 = Please be aware that the tests you can generate using this site are under development, and not validated for clinical use. We are currently exploring how reproducible the test is on different printers, and following that hope to determine how well vision measured in this way relates to vision measured on traditional tests of vision. We are grateful for feedback on how consistent the print size of the test is by comparison of the silhouette on page 2 of the printout to a credit card.
+index-about-3 = If you do decide to use these printouts during a video consultation, for example, please consider them only as an aid as you might ask a patient in clinic to read some text to assess their visual performance. <span style="font-weight: bold">They are not validated tests of acuity.</span>
+index-downloads-intro = This site allows the generation of two different aids:
+index-downloads-standardised = a standardised test - a simple download which will be the same every time. Ultimately this approach will be useful in patients performing the test only once or twice,
+index-downloads-randomised = <span style="font-style: italic">and;</span> a randomized test could be useful in patients who are regularly monitoring vision to help their judgement be unclouded by memories of previous readings.
+index-downloads-symbols = If the person taking the test cannot reliably identify letters, you should download the symbol test. If you're using the symbol test, you should practice the symbols using <a href="/naming-sheet/aukland" target="_blank">this document.</a>
+index-standardised-heading = 1. Standardised assessment aids
+index-download-standardised = Download ({ $label }, Standardised)
+standard-chart-letters = Letters
+standard-chart-symbols = Symbols
+index-randomised-heading = 2. Randomised assessment aids
+index-randomised-intro = <span style="font-weight: bold">Each time you need to repeat the test, click the correct link below to download a new version of the test sheet.</span> This means that the order of the letters will be changed each time you perform the test.
+index-telephone-heading = Telephone assessment
+index-telephone-intro = These assessment aids can be identified using a simple 6 digit code that can easily be given over the telephone. They do not contain as many random combinations of letters as the postal/self assessment tests, but there are expected to be enough combinations of letters to be sufficient for several years of weekly testing.
+index-download-letters-telephone = Download (Letters, Telephone)
+index-download-symbols-telephone = Download (Symbols, Telephone)
+index-self-heading = Postal/self assessment
+index-self-intro = These assessment aids have over a billion possible letter orderings. Each sheet is identified using a longer code (16 digits). This code contains information about the type of test being administered, the correct answers, and a code which allows us to check for errors.
+index-self-check = If you have been asked to check your own answers, please use <a href="/self-check">this page</a>. It asks you what you read on each row before showing you the answers.
+index-download-letters-self = Download (Letters, Self-Assessment)
+index-download-symbols-self = Download (Symbols, Self-Assessment)
+index-monitoring = If you are monitoring vision over time, you can <a href="/monitor">compare the results of previous tests</a> to see whether the vision has changed by more than the test can reliably measure.
+index-patient-download = <span style="font-weight: bold">Download the version of the test that your clinician tells you to, and print it.</span>
+index-patient-print = Make sure to select "Full Size" or "100%" in the print settings when you're printing.
+index-patient-step-1 = Check the chart size is correct by placing a bank card in the box on the second page. If you can hide the grey box but still see all of the black outline, the size is correct.
+index-patient-step-2 = Fix this chart to a wall using Blu-Tac, at about eye level
+index-patient-step-3 = Stand { $distance } cm away from the chart. (Use the string provided to measure this distance)
+index-patient-step-4 = Follow the instructions given by the clinician on the telephone
+index-clinician-intro = The basic structure of the test is the same, regardless of which option you choose.
+index-choosing-heading = 1. Choosing an assessment aid
+index-choosing-once = <span style="font-weight: bold">If you are testing a patient only once or twice:</span> you should use the standardised test downloads from above. These are always the same, so you can know the correct answer.
+index-choosing-telephone = <span style="font-weight: bold">If you are testing a patient multiple times AND checking their answers during the test using telephone/video call:</span> you or the patient should use the telephone assessment from above. Using this assessment, the patient can give you a 6 character code over the phone, which you can enter into <a href="/answers">this web page</a> to view the correct answers. This allows you to have the correct answers in front of you when the patient is reading the chart over the phone.
+index-choosing-self = <span style="font-weight: bold">If you are testing a patient multiple times AND asking them to self-check their answers, or post their answers in:</span> you should use the self-assessment test downloads from above. Each test is identified by a 16 character code. This code allows you to identify the correct answers. To view the correct answers from a code, you should enter the code into <a href="/answers">this web page</a>.
+index-using-heading = 2. Using the assessment aid
+index-using-distance = You should typically administer the test from { $distance } cm, providing the patient with a pre-measured string to use for measuring this distance.
+index-using-lines = At { $distance } cm, the lines equate to 3/60, 6/60, 6/30, 6/15, 6/7.5.
+index-using-190 = At 190cm, the lines equate to 6/96, 6/48, 6/24, 6/12, 6/6.
+index-using-string = If different parts of your clinic use different distances for testing, it is recommended that you colour code the string given to patients, using one colour per distance. This should be checked before each trial.
+index-answers-heading = 3. Viewing answer sheets
+index-answers-intro = To mark answers from a patient, please use the link below and enter their test code to view the correct answers.
+index-answers-button = View answer sheets
+index-scoring-heading = 4. Scoring tests
+index-feedback-email = Your email:
+index-feedback-name = Your name:
+index-feedback-message = Your message:
+index-feedback-send = Send
+index-privacy-intro = We are committed to respecting your privacy. We collect only the reasonable minimum of data which allows us to understand the impact of this website ("the service") and monitor for abuse. This means that:
+index-privacy-cookies = The service does not use cookies, except to keep clinicians signed in to the stored results on servers which keep them.
+index-privacy-personal = The service does not collect any personal information such as names, addresses, and email addresses.
+index-privacy-downloads = When an assessment aid is downloaded, the service stores three pieces of information: (a) a unique identifier (the IP address of the user), (b) the type of sheet which is downloaded, and (c) the date and time of the download. This is to help detect abuse of the service, and is not shared with any third parties.
+index-privacy-aggregated = Aggregated, non-identifying, usage data (for example, the number of users using the service in a particular time period) may be shared privately or publicly. The reasons for this include, but are by no means limited to: (a) understanding how the service is used, (b) monitoring and sharing the impact of the service, and (c) understanding how to improve the service.
+index-privacy-formspree = The service uses Formspree.io to process submissions on the contact form. Submitting the contact form therefore agrees to their privacy policy, which can be found <a href="https://docs.formspree.io/files/privacy.pdf">here</a>.
+index-privacy-agreement = By using the service, you agree to the collection and use of information in accordance with this policy.
+
+## Answer sheet form
+
+code-form-invalid = The code that you entered was not valid. Please check it and try again.
+code-form-contact = If you believe this is an error, please contact the author quoting your code and error message below.
+code-form-heading = View an answer sheet
+code-form-intro = To view the correct answer for a particular test, please enter the test code below.
+code-form-lengths = The test code is either 6 characters long (e.g. RFD-CAM) or 16 characters long (FFT7-CVBJ-8ZV8-ALWE).
+code-form-code = Code:
+code-form-help = It doesn't matter whether or not you enter the dashes/spaces.
+code-form-submit = Submit
+
+## Scoring
+
+scoring-intro = To score the vision of your patient, please enter below the viewing distance used for the test (including the distance to and from any mirror), and the distance the chart was printed for. Then, enter the number of letters correctly read.
+scoring-number-read = Number of correctly read letters
+scoring-correct = Correct:
+scoring-submit = Score
+distances-viewing = Viewing distance (cm)
+distances-chart = Distance the chart was printed for (cm)
+distances-chart-help = This is shown at the top of the printed chart. Charts are printed for { $distance } cm unless another distance was chosen when downloading them.
+score-heading = Score a test
+score-not-a-test = This is not a vision test.
+score-code = Scoring the test with the code "{ $code }". To view the correct answers, <a href="{ $answers }">click here</a>.
+score-result = Correct score of { $correct } from { $distance } cm:
+score-letter-by-letter = Letter-by-letter score:
+score-missing-values = Error: Please enter the viewing distance and the number of correctly read optotypes.
+score-worse-than = Home-based estimation of vision was consistent with an acuity of less than { $acuity }
+score-at-least = Home-based estimation of vision was consistent with an acuity of at least { $acuity }
+score-between = Home-based estimation of vision was consistent with an acuity of at least { $acuity }, although not as good as { $next }
+
+## Self-checking
+
+self-check-heading = Check your answers
+self-check-intro = Use this page after you have read your chart. You will type what you read on each row of the chart, starting from the top, and the answers will only be shown once you have finished.
+self-check-code = Code (printed on your chart):
+self-check-start = Start
+self-check-short-code = Error: This code is for a test over the telephone, so the person testing you will check your answers.
+self-check-missing-distance = Error: Please enter the viewing distance.
+self-check-row = Row { $row } of { $rows }
+self-check-row-instructions = Look at row { $row } of your chart, and type what you see from left to right, with a space between each one. Type "-" for anything you cannot read.
+self-check-row-count = Number of symbols on this row: { $count }
+self-check-too-many = Error: You typed { $typed } answers, but this row only has { $count } symbols.
+self-check-names = You can also click on the name of a symbol to type it:
+self-check-next = Next row
+self-check-finish = I can't read any more
+self-check-results = Your results
+self-check-stopped = You stopped after row { $row }, so the rows below it were not read.
+self-check-again = Check another chart
+
+## Comparing tests over time, and the stored results
+
+change-overall-worsened = Worsened since the first test
+change-overall-improved = Improved since the first test
+change-overall-stable = Stable since the first test
+change-overall = The acuity changed by { $difference } logMAR between { $from } and { $to }.
+change-limit = Changes of { $limit } logMAR or less are within the test-retest variability of the chart, so are reported as stable.
+change-limit-label = Test-retest limit (logMAR)
+change-worsened = Worsened
+change-improved = Improved
+change-stable = Stable
+change-date = Date
+change-acuity = Acuity
+change-since-previous = Change since the previous test
+monitor-heading = Compare tests over time
+monitor-instructions = Enter the results of two or more tests of the same eye, with one test on each line. Each line should start with the date of the test (YYYY-MM-DD), followed by the acuity in logMAR (e.g. "0.3"), as a Snellen fraction (e.g. "6/12" or "20/40"), as a decimal acuity (e.g. "0.5 decimal"), as a Visual Acuity Rating (e.g. "85 VAR") or as an ETDRS letter score (e.g. "70 letters").
+monitor-eye = Eye
+monitor-right = Right
+monitor-left = Left
+monitor-both = Both
+monitor-results = Results
+monitor-submit = Compare
+patients-heading = Patients
+patients-intro = These patients have stored results. Choose a patient to see their results for each eye over time.
+patients-none = No results have been stored yet.
+patients-sign-out = Sign out
+patient-return = ← Return to the list of patients
+patient-heading = Results for { $patient }
+patient-limit = Changes of more than { $limit } logMAR since the previous test are highlighted. Smaller changes are within the test-retest variability of the chart, so are reported as stable.
+patient-right-eye = Right eye
+patient-left-eye = Left eye
+patient-both-eyes = Both eyes
+patient-chart = Chart
+patient-distance = Distance
+patient-correct = Correct
+patient-unscored = Worse than the top row
+patient-issued-heading = Charts issued
+patient-issued-date = Issued
+patient-eye = Eye
+patient-update = Update
+
+## Signing in to the stored results
+
+login-heading = Sign in to the stored results
+login-intro = Enter the results token to see the results stored for your patients. You will stay signed in until you close your browser.
+login-token = Results token:
+login-failed = That token was not right. Please check it and try again.
+login-submit = Sign in
+
+## Errors in codes, by the kind of error
+
+code-error-invalid-character = Error: The code contained characters which are not used in codes. Please check it and try again.
+code-error-invalid-length = Error: Invalid code length.
+code-error-out-of-range = Error: The code was entered incorrectly, as it is outside the allowed range.
+code-error-invalid-field = Error: The code was entered incorrectly, as it contains an unexpected value.
+code-error-crc-mismatch = Error: The code was entered incorrectly (the check digits do not match).
+code-error-optotype-out-of-range = Error: The code refers to a symbol which is not in its set of symbols.
+code-error-repeated-optotype = Error: The code places the same symbol more than once on a row.
+
+## Errors when scoring or marking a chart, and when comparing sessions
+
+scoring-error-too-many-correct = Error: The number of correctly read optotypes must be between 0 and { $optotypes } (inclusive).
+scoring-error-unexpected-layout = Error: The chart does not have the expected number of rows and optotypes.
+scoring-error-distance-out-of-range = Error: The viewing distance must be between { $min } cm and { $max } cm.
+scoring-error-missing-rows = Error: A score is needed for each row of the chart.
+scoring-error-too-many-correct-on-row = Error: Row { $row } only has { $optotypes } optotypes, but { $correct } were read correctly.
+scoring-error-too-many-rows = Error: Responses were given for { $responses } rows, but the chart only has { $rows } rows.
+scoring-error-too-many-responses = Error: Row { $row } only has { $optotypes } optotypes, but { $responses } responses were given.
+mark-missing-distance = Error: Please select the viewing distance.
+monitoring-error-unknown-eye = Error: The eye was not recognised.
+monitoring-error-too-few-sessions = Error: At least two sessions are needed to look for a change in vision.
+monitoring-error-invalid-limit = Error: The test-retest limit must be zero or more.
+monitoring-error-mixed-eyes = Error: All of the sessions must be for the same eye.
+monitoring-error-incomplete-line = Error: Line { $line } needs both a date and an acuity.
+monitoring-error-invalid-date = Error: Line { $line } does not start with a date in the format YYYY-MM-DD.
+monitoring-error-invalid-acuity = Error: The acuity on line { $line } was not recognised.
+
+## Error pages
+
+error-contact = If this keeps happening, please <a href="/#feedback">contact us</a>, quoting the error code <strong>{ $status }</strong> and the request id <code>{ $request_id }</code>.
+error-bad-request-title = Bad request
+error-bad-request = The request was not valid. If you followed a link to a chart, please check that the code is correct.
+error-unauthorized-title = Not authorised
+error-unauthorized = This page is only available to staff with an access token.
+error-not-found-title = Page not found
+error-not-found = The page you were looking for could not be found. It may have moved, or the link may be incomplete.
+error-unprocessable-title = Request could not be processed
+error-unprocessable = The request was understood, but some of the information in it was not valid.
+error-too-many-requests-title = Too many requests
+error-too-many-requests = Too many sheets have been requested from your address. Please wait a minute and try again.
+error-internal-title = Something went wrong
+error-internal = Something went wrong on our side while handling your request. Please try again later.
+
+## PDFs
+
+pdf-chart-title = Vision chart ({ $code })
+pdf-chart-printed-for = Printed for a viewing distance of { $distance } cm. Please print at 100% scale ("actual size").
+pdf-chart-range = From { $distance } cm, the rows cover { $top } to { $bottom }.
+pdf-chart-range-clamped = A chart for { $distance } cm does not fit on the page. From { $distance } cm, the rows cover { $top } to { $bottom }.
+pdf-chart-calibration = This line should measure { $length } cm. If it does not, the chart was not printed to scale.
+pdf-naming-title = Naming sheet ({ $optotypes })
+pdf-naming-instructions = Practise naming each of the symbols below before taking the test.
+pdf-answers-title = Answer key ({ $code })
+pdf-answers-instructions = This is the answer key for the chart, and should not be shown to the patient.
+pdf-answers-duplicate-rows = Warning: some rows of this chart are repeated, so it is easier to memorise than usual.
+pdf-answers-repeated-optotypes = Warning: some rows contain the same symbol more than once. Please check the code.
+pdf-answers-breaks-constraints = Warning: this chart places easily confused symbols next to each other, or has unbalanced rows.
+pdf-answers-row = Row { $row } - { $acuity } at { $distance } cm
@@ -0,0 +1,234 @@
+# Polish messages (polski). Any message missing from this catalogue is shown in English. These translations
+# should be checked by a native speaker before they are relied upon in clinics.
+
+## Shared by every page
+
+site-title = Domowy test ostrości wzroku
+site-heading = Pomoc w ocenie widzenia do dali w domu
+return-home = ← Powrót do strony głównej
+language-name = Polski
+
+## Homepage
+
+index-intro = Celem projektu „Pomoc w ocenie widzenia do dali w domu” jest opracowanie dokładnego i taniego sposobu pomiaru wzroku w domu. Obecnie jest to narzędzie badawcze – należy pamiętać, że nie jest to jeszcze zwalidowany test wzroku. Z tej strony należy korzystać tylko wtedy, gdy zostało się do niej skierowanym.
+index-contents = Spis treści
+index-about = O projekcie
+index-downloads = Do pobrania
+index-patient-instructions = Instrukcje dla pacjentów
+index-clinician-instructions = Instrukcje dla lekarzy
+index-feedback-link = Opinie/pytania
+index-feedback = Opinie i pytania
+index-references = Bibliografia
+index-privacy-policy = Polityka prywatności
+index-about-1 = Pomiar widzenia do dali w domu jest trudny i istnieje niewiele klinicznie zwalidowanych i wiarygodnych metod jego wykonania. Rozwiązania cyfrowe, w tym aplikacje na smartfony i tablety, są potencjalnym rozwiązaniem, ale grożą wykluczeniem osób, które nie mają dostępu do tych urządzeń. Naszym celem jest ostatecznie stworzenie testu wzroku, który pacjent może wydrukować na papierze A4 lub który placówka opieki może wydrukować i wysłać pacjentowi pocztą.
+index-about-2 = Należy pamiętać, że testy, które można wygenerować za pomocą tej strony, są w fazie rozwoju i nie zostały zwalidowane do użytku klinicznego. Obecnie badamy, na ile powtarzalny jest test na różnych drukarkach, a następnie chcemy ustalić, w jakim stopniu wzrok zmierzony w ten sposób odpowiada wzrokowi zmierzonemu tradycyjnymi testami. Będziemy wdzięczni za informacje o tym, na ile stały jest rozmiar wydruku testu, poprzez porównanie konturu na stronie 2 wydruku z kartą płatniczą.
+index-about-3 = Jeśli zdecydują się Państwo użyć tych wydruków na przykład podczas konsultacji wideo, prosimy traktować je wyłącznie jako pomoc, podobnie jak prośbę skierowaną w gabinecie do pacjenta o przeczytanie tekstu w celu oceny jego widzenia. <span style="font-weight: bold">Nie są to zwalidowane testy ostrości wzroku.</span>
+index-downloads-intro = Ta strona umożliwia wygenerowanie dwóch różnych pomocy:
+index-downloads-standardised = test standardowy - prosty plik do pobrania, który za każdym razem jest taki sam. Docelowo takie podejście będzie przydatne u pacjentów wykonujących test tylko raz lub dwa razy,
+index-downloads-randomised = <span style="font-style: italic">oraz;</span> test losowy może być przydatny u pacjentów regularnie monitorujących wzrok, aby wspomnienia poprzednich odczytów nie wpływały na ich ocenę.
+index-downloads-symbols = Jeśli osoba wykonująca test nie potrafi wiarygodnie rozpoznawać liter, należy pobrać test z symbolami. Korzystając z testu z symbolami, należy przećwiczyć symbole przy użyciu <a href="/naming-sheet/aukland" target="_blank">tego dokumentu.</a>
+index-standardised-heading = 1. Standardowe pomoce do oceny
+index-download-standardised = Pobierz ({ $label }, standardowy)
+standard-chart-letters = Litery
+standard-chart-symbols = Symbole
+index-randomised-heading = 2. Losowe pomoce do oceny
+index-randomised-intro = <span style="font-weight: bold">Za każdym razem, gdy trzeba powtórzyć test, kliknij odpowiedni link poniżej, aby pobrać nową wersję arkusza testowego.</span> Dzięki temu kolejność liter będzie się zmieniać przy każdym wykonaniu testu.
+index-telephone-heading = Ocena telefoniczna
+index-telephone-intro = Te pomoce do oceny można zidentyfikować za pomocą prostego 6-znakowego kodu, który łatwo podać przez telefon. Nie zawierają tylu losowych kombinacji liter co testy pocztowe/do samodzielnej oceny, ale oczekuje się, że kombinacji wystarczy na kilka lat cotygodniowych badań.
+index-download-letters-telephone = Pobierz (litery, telefoniczny)
+index-download-symbols-telephone = Pobierz (symbole, telefoniczny)
+index-self-heading = Ocena pocztowa/samodzielna
+index-self-intro = Te pomoce do oceny mają ponad miliard możliwych układów liter. Każdy arkusz jest identyfikowany dłuższym kodem (16 znaków). Kod ten zawiera informacje o rodzaju testu, poprawnych odpowiedziach oraz kod pozwalający nam wykryć błędy.
+index-self-check = Jeśli poproszono Cię o samodzielne sprawdzenie odpowiedzi, skorzystaj z <a href="/self-check">tej strony</a>. Najpierw zapyta, co odczytałeś/-aś w każdym rzędzie, a dopiero potem pokaże odpowiedzi.
+index-download-letters-self = Pobierz (litery, samodzielna ocena)
+index-download-symbols-self = Pobierz (symbole, samodzielna ocena)
+index-monitoring = Jeśli monitorujesz wzrok w czasie, możesz <a href="/monitor">porównać wyniki poprzednich testów</a>, aby sprawdzić, czy wzrok zmienił się bardziej, niż test jest w stanie wiarygodnie zmierzyć.
+index-patient-download = <span style="font-weight: bold">Pobierz wersję testu wskazaną przez lekarza i wydrukuj ją.</span>
+index-patient-print = Podczas drukowania wybierz w ustawieniach drukowania opcję „Pełny rozmiar” lub „100%”.
+index-patient-step-1 = Sprawdź, czy rozmiar tablicy jest prawidłowy, kładąc kartę bankową w ramce na drugiej stronie. Jeśli karta zakrywa szarą ramkę, a cały czarny kontur jest nadal widoczny, rozmiar jest prawidłowy.
+index-patient-step-2 = Przymocuj tablicę do ściany masą mocującą (np. Blu-Tack), mniej więcej na wysokości oczu
+index-patient-step-3 = Stań { $distance } cm od tablicy. (Do odmierzenia tej odległości użyj dołączonego sznurka)
+index-patient-step-4 = Postępuj zgodnie z instrukcjami podanymi przez lekarza przez telefon
+index-clinician-intro = Podstawowa struktura testu jest taka sama niezależnie od wybranej opcji.
+index-choosing-heading = 1. Wybór pomocy do oceny
+index-choosing-once = <span style="font-weight: bold">Jeśli badasz pacjenta tylko raz lub dwa razy:</span> użyj standardowych testów do pobrania powyżej. Są one zawsze takie same, więc możesz znać poprawną odpowiedź.
+index-choosing-telephone = <span style="font-weight: bold">Jeśli badasz pacjenta wielokrotnie I sprawdzasz jego odpowiedzi w trakcie testu podczas rozmowy telefonicznej/wideo:</span> ty lub pacjent powinniście użyć powyższej oceny telefonicznej. W tej ocenie pacjent może podać ci przez telefon 6-znakowy kod, który możesz wpisać na <a href="/answers">tej stronie</a>, aby zobaczyć poprawne odpowiedzi. Dzięki temu masz przed sobą poprawne odpowiedzi, gdy pacjent odczytuje tablicę przez telefon.
+index-choosing-self = <span style="font-weight: bold">Jeśli badasz pacjenta wielokrotnie I prosisz go o samodzielne sprawdzenie odpowiedzi lub przesłanie ich pocztą:</span> użyj powyższych testów do samodzielnej oceny. Każdy test jest identyfikowany 16-znakowym kodem. Kod ten pozwala ustalić poprawne odpowiedzi. Aby zobaczyć poprawne odpowiedzi dla kodu, wpisz go na <a href="/answers">tej stronie</a>.
+index-using-heading = 2. Korzystanie z pomocy do oceny
+index-using-distance = Test należy zazwyczaj przeprowadzać z odległości { $distance } cm, dając pacjentowi odmierzony wcześniej sznurek do odmierzenia tej odległości.
+index-using-lines = Z odległości { $distance } cm rzędy odpowiadają 3/60, 6/60, 6/30, 6/15, 6/7,5.
+index-using-190 = Z odległości 190 cm rzędy odpowiadają 6/96, 6/48, 6/24, 6/12, 6/6.
+index-using-string = Jeśli różne części placówki stosują różne odległości badania, zaleca się oznaczanie kolorami sznurka przekazywanego pacjentom, używając jednego koloru dla każdej odległości. Należy to sprawdzić przed każdym badaniem.
+index-answers-heading = 3. Przeglądanie arkuszy odpowiedzi
+index-answers-intro = Aby ocenić odpowiedzi pacjenta, skorzystaj z poniższego linku i wpisz kod jego testu, aby zobaczyć poprawne odpowiedzi.
+index-answers-button = Zobacz arkusze odpowiedzi
+index-scoring-heading = 4. Punktacja testów
+index-feedback-email = Twój e-mail:
+index-feedback-name = Twoje imię:
+index-feedback-message = Twoja wiadomość:
+index-feedback-send = Wyślij
+index-privacy-intro = Szanujemy Twoją prywatność. Zbieramy jedynie rozsądne minimum danych, które pozwala nam zrozumieć wpływ tej strony („usługi”) i wykrywać nadużycia. Oznacza to, że:
+index-privacy-cookies = Usługa nie używa plików cookie, z wyjątkiem utrzymania zalogowania klinicystów do zapisanych wyników na serwerach, które je przechowują.
+index-privacy-personal = Usługa nie zbiera żadnych danych osobowych, takich jak imiona i nazwiska, adresy czy adresy e-mail.
+index-privacy-downloads = Po pobraniu pomocy do oceny usługa zapisuje trzy informacje: (a) unikalny identyfikator (adres IP użytkownika), (b) rodzaj pobranego arkusza oraz (c) datę i godzinę pobrania. Ma to pomóc w wykrywaniu nadużyć usługi i nie jest udostępniane żadnym stronom trzecim.
+index-privacy-aggregated = Zagregowane, nieidentyfikujące dane o użytkowaniu (na przykład liczba użytkowników korzystających z usługi w danym okresie) mogą być udostępniane prywatnie lub publicznie. Powody obejmują między innymi: (a) zrozumienie sposobu korzystania z usługi, (b) monitorowanie i przedstawianie wpływu usługi oraz (c) zrozumienie, jak usługę ulepszyć.
+index-privacy-formspree = Usługa korzysta z Formspree.io do obsługi formularza kontaktowego. Wysłanie formularza kontaktowego oznacza zatem akceptację ich polityki prywatności, którą można znaleźć <a href="https://docs.formspree.io/files/privacy.pdf">tutaj</a>.
+index-privacy-agreement = Korzystając z usługi, wyrażasz zgodę na zbieranie i wykorzystywanie informacji zgodnie z niniejszą polityką.
+
+## Answer sheet form
+
+code-form-invalid = Wprowadzony kod jest nieprawidłowy. Sprawdź go i spróbuj ponownie.
+code-form-contact = Jeśli uważasz, że to błąd, skontaktuj się z autorem, podając swój kod i poniższy komunikat o błędzie.
+code-form-heading = Zobacz arkusz odpowiedzi
+code-form-intro = Aby zobaczyć poprawne odpowiedzi dla danego testu, wpisz poniżej kod testu.
+code-form-lengths = Kod testu ma 6 znaków (np. RFD-CAM) lub 16 znaków (FFT7-CVBJ-8ZV8-ALWE).
+code-form-code = Kod:
+code-form-help = Nie ma znaczenia, czy wpiszesz myślniki/spacje.
+code-form-submit = Wyślij
+
+## Scoring
+
+scoring-intro = Aby ocenić wzrok pacjenta, wpisz poniżej odległość, z której wykonano test (wliczając odległość do lustra i z powrotem, jeśli było używane), oraz odległość, dla której wydrukowano tablicę. Następnie wpisz liczbę poprawnie odczytanych liter.
+scoring-number-read = Liczba poprawnie odczytanych liter
+scoring-correct = Poprawne:
+scoring-submit = Oceń
+distances-viewing = Odległość badania (cm)
+distances-chart = Odległość, dla której wydrukowano tablicę (cm)
+distances-chart-help = Jest ona podana u góry wydrukowanej tablicy. Tablice są drukowane dla { $distance } cm, chyba że przy pobieraniu wybrano inną odległość.
+score-heading = Oceń test
+score-not-a-test = To nie jest test wzroku.
+score-code = Ocena testu o kodzie „{ $code }”. Aby zobaczyć poprawne odpowiedzi, <a href="{ $answers }">kliknij tutaj</a>.
+score-result = Wynik dla { $correct } poprawnych odpowiedzi z odległości { $distance } cm:
+score-letter-by-letter = Wynik litera po literze:
+score-missing-values = Błąd: Wpisz odległość badania i liczbę poprawnie odczytanych optotypów.
+score-worse-than = Domowa ocena wzroku wskazuje na ostrość wzroku mniejszą niż { $acuity }
+score-at-least = Domowa ocena wzroku wskazuje na ostrość wzroku co najmniej { $acuity }
+score-between = Domowa ocena wzroku wskazuje na ostrość wzroku co najmniej { $acuity }, choć nie tak dobrą jak { $next }
+
+## Self-checking
+
+self-check-heading = Sprawdź swoje odpowiedzi
+self-check-intro = Skorzystaj z tej strony po odczytaniu tablicy. Wpiszesz, co odczytałeś/-aś w każdym rzędzie tablicy, zaczynając od góry, a odpowiedzi zostaną pokazane dopiero na końcu.
+self-check-code = Kod (wydrukowany na tablicy):
+self-check-start = Rozpocznij
+self-check-short-code = Błąd: Ten kod służy do badania przez telefon, więc odpowiedzi sprawdzi osoba, która Cię bada.
+self-check-missing-distance = Błąd: Wpisz odległość badania.
+self-check-row = Rząd { $row } z { $rows }
+self-check-row-instructions = Spójrz na rząd { $row } tablicy i wpisz, co widzisz, od lewej do prawej, oddzielając znaki spacjami. Wpisz „-” w miejscu znaku, którego nie możesz odczytać.
+self-check-row-count = Liczba znaków w tym rzędzie: { $count }
+self-check-too-many = Błąd: Wpisano tyle odpowiedzi: { $typed }, ale liczba znaków w tym rzędzie to tylko { $count }.
+self-check-names = Możesz też kliknąć nazwę znaku, aby ją wpisać:
+self-check-next = Następny rząd
+self-check-finish = Nie mogę odczytać więcej
+self-check-results = Twoje wyniki
+self-check-stopped = Zakończono po rzędzie { $row }, więc rzędy poniżej nie zostały odczytane.
+self-check-again = Sprawdź inną tablicę
+
+## Comparing tests over time, and the stored results
+
+change-overall-worsened = Pogorszenie od pierwszego badania
+change-overall-improved = Poprawa od pierwszego badania
+change-overall-stable = Bez zmian od pierwszego badania
+change-overall = Ostrość wzroku zmieniła się o { $difference } logMAR między { $from } a { $to }.
+change-limit = Zmiany o { $limit } logMAR lub mniej mieszczą się w zmienności test-retest tablicy, więc są oznaczane jako brak zmian.
+change-limit-label = Limit test-retest (logMAR)
+change-worsened = Pogorszenie
+change-improved = Poprawa
+change-stable = Bez zmian
+change-date = Data
+change-acuity = Ostrość wzroku
+change-since-previous = Zmiana od poprzedniego badania
+monitor-heading = Porównaj badania w czasie
+monitor-instructions = Wpisz wyniki dwóch lub więcej badań tego samego oka, po jednym badaniu w każdym wierszu. Każdy wiersz powinien zaczynać się od daty badania (RRRR-MM-DD), a po niej ostrości wzroku w logMAR (np. "0.3"), jako ułamka Snellena (np. "6/12" lub "20/40"), jako ostrości dziesiętnej (np. "0.5 decimal"), jako Visual Acuity Rating (np. "85 VAR") lub jako wyniku liter ETDRS (np. "70 letters").
+monitor-eye = Oko
+monitor-right = Prawe
+monitor-left = Lewe
+monitor-both = Oba
+monitor-results = Wyniki
+monitor-submit = Porównaj
+patients-heading = Pacjenci
+patients-intro = Ci pacjenci mają zapisane wyniki. Wybierz pacjenta, aby zobaczyć jego wyniki dla każdego oka w czasie.
+patients-none = Nie zapisano jeszcze żadnych wyników.
+patients-sign-out = Wyloguj się
+patient-return = ← Powrót do listy pacjentów
+patient-heading = Wyniki pacjenta { $patient }
+patient-limit = Wyróżniono zmiany większe niż { $limit } logMAR od poprzedniego badania. Mniejsze zmiany mieszczą się w zmienności test-retest tablicy, więc są oznaczane jako brak zmian.
+patient-right-eye = Prawe oko
+patient-left-eye = Lewe oko
+patient-both-eyes = Oba oczy
+patient-chart = Tablica
+patient-distance = Odległość
+patient-correct = Poprawne
+patient-unscored = Gorzej niż górny wiersz
+patient-issued-heading = Wydane tablice
+patient-issued-date = Data wydania
+patient-eye = Oko
+patient-update = Aktualizuj
+
+## Signing in to the stored results
+
+login-heading = Logowanie do zapisanych wyników
+login-intro = Wpisz token wyników, aby zobaczyć wyniki zapisane dla Twoich pacjentów. Pozostaniesz zalogowany do zamknięcia przeglądarki.
+login-token = Token wyników:
+login-failed = Ten token jest nieprawidłowy. Sprawdź go i spróbuj ponownie.
+login-submit = Zaloguj się
+
+## Errors in codes, by the kind of error
+
+code-error-invalid-character = Błąd: Kod zawiera znaki, które nie są używane w kodach. Sprawdź go i spróbuj ponownie.
+code-error-invalid-length = Błąd: Nieprawidłowa długość kodu.
+code-error-out-of-range = Błąd: Kod został wpisany nieprawidłowo, ponieważ jest poza dozwolonym zakresem.
+code-error-invalid-field = Błąd: Kod został wpisany nieprawidłowo, ponieważ zawiera nieoczekiwaną wartość.
+code-error-crc-mismatch = Błąd: Kod został wpisany nieprawidłowo (cyfry kontrolne się nie zgadzają).
+code-error-optotype-out-of-range = Błąd: Kod odnosi się do symbolu, którego nie ma w jego zestawie symboli.
+code-error-repeated-optotype = Błąd: Kod umieszcza ten sam symbol więcej niż raz w jednym rzędzie.
+
+## Errors when scoring or marking a chart, and when comparing sessions
+
+scoring-error-too-many-correct = Błąd: Liczba poprawnie odczytanych optotypów musi wynosić od 0 do { $optotypes } (włącznie).
+scoring-error-unexpected-layout = Błąd: Tablica nie ma oczekiwanej liczby rzędów i optotypów.
+scoring-error-distance-out-of-range = Błąd: Odległość badania musi wynosić od { $min } cm do { $max } cm.
+scoring-error-missing-rows = Błąd: Wynik jest potrzebny dla każdego rzędu tablicy.
+scoring-error-too-many-correct-on-row = Błąd: Liczba optotypów w rzędzie { $row }: { $optotypes }, a liczba poprawnie odczytanych: { $correct }.
+scoring-error-too-many-rows = Błąd: Liczba rzędów z odpowiedziami: { $responses }, a liczba rzędów tablicy: { $rows }.
+scoring-error-too-many-responses = Błąd: Liczba optotypów w rzędzie { $row }: { $optotypes }, a liczba odpowiedzi: { $responses }.
+mark-missing-distance = Błąd: Wpisz odległość badania.
+monitoring-error-unknown-eye = Błąd: Nie rozpoznano oka.
+monitoring-error-too-few-sessions = Błąd: Aby sprawdzić, czy wzrok się zmienił, potrzebne są co najmniej dwa badania.
+monitoring-error-invalid-limit = Błąd: Granica powtarzalności testu musi wynosić zero lub więcej.
+monitoring-error-mixed-eyes = Błąd: Wszystkie badania muszą dotyczyć tego samego oka.
+monitoring-error-incomplete-line = Błąd: Wiersz { $line } musi zawierać datę i ostrość wzroku.
+monitoring-error-invalid-date = Błąd: Wiersz { $line } nie zaczyna się od daty w formacie RRRR-MM-DD.
+monitoring-error-invalid-acuity = Błąd: Nie rozpoznano ostrości wzroku w wierszu { $line }.
+
+## Error pages
+
+error-contact = Jeśli problem będzie się powtarzał, <a href="/#feedback">skontaktuj się z nami</a>, podając kod błędu <strong>{ $status }</strong> i identyfikator żądania <code>{ $request_id }</code>.
+error-bad-request-title = Nieprawidłowe żądanie
+error-bad-request = Żądanie było nieprawidłowe. Jeśli kliknięto link do tablicy, sprawdź, czy kod jest poprawny.
+error-unauthorized-title = Brak autoryzacji
+error-unauthorized = Ta strona jest dostępna tylko dla personelu z tokenem dostępu.
+error-not-found-title = Nie znaleziono strony
+error-not-found = Nie udało się znaleźć szukanej strony. Mogła zostać przeniesiona lub link może być niekompletny.
+error-unprocessable-title = Nie można przetworzyć żądania
+error-unprocessable = Żądanie zostało zrozumiane, ale część zawartych w nim informacji była nieprawidłowa.
+error-too-many-requests-title = Zbyt wiele żądań
+error-too-many-requests = Z Twojego adresu zażądano zbyt wielu arkuszy. Poczekaj minutę i spróbuj ponownie.
+error-internal-title = Coś poszło nie tak
+error-internal = Podczas obsługi żądania wystąpił błąd po naszej stronie. Spróbuj ponownie później.
+
+## PDFs
+
+pdf-chart-title = Tablica do badania wzroku ({ $code })
+pdf-chart-printed-for = Wydrukowano dla odległości badania { $distance } cm. Drukuj w skali 100% („rzeczywisty rozmiar”).
+pdf-chart-range = Z odległości { $distance } cm rzędy obejmują od { $top } do { $bottom }.
+pdf-chart-range-clamped = Tablica dla { $distance } cm nie mieści się na stronie. Z odległości { $distance } cm rzędy obejmują od { $top } do { $bottom }.
+pdf-chart-calibration = Ta linia powinna mieć { $length } cm. Jeśli nie ma, tablica nie została wydrukowana w skali.
+pdf-naming-title = Arkusz do nazywania ({ $optotypes })
+pdf-naming-instructions = Przed wykonaniem testu przećwicz nazywanie każdego z poniższych symboli.
+pdf-answers-title = Klucz odpowiedzi ({ $code })
+pdf-answers-instructions = To jest klucz odpowiedzi do tablicy i nie należy go pokazywać pacjentowi.
+pdf-answers-duplicate-rows = Uwaga: niektóre rzędy tej tablicy się powtarzają, więc łatwiej ją zapamiętać niż zwykle.
+pdf-answers-repeated-optotypes = Uwaga: niektóre rzędy zawierają ten sam symbol więcej niż raz. Sprawdź kod.
+pdf-answers-breaks-constraints = Uwaga: ta tablica umieszcza obok siebie łatwo mylone symbole lub ma niezrównoważone rzędy.
+pdf-answers-row = Rząd { $row } - { $acuity } z odległości { $distance } cm
@@ -0,0 +1,216 @@
+use std::fmt;
+use std::error::Error;
+use std::str::FromStr;
+use schemars::JsonSchema;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+
+/// The preferred numbers (the Renard R10 series) which give the conventional Snellen denominators for
+/// each 0.1 logMAR step within a decade - for example 6/6, 6/7.5, 6/9.6, 6/12 and so on.
+const SNELLEN_SERIES: [f64; 10] = [1.0, 1.25, 1.6, 2.0, 2.5, 3.2, 4.0, 5.0, 6.3, 8.0];
+
+/// Snellen numerators for metric (6/x) and imperial (20/x) fractions
+pub const METRIC_SNELLEN_NUMERATOR: f64 = 6.0;
+pub const IMPERIAL_SNELLEN_NUMERATOR: f64 = 20.0;
+
+/// Visual Acuity Rating and ETDRS letter scores both count 50 letters per logMAR unit, starting from 100
+/// and 85 letters respectively at 0.0 logMAR.
+const LETTERS_PER_LOG_MAR: f64 = 50.0;
+const VAR_AT_ZERO_LOG_MAR: f64 = 100.0;
+const ETDRS_LETTERS_AT_ZERO_LOG_MAR: f64 = 85.0;
+
+/// A visual acuity, which can be converted between the different units used by clinicians. Acuities
+/// are stored in logMAR to the nearest 0.01, which is finer than any chart can measure, so that
+/// converting to another unit and back again gives the same acuity.
+///
+/// When serialised, every unit is included, so that exported scores can be read without converting them.
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy, Serialize, Deserialize)]
+#[serde(into = "AcuityRecord", from = "AcuityRecord")]
+pub struct Acuity {
+    log_mar: f64
+}
+
+impl Acuity {
+    pub fn from_log_mar(log_mar: f64) -> Acuity {
+        Acuity { log_mar: round_to_hundredths(log_mar) }
+    }
+
+    /// Creates an acuity from a Snellen fraction, such as 6/12 or 20/40
+    pub fn from_snellen(numerator: f64, denominator: f64) -> Result<Acuity, AcuityError> {
+        if numerator.is_nan() || denominator.is_nan() || numerator <= 0.0 || denominator <= 0.0 {
+            return Err(AcuityError(format!("{}/{} is not a valid Snellen fraction.", numerator, denominator)));
+        }
+        Ok(Acuity::from_log_mar((denominator / numerator).log10()))
+    }
+
+    /// Creates an acuity from a decimal acuity, where 1.0 is equivalent to 6/6
+    pub fn from_decimal(decimal: f64) -> Result<Acuity, AcuityError> {
+        if decimal.is_nan() || decimal <= 0.0 {
+            return Err(AcuityError(format!("{} is not a valid decimal acuity.", decimal)));
+        }
+        Ok(Acuity::from_log_mar(-decimal.log10()))
+    }
+
+    /// Creates an acuity from a Visual Acuity Rating, where 100 is equivalent to 0.0 logMAR
+    pub fn from_visual_acuity_rating(rating: f64) -> Acuity {
+        Acuity::from_log_mar((VAR_AT_ZERO_LOG_MAR - rating) / LETTERS_PER_LOG_MAR)
+    }
+
+    /// Creates an acuity from an ETDRS letter score, where 85 letters is equivalent to 0.0 logMAR
+    pub fn from_etdrs_letters(letters: u32) -> Acuity {
+        Acuity::from_log_mar((ETDRS_LETTERS_AT_ZERO_LOG_MAR - f64::from(letters)) / LETTERS_PER_LOG_MAR)
+    }
+
+    pub fn log_mar(&self) -> f64 {
+        self.log_mar
+    }
+
+    /// The decimal acuity, rounded to two decimal places
+    pub fn decimal(&self) -> f64 {
+        round_to_hundredths(10_f64.powf(-self.log_mar))
+    }
+
+    pub fn visual_acuity_rating(&self) -> f64 {
+        round_to_hundredths(VAR_AT_ZERO_LOG_MAR - LETTERS_PER_LOG_MAR * self.log_mar)
+    }
+
+    /// The ETDRS letter score, rounded to a whole number of letters. Acuities worse than the top of
+    /// an ETDRS chart (1.7 logMAR) have a score of zero.
+    pub fn etdrs_letters(&self) -> u32 {
+        (ETDRS_LETTERS_AT_ZERO_LOG_MAR - LETTERS_PER_LOG_MAR * self.log_mar).round().max(0.0) as u32
+    }
+
+    /// The acuity as a metric Snellen fraction (e.g. "6/7.5")
+    pub fn snellen_metric(&self) -> String {
+        self.snellen(METRIC_SNELLEN_NUMERATOR)
+    }
+
+    /// The acuity as an imperial Snellen fraction (e.g. "20/25")
+    pub fn snellen_imperial(&self) -> String {
+        self.snellen(IMPERIAL_SNELLEN_NUMERATOR)
+    }
+
+    /// Formats the acuity as a Snellen fraction with the given numerator. Multiples of 0.1 logMAR use the
+    /// conventional denominators printed on charts (e.g. 6/7.5 and 20/400), and other values are rounded to
+    /// two significant figures.
+    pub fn snellen(&self, numerator: f64) -> String {
+        let denominator = if is_whole_line(self.log_mar) {
+            let steps = (self.log_mar * 10.0).round();
+            let decade = (steps / 10.0).floor();
+            let nominal = numerator * SNELLEN_SERIES[(steps - decade * 10.0) as usize] * 10_f64.powf(decade);
+            // Charts print small denominators to one decimal place (e.g. 6/3.8), and larger ones as whole numbers
+            if nominal < 10.0 { (nominal * 10.0).round() / 10.0 } else { nominal.round() }
+        } else {
+            let exact = numerator * 10_f64.powf(self.log_mar);
+            let magnitude = 10_f64.powf(exact.log10().floor() - 1.0);
+            (exact / magnitude).round() * magnitude
+        };
+        format!("{}/{}", numerator, round_to_hundredths(denominator))
+    }
+}
+
+/// Acuities are displayed in logMAR, to one decimal place when they are a whole line of a standard chart
+/// (e.g. "0.0 logMAR"), and otherwise to two decimal places.
+impl fmt::Display for Acuity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if is_whole_line(self.log_mar) {
+            write!(f, "{:.1} logMAR", self.log_mar)
+        } else {
+            write!(f, "{:.2} logMAR", self.log_mar)
+        }
+    }
+}
+
+/// Parses an acuity entered by a clinician. The unit is identified from the format of the text:
+/// "6/12" and "20/40" are Snellen fractions, "0.5 decimal" is a decimal acuity, "85 VAR" is a Visual
+/// Acuity Rating, "70 letters" is an ETDRS letter score, and "0.3 logMAR" (or just "0.3") is logMAR.
+impl FromStr for Acuity {
+    type Err = AcuityError;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let invalid = || AcuityError(format!("\"{}\" is not a recognised acuity.", text));
+        let parse_number = |number: &str| number.trim().parse::<f64>().map_err(|_| invalid());
+        if let Some(slash) = text.find('/') {
+            return Acuity::from_snellen(parse_number(&text[..slash])?, parse_number(&text[slash + 1..])?);
+        }
+        let lowercase = text.to_lowercase();
+        let (number, unit) = match lowercase.find(|c: char| c.is_alphabetic()) {
+            Some(index) => (&lowercase[..index], lowercase[index..].trim()),
+            None => (lowercase.as_str(), "logmar")
+        };
+        let number = parse_number(number)?;
+        match unit {
+            "logmar" => Ok(Acuity::from_log_mar(number)),
+            "decimal" => Acuity::from_decimal(number),
+            "var" => Ok(Acuity::from_visual_acuity_rating(number)),
+            "letters" | "etdrs" if number >= 0.0 && number.fract() == 0.0 => Ok(Acuity::from_etdrs_letters(number as u32)),
+            _ => Err(invalid())
+        }
+    }
+}
+
+/// Acuities are described in API schemas by their serialised form
+impl JsonSchema for Acuity {
+    fn schema_name() -> String {
+        "Acuity".to_string()
+    }
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        AcuityRecord::json_schema(gen)
+    }
+}
+
+/// The serialised form of an Acuity, which includes every unit. Only the logMAR value is used when
+/// deserialising, because the other units are derived from it.
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct AcuityRecord {
+    log_mar: f64,
+    #[serde(default)]
+    snellen_metric: String,
+    #[serde(default)]
+    snellen_imperial: String,
+    #[serde(default)]
+    decimal: f64,
+    #[serde(default)]
+    visual_acuity_rating: f64,
+    #[serde(default)]
+    etdrs_letters: u32
+}
+
+impl From<Acuity> for AcuityRecord {
+    fn from(acuity: Acuity) -> Self {
+        AcuityRecord {
+            log_mar: acuity.log_mar(),
+            snellen_metric: acuity.snellen_metric(),
+            snellen_imperial: acuity.snellen_imperial(),
+            decimal: acuity.decimal(),
+            visual_acuity_rating: acuity.visual_acuity_rating(),
+            etdrs_letters: acuity.etdrs_letters()
+        }
+    }
+}
+
+impl From<AcuityRecord> for Acuity {
+    fn from(record: AcuityRecord) -> Self {
+        Acuity::from_log_mar(record.log_mar)
+    }
+}
+
+/// Whether a logMAR value falls exactly on one of the 0.1 logMAR lines of a standard chart
+fn is_whole_line(log_mar: f64) -> bool {
+    ((log_mar * 10.0).round() - log_mar * 10.0).abs() < 1e-6
+}
+
+/// Rounds to two decimal places, also turning -0.0 into 0.0 so that it is displayed without a sign
+pub fn round_to_hundredths(x: f64) -> f64 {
+    (x * 100.0).round() / 100.0 + 0.0
+}
+
+/// This struct is used to represent errors when converting or parsing acuities.
+#[derive(Debug)]
+pub struct AcuityError(pub String);
+impl fmt::Display for AcuityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error: {}", self.0)
+    }
+}
+impl Error for AcuityError {}
@@ -0,0 +1,497 @@
+use std::str::FromStr;
+use rocket::{Route, State};
+use rocket::http::{Status, ContentType};
+use rocket::response::{status, content::Content};
+use rocket_contrib::json::Json;
+use chrono::{NaiveDateTime, Utc};
+use schemars::JsonSchema;
+use schemars::gen::SchemaGenerator;
+
+use crate::acuity::Acuity;
+use crate::audit::AuditLog;
+use crate::auth::{ResultsAccess, StatisticsAccess};
+use crate::config::{AppConfig, StandardChart};
+use crate::metrics;
+use crate::monitoring::Eye;
+use crate::codes::{NUM_ROWS, CodeError, CodeErrorKind, CodeKind, TestMode, short::ShortCode, long::LongCode};
+use crate::openapi::{ApiRoute, OpenApiDocument, OperationDescription, empty_response, parameter, response};
+use crate::optotypes::{OptotypeArrangement, OptotypeDefinition};
+use crate::results::{IssuedChart, PatientId, ResultRepository, StoredResult, TestResult};
+use crate::scoring::{self, Score};
+use crate::statistics::{self, Granularity, UsageStatistics, MIN_REPORTED_COUNT};
+
+/// A code which has been decoded into the chart it represents, along with the details needed to
+/// use the chart without decoding the code again.
+#[derive(Serialize, JsonSchema)]
+pub struct DecodedCode {
+    pub kind: CodeKind,
+    pub version: u8,
+    /// The name of the optotype definition used by the chart (e.g. "sloan")
+    pub optotype_set: String,
+    /// The acuity equivalent of each row (from the top of the chart down) when a chart printed for the
+    /// server's default distance is read from that distance
+    pub row_acuity: Vec<Acuity>,
+    pub arrangement: OptotypeArrangement
+}
+
+/// The body returned by the API when a request fails, so that integrations can tell what went wrong
+/// without parsing the message. For errors in codes, `error` is the name of the CodeErrorKind.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ApiError {
+    pub error: String,
+    pub message: String
+}
+
+/// A request to issue a new randomised chart
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct IssueRequest {
+    pub mode: TestMode,
+    /// The name of the optotype definition to use (e.g. "sloan"). The server's default optotype set is used if
+    /// this is left out.
+    #[serde(default)]
+    pub optotypes: Option<String>,
+    /// The patient the chart is for. If this is given (which needs the results token), the chart is recorded with
+    /// the patient's results, and the eye must be given too.
+    #[serde(default)]
+    pub patient: Option<String>,
+    #[serde(default)]
+    pub eye: Option<Eye>,
+    /// The viewing distance (in cm) which the chart should be printed for. The server's default distance is used if
+    /// this is left out.
+    #[serde(default)]
+    pub distance: Option<f64>
+}
+
+/// Links to the pages for a chart, relative to the root of the site
+#[derive(Serialize, JsonSchema)]
+pub struct ChartLinks {
+    pub pdf: String,
+    pub answers: String
+}
+
+/// A newly issued chart
+#[derive(Serialize, JsonSchema)]
+pub struct IssuedCode {
+    pub code: String,
+    pub kind: CodeKind,
+    pub arrangement: OptotypeArrangement,
+    pub links: ChartLinks
+}
+
+/// Codes which can't be read at all are bad requests, but codes which are well formed and yet don't
+/// describe a valid chart (such as those with the wrong CRC) are unprocessable.
+pub fn code_error_status(kind: CodeErrorKind) -> Status {
+    match kind {
+        CodeErrorKind::InvalidCharacter | CodeErrorKind::InvalidLength => Status::BadRequest,
+        _ => Status::UnprocessableEntity
+    }
+}
+
+/// The response for a code which couldn't be decoded, with the status given by `code_error_status`
+pub fn code_error_response(e: CodeError) -> status::Custom<Json<ApiError>> {
+    status::Custom(code_error_status(e.kind), Json(ApiError { error: e.kind.as_str().to_string(), message: e.message }))
+}
+
+/// Decodes a code, working out its kind and version along the way, and the acuity of each row for a chart
+/// printed for and read from the given distance (in cm). This is shared by the codes API and the JSON version
+/// of the answer sheet. In lenient mode, long codes which fail validation are decoded anyway, with the
+/// problems given as warnings on the arrangement (see `LongCode::from_str_lenient`).
+pub fn decode(code: &str, distance: f64, lenient: bool) -> Result<DecodedCode, CodeError> {
+    decode_code(code, distance, lenient).inspect_err(metrics::record_code_error)
+}
+
+fn decode_code(code: &str, distance: f64, lenient: bool) -> Result<DecodedCode, CodeError> {
+    let (kind, version, arrangement) = match CodeKind::from_code(code)? {
+        CodeKind::Short => {
+            let short_code = ShortCode::from_str(code)?;
+            (CodeKind::Short, bool::from(short_code.version) as u8, OptotypeArrangement::from(short_code))
+        },
+        CodeKind::Long => {
+            let long_code = if lenient { LongCode::from_str_lenient(code)?.0 } else { LongCode::from_str(code)? };
+            (CodeKind::Long, u16::from(long_code.version) as u8, OptotypeArrangement::from(long_code))
+        }
+    };
+    Ok(DecodedCode {
+        kind,
+        version,
+        optotype_set: arrangement.optotype_definition.name.clone(),
+        row_acuity: (0..NUM_ROWS).map(|row| scoring::get_row_acuity(row, distance, distance)).collect(),
+        arrangement
+    })
+}
+
+/// Decode a short or long code into the arrangement of optotypes which it represents. With `lenient=true`, a long
+/// code which repeats an optotype on a row is decoded with a warning, rather than rejected.
+#[get("/codes/<code>?<lenient>")]
+pub fn code(code: String, lenient: Option<bool>, app_config: State<AppConfig>) -> Result<Json<DecodedCode>, status::Custom<Json<ApiError>>> {
+    decode(&code, app_config.default_distance, lenient.unwrap_or(false)).map(Json).map_err(code_error_response)
+}
+
+/// The code route in the OpenAPI document
+fn describe_code(gen: &mut SchemaGenerator) -> OperationDescription {
+    OperationDescription {
+        summary: "Decode a short or long code into the arrangement of optotypes it represents",
+        parameters: vec![
+            parameter::<String>(gen, "code", true, "The code, with or without dashes"),
+            parameter::<bool>(gen, "lenient", false, "Decode long codes which repeat an optotype on a row, with a warning, rather than rejecting them")
+        ],
+        request_body: None,
+        responses: vec![
+            response::<DecodedCode>(gen, 200, "The decoded code"),
+            response::<ApiError>(gen, 400, "The code could not be read, because it has invalid characters or the wrong length"),
+            response::<ApiError>(gen, 422, "The code was read, but does not describe a valid chart (e.g. the CRC does not match)")
+        ]
+    }
+}
+
+/// Issue a new randomised chart, using a short code for telephone tests and a long code for self-assessment.
+/// The response links to the decoded code, as well as to the PDF and answer sheet for the chart.
+#[post("/codes", format = "json", data = "<request>")]
+pub fn issue_code(request: Json<IssueRequest>, access: Option<ResultsAccess>, repository: Option<State<ResultRepository>>, app_config: State<AppConfig>) -> Result<status::Created<Json<IssuedCode>>, status::Custom<Json<ApiError>>> {
+    let unprocessable = |error: &str, message: String| status::Custom(Status::UnprocessableEntity, Json(ApiError { error: error.to_string(), message }));
+    let distance = request.distance.unwrap_or(app_config.default_distance);
+    app_config.check_distance(distance).map_err(|e| unprocessable("InvalidDistance", e.message))?;
+    // Charts are only recorded for a patient when results are being stored, by a client allowed to see them
+    let recipient = match &request.patient {
+        Some(patient) => {
+            let repository = repository.ok_or_else(no_result_store)?;
+            access.ok_or_else(results_unauthorized)?;
+            let patient = PatientId::from_str(patient).map_err(|e| unprocessable("InvalidPatient", e.0))?;
+            let eye = request.eye.ok_or_else(|| unprocessable("MissingEye", "The eye which the chart is for must be given with the patient.".to_string()))?;
+            Some((repository, patient, eye))
+        },
+        None => None
+    };
+    let optotypes = request.optotypes.as_deref().unwrap_or(&app_config.default_optotypes);
+    let optotype_definition = match OptotypeDefinition::from_name(optotypes) {
+        Some(definition) => definition,
+        None => { return Err(unprocessable("UnknownOptotypeSet", format!("There is no optotype set called \"{}\".", optotypes))); }
+    };
+    let (kind, code) = match request.mode.generate_code(optotype_definition) {
+        Ok(generated) => generated,
+        Err(e) => { return Err(status::Custom(Status::InternalServerError, Json(ApiError { error: "GenerationFailed".to_string(), message: e.0 }))); }
+    };
+    let arrangement = OptotypeArrangement::from_code(&code).inspect_err(metrics::record_code_error).map_err(code_error_response)?;
+    if let Some((repository, patient, eye)) = recipient {
+        repository.record_issue(&IssuedChart::new(patient, &arrangement, eye, distance)).map_err(|e| result_store_error(e.0))?;
+    }
+    metrics::metrics().codes_issued.with_label_values(&[&request.mode.to_string()]).inc();
+    // The chart is printed for the default distance unless another distance was asked for
+    let pdf = match request.distance {
+        Some(distance) => format!("/generate?code={}&distance={}", code, distance),
+        None => format!("/generate?code={}", code)
+    };
+    let issued = IssuedCode {
+        links: ChartLinks {
+            pdf,
+            answers: format!("/answers?code={}", code)
+        },
+        code,
+        kind,
+        arrangement
+    };
+    Ok(status::Created(format!("/api/v1/codes/{}", issued.code), Some(Json(issued))))
+}
+
+/// The issue_code route in the OpenAPI document
+fn describe_issue_code(gen: &mut SchemaGenerator) -> OperationDescription {
+    OperationDescription {
+        summary: "Issue a new randomised chart",
+        parameters: Vec::new(),
+        request_body: Some(gen.subschema_for::<IssueRequest>()),
+        responses: vec![
+            response::<IssuedCode>(gen, 201, "The newly issued chart"),
+            response::<ApiError>(gen, 401, "A patient was given, but the request doesn't have the results token"),
+            response::<ApiError>(gen, 404, "A patient was given, but results are not being stored by this server"),
+            response::<ApiError>(gen, 422, "The optotype set does not exist, the distance is out of range, or the patient or eye is not valid"),
+            response::<ApiError>(gen, 500, "No chart satisfying the generation constraints could be found")
+        ]
+    }
+}
+
+/// A standardised chart, with links to its PDF and answer sheet
+#[derive(Serialize, JsonSchema)]
+pub struct StandardChartListing {
+    #[serde(flatten)]
+    pub chart: StandardChart,
+    pub links: ChartLinks
+}
+
+/// List the standardised charts which this server is configured with, which are the same every time they are downloaded
+#[get("/standard-charts")]
+pub fn standard_charts(app_config: State<AppConfig>) -> Json<Vec<StandardChartListing>> {
+    Json(app_config.standard_charts.iter()
+        .map(|chart| StandardChartListing {
+            links: ChartLinks {
+                pdf: format!("/standard/{}", chart.name),
+                answers: format!("/answers?code={}", chart.code)
+            },
+            chart: chart.clone()
+        })
+        .collect())
+}
+
+/// The standard_charts route in the OpenAPI document
+fn describe_standard_charts(gen: &mut SchemaGenerator) -> OperationDescription {
+    OperationDescription {
+        summary: "List the standardised charts, which are the same every time they are downloaded",
+        parameters: Vec::new(),
+        request_body: None,
+        responses: vec![response::<Vec<StandardChartListing>>(gen, 200, "The standardised charts")]
+    }
+}
+
+/// Score a test from the code of the chart, the viewing distance (in cm) and the number of
+/// optotypes which were read correctly. Returns the same score as is shown on the score page.
+/// The chart is assumed to have been printed for the server's default distance unless `chart_distance` is given.
+#[get("/score?<code>&<distance>&<correct>&<chart_distance>")]
+pub fn score(code: String, distance: f64, correct: u32, chart_distance: Option<f64>, app_config: State<AppConfig>) -> Result<Json<Score>, status::Custom<Json<ApiError>>> {
+    let arrangement = OptotypeArrangement::from_code(&code).inspect_err(metrics::record_code_error).map_err(code_error_response)?;
+    scoring::score_arrangement(&arrangement, distance, chart_distance.unwrap_or(app_config.default_distance), correct)
+        .map(Json)
+        .map_err(|e| status::Custom(Status::UnprocessableEntity, Json(ApiError { error: "InvalidScore".to_string(), message: e.message })))
+}
+
+/// The score route in the OpenAPI document
+fn describe_score(gen: &mut SchemaGenerator) -> OperationDescription {
+    OperationDescription {
+        summary: "Score a test from the number of optotypes which were read correctly",
+        parameters: vec![
+            parameter::<String>(gen, "code", true, "The code of the chart which was read"),
+            parameter::<f64>(gen, "distance", true, "The viewing distance (in cm)"),
+            parameter::<u32>(gen, "correct", true, "The number of optotypes which were read correctly"),
+            parameter::<f64>(gen, "chart_distance", false, "The viewing distance (in cm) which the chart was printed for, if it was not the standard distance")
+        ],
+        request_body: None,
+        responses: vec![
+            response::<Score>(gen, 200, "The score"),
+            response::<ApiError>(gen, 400, "The code could not be read, because it has invalid characters or the wrong length"),
+            response::<ApiError>(gen, 422, "The code does not describe a valid chart, or the distance or number of correct optotypes is impossible")
+        ]
+    }
+}
+
+/// A request to store the result of a test for a patient
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ResultRequest {
+    /// The pseudonymous identifier of the patient (e.g. "P-0017"), which must not be their name or hospital number
+    pub patient: String,
+    /// The code of the chart which was read
+    pub code: String,
+    pub eye: Eye,
+    /// The viewing distance (in cm)
+    pub distance: f64,
+    /// The viewing distance (in cm) which the chart was printed for. The server's default distance is used if this is left out.
+    #[serde(default)]
+    pub chart_distance: Option<f64>,
+    /// The number of optotypes which were read correctly
+    pub correct: u32,
+    /// When the test was done (in UTC). The time the result is stored is used if this is left out.
+    #[serde(default)]
+    pub tested_at: Option<NaiveDateTime>
+}
+
+fn no_result_store() -> status::Custom<Json<ApiError>> {
+    let message = "Results are not being stored by this server.".to_string();
+    status::Custom(Status::NotFound, Json(ApiError { error: "NoResultStore".to_string(), message }))
+}
+
+fn results_unauthorized() -> status::Custom<Json<ApiError>> {
+    let message = "The results token must be given (as \"Authorization: Bearer <token>\") to store or read results.".to_string();
+    status::Custom(Status::Unauthorized, Json(ApiError { error: "Unauthorized".to_string(), message }))
+}
+
+fn result_store_error(message: String) -> status::Custom<Json<ApiError>> {
+    status::Custom(Status::InternalServerError, Json(ApiError { error: "ResultStoreError".to_string(), message }))
+}
+
+/// Score a test and store the result for a patient, so that it can be compared with their other results
+#[post("/results", format = "json", data = "<request>")]
+pub fn record_result(request: Json<ResultRequest>, _access: ResultsAccess, repository: Option<State<ResultRepository>>, app_config: State<AppConfig>) -> Result<status::Created<Json<StoredResult>>, status::Custom<Json<ApiError>>> {
+    let repository = repository.ok_or_else(no_result_store)?;
+    let unprocessable = |error: &str, message: String| status::Custom(Status::UnprocessableEntity, Json(ApiError { error: error.to_string(), message }));
+    let patient = PatientId::from_str(&request.patient).map_err(|e| unprocessable("InvalidPatient", e.0))?;
+    let arrangement = OptotypeArrangement::from_code(&request.code).inspect_err(metrics::record_code_error).map_err(code_error_response)?;
+    let chart_distance = request.chart_distance.unwrap_or(app_config.default_distance);
+    let score = scoring::score_arrangement(&arrangement, request.distance, chart_distance, request.correct)
+        .map_err(|e| unprocessable("InvalidScore", e.message))?;
+    let tested_at = request.tested_at.unwrap_or_else(|| Utc::now().naive_utc());
+    let stored = repository.record(TestResult::new(patient, &arrangement, request.eye, &score, tested_at))
+        .map_err(|e| result_store_error(e.0))?;
+    Ok(status::Created(format!("/api/v1/patients/{}/results", stored.result.patient), Some(Json(stored))))
+}
+
+/// The record_result route in the OpenAPI document
+fn describe_record_result(gen: &mut SchemaGenerator) -> OperationDescription {
+    OperationDescription {
+        summary: "Score a test and store the result for a patient",
+        parameters: Vec::new(),
+        request_body: Some(gen.subschema_for::<ResultRequest>()),
+        responses: vec![
+            response::<StoredResult>(gen, 201, "The stored result"),
+            response::<ApiError>(gen, 400, "The code could not be read"),
+            response::<ApiError>(gen, 401, "The request doesn't have the results token"),
+            response::<ApiError>(gen, 404, "Results are not being stored by this server"),
+            response::<ApiError>(gen, 422, "The patient identifier, code, distance or number of correct optotypes is not valid")
+        ]
+    }
+}
+
+/// List the patients who have at least one stored result
+#[get("/patients")]
+pub fn patients(_access: ResultsAccess, repository: Option<State<ResultRepository>>) -> Result<Json<Vec<PatientId>>, status::Custom<Json<ApiError>>> {
+    let repository = repository.ok_or_else(no_result_store)?;
+    repository.patients().map(Json).map_err(|e| result_store_error(e.0))
+}
+
+/// The patients route in the OpenAPI document
+fn describe_patients(gen: &mut SchemaGenerator) -> OperationDescription {
+    OperationDescription {
+        summary: "List the pseudonymous identifiers of the patients with stored results",
+        parameters: Vec::new(),
+        request_body: None,
+        responses: vec![
+            response::<Vec<PatientId>>(gen, 200, "The patients"),
+            response::<ApiError>(gen, 401, "The request doesn't have the results token"),
+            response::<ApiError>(gen, 404, "Results are not being stored by this server")
+        ]
+    }
+}
+
+/// List the stored results for a patient, in the order the tests were done
+#[get("/patients/<patient>/results")]
+pub fn patient_results(patient: String, _access: ResultsAccess, repository: Option<State<ResultRepository>>) -> Result<Json<Vec<StoredResult>>, status::Custom<Json<ApiError>>> {
+    let repository = repository.ok_or_else(no_result_store)?;
+    let patient = PatientId::from_str(&patient)
+        .map_err(|e| status::Custom(Status::BadRequest, Json(ApiError { error: "InvalidPatient".to_string(), message: e.0 })))?;
+    repository.results_for(&patient).map(Json).map_err(|e| result_store_error(e.0))
+}
+
+/// The patient_results route in the OpenAPI document
+fn describe_patient_results(gen: &mut SchemaGenerator) -> OperationDescription {
+    OperationDescription {
+        summary: "List the stored results for a patient, in the order the tests were done",
+        parameters: vec![parameter::<String>(gen, "patient", true, "The pseudonymous identifier of the patient")],
+        request_body: None,
+        responses: vec![
+            response::<Vec<StoredResult>>(gen, 200, "The patient's results, which is empty if none have been stored"),
+            response::<ApiError>(gen, 400, "The patient identifier is not valid"),
+            response::<ApiError>(gen, 401, "The request doesn't have the results token"),
+            response::<ApiError>(gen, 404, "Results are not being stored by this server")
+        ]
+    }
+}
+
+/// List the charts which have been issued to a patient through the API, in the order they were issued
+#[get("/patients/<patient>/charts")]
+pub fn patient_charts(patient: String, _access: ResultsAccess, repository: Option<State<ResultRepository>>) -> Result<Json<Vec<IssuedChart>>, status::Custom<Json<ApiError>>> {
+    let repository = repository.ok_or_else(no_result_store)?;
+    let patient = PatientId::from_str(&patient)
+        .map_err(|e| status::Custom(Status::BadRequest, Json(ApiError { error: "InvalidPatient".to_string(), message: e.0 })))?;
+    repository.issued_to(&patient).map(Json).map_err(|e| result_store_error(e.0))
+}
+
+/// The patient_charts route in the OpenAPI document
+fn describe_patient_charts(gen: &mut SchemaGenerator) -> OperationDescription {
+    OperationDescription {
+        summary: "List the charts which have been issued to a patient, in the order they were issued",
+        parameters: vec![parameter::<String>(gen, "patient", true, "The pseudonymous identifier of the patient")],
+        request_body: None,
+        responses: vec![
+            response::<Vec<IssuedChart>>(gen, 200, "The charts issued to the patient, which is empty if none have been issued"),
+            response::<ApiError>(gen, 400, "The patient identifier is not valid"),
+            response::<ApiError>(gen, 401, "The request doesn't have the results token"),
+            response::<ApiError>(gen, 404, "Results are not being stored by this server")
+        ]
+    }
+}
+
+/// The usage statistics, in whichever format was asked for
+#[derive(Responder)]
+pub enum StatisticsResponse {
+    Json(Json<UsageStatistics>),
+    Csv(Content<String>)
+}
+
+/// Aggregated, non-identifying counts of downloads for each finished week (or each day of them, with
+/// `granularity=day`), by type of sheet, optotype set and mode. Small counts are suppressed. The statistics are
+/// returned as JSON, or as CSV with `format=csv`, to clients with the statistics token.
+#[get("/statistics?<format>&<granularity>")]
+pub fn statistics(format: Option<String>, granularity: Option<String>, _access: StatisticsAccess, log: Option<State<AuditLog>>) -> Result<StatisticsResponse, status::Custom<Json<ApiError>>> {
+    let bad_request = |error: &str, message: String| status::Custom(Status::BadRequest, Json(ApiError { error: error.to_string(), message }));
+    let csv = match format.as_deref() {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(format) => { return Err(bad_request("InvalidFormat", format!("\"{}\" is not a recognised format (use json or csv).", format))); }
+    };
+    let granularity = match granularity.as_deref() {
+        None | Some("week") => Granularity::Week,
+        Some("day") => Granularity::Day,
+        Some(granularity) => { return Err(bad_request("InvalidGranularity", format!("\"{}\" is not a recognised granularity (use week or day).", granularity))); }
+    };
+    let log = match log {
+        Some(log) => log,
+        None => {
+            let message = "Downloads are not being recorded by this server.".to_string();
+            return Err(status::Custom(Status::NotFound, Json(ApiError { error: "NoDownloadLog".to_string(), message })));
+        }
+    };
+    let entries = log.entries().map_err(|e| status::Custom(Status::InternalServerError, Json(ApiError { error: "DownloadLogError".to_string(), message: e.0 })))?;
+    let statistics = statistics::aggregate(&entries, log.first_complete_day(), Utc::now().naive_utc().date(), MIN_REPORTED_COUNT, granularity);
+    if csv {
+        Ok(StatisticsResponse::Csv(Content(ContentType::CSV, statistics::to_csv(&statistics))))
+    } else {
+        Ok(StatisticsResponse::Json(Json(statistics)))
+    }
+}
+
+/// The statistics route in the OpenAPI document
+fn describe_statistics(gen: &mut SchemaGenerator) -> OperationDescription {
+    OperationDescription {
+        summary: "Aggregated, non-identifying counts of downloads for each week or day, with small counts suppressed",
+        parameters: vec![
+            parameter::<String>(gen, "format", false, "\"json\" (the default) or \"csv\""),
+            parameter::<String>(gen, "granularity", false, "\"week\" (the default) or \"day\"")
+        ],
+        request_body: None,
+        responses: vec![
+            response::<UsageStatistics>(gen, 200, "The usage statistics (as CSV if it was asked for)"),
+            response::<ApiError>(gen, 400, "The format or granularity is not recognised"),
+            response::<ApiError>(gen, 401, "The request doesn't have the statistics token (as \"Authorization: Bearer <token>\")"),
+            response::<ApiError>(gen, 404, "Downloads are not being recorded by this server")
+        ]
+    }
+}
+
+/// The OpenAPI document describing the API, which is generated from the API routes at startup
+#[get("/openapi.json")]
+pub fn openapi(document: State<OpenApiDocument>) -> Content<String> {
+    Content(ContentType::JSON, document.0.clone())
+}
+
+/// The openapi route in the OpenAPI document
+fn describe_openapi(_: &mut SchemaGenerator) -> OperationDescription {
+    OperationDescription {
+        summary: "This OpenAPI document",
+        parameters: Vec::new(),
+        request_body: None,
+        responses: vec![empty_response(200, "The OpenAPI document")]
+    }
+}
+
+/// Every API route, along with where it is mounted and its description in the OpenAPI document
+pub fn routes() -> Vec<ApiRoute> {
+    let api_route = |base: &'static str, routes: Vec<Route>, describe: fn(&mut SchemaGenerator) -> OperationDescription| ApiRoute { base, routes, describe };
+    vec![
+        api_route("/api/v1", routes![code], describe_code),
+        api_route("/api/v1", routes![issue_code], describe_issue_code),
+        api_route("/api/v1", routes![standard_charts], describe_standard_charts),
+        api_route("/api/v1", routes![score], describe_score),
+        api_route("/api/v1", routes![record_result], describe_record_result),
+        api_route("/api/v1", routes![patients], describe_patients),
+        api_route("/api/v1", routes![patient_results], describe_patient_results),
+        api_route("/api/v1", routes![patient_charts], describe_patient_charts),
+        api_route("/api/v1", routes![statistics], describe_statistics),
+        api_route("/api", routes![openapi], describe_openapi)
+    ]
+}
@@ -0,0 +1,391 @@
+use std::fmt;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use rusqlite::{Connection, params};
+use sha2::{Digest, Sha256};
+use rocket::request::{self, FromRequest, Request, State};
+use rocket::Outcome;
+
+use crate::codes::{CodeKind, TestMode};
+use crate::config::{self, AppConfig};
+use crate::optotypes::OptotypeArrangement;
+use crate::sqlite;
+
+/// Downloads are kept for this many days unless the retention period is configured
+pub const DEFAULT_RETENTION_DAYS: i64 = 90;
+
+/// Expired entries are purged at startup, and then at most once in this many hours while recording downloads
+const PURGE_INTERVAL_HOURS: i64 = 24;
+
+/// Stored in place of the client when the IP address of a download isn't known
+const UNKNOWN_CLIENT: &str = "unknown";
+
+/// Where the audit log is kept
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditStoreKind {
+    /// An SQLite database (see SqliteStore)
+    Sqlite,
+    /// A file with one JSON entry on each line (see FileStore)
+    File
+}
+
+impl AuditStoreKind {
+    /// Where the log is kept if the audit_path setting isn't given
+    pub fn default_path(self) -> &'static str {
+        match self {
+            AuditStoreKind::Sqlite => "audit.sqlite",
+            AuditStoreKind::File => "audit.log"
+        }
+    }
+}
+
+/// How much of the IP address of each download is kept
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpPrivacy {
+    /// The whole IP address is stored
+    Full,
+    /// The end of the address is removed (keeping the /24 network for IPv4 and the /48 network for IPv6)
+    Truncate,
+    /// A salted hash of the address is stored, so repeated downloads can be linked without storing the address
+    Hash
+}
+
+impl FromStr for IpPrivacy {
+    type Err = AuditError;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_lowercase().as_str() {
+            "full" => Ok(IpPrivacy::Full),
+            "truncate" => Ok(IpPrivacy::Truncate),
+            "hash" => Ok(IpPrivacy::Hash),
+            _ => Err(AuditError(format!("\"{}\" is not a recognised IP address setting (use full, truncate or hash).", text)))
+        }
+    }
+}
+
+/// A single download. This is all that the privacy policy allows to be stored.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: NaiveDateTime,
+    /// The IP address of the client, after truncation or hashing
+    pub client: String,
+    /// The type of sheet which was downloaded, with its optotype set and (for charts) the mode of test, e.g.
+    /// "chart/sloan/telephone" or "naming-sheet/aukland"
+    pub sheet: String
+}
+
+/// Somewhere to keep the audit log. New stores only need to implement this trait to be used by the server.
+pub trait AuditStore: Send + Sync {
+    fn record(&self, entry: &AuditEntry) -> Result<(), AuditError>;
+    /// Removes every entry from before the given time, returning the number of entries removed
+    fn purge(&self, before: NaiveDateTime) -> Result<usize, AuditError>;
+    /// Lists every entry, oldest first
+    fn entries(&self) -> Result<Vec<AuditEntry>, AuditError>;
+}
+
+fn sqlite_error(e: rusqlite::Error) -> AuditError {
+    AuditError(sqlite::error_message("audit", e))
+}
+
+/// Keeps the audit log in an SQLite database. This is the default store, and the database can be shared by several
+/// servers so that the statistics cover all of them.
+pub struct SqliteStore {
+    connection: Mutex<Connection>
+}
+
+impl SqliteStore {
+    /// Opens the audit log at the given path (audit_path), creating it if this is the first download
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<SqliteStore, AuditError> {
+        SqliteStore::from_connection(sqlite::open(Some(path.as_ref())).map_err(sqlite_error)?)
+    }
+
+    /// Creates an empty audit log which is lost when it is dropped, so that tests don't record their downloads
+    pub fn in_memory() -> Result<SqliteStore, AuditError> {
+        SqliteStore::from_connection(sqlite::open(None).map_err(sqlite_error)?)
+    }
+
+    fn from_connection(connection: Connection) -> Result<SqliteStore, AuditError> {
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS downloads (timestamp TEXT NOT NULL, client TEXT NOT NULL, sheet TEXT NOT NULL)",
+            params![]
+        ).map_err(sqlite_error)?;
+        Ok(SqliteStore { connection: Mutex::new(connection) })
+    }
+}
+
+impl AuditStore for SqliteStore {
+    fn record(&self, entry: &AuditEntry) -> Result<(), AuditError> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT INTO downloads (timestamp, client, sheet) VALUES (?1, ?2, ?3)",
+            params![sqlite::format_timestamp(entry.timestamp), entry.client, entry.sheet]
+        ).map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn purge(&self, before: NaiveDateTime) -> Result<usize, AuditError> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute("DELETE FROM downloads WHERE timestamp < ?1", params![sqlite::format_timestamp(before)])
+            .map_err(sqlite_error)
+    }
+
+    fn entries(&self) -> Result<Vec<AuditEntry>, AuditError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare("SELECT timestamp, client, sheet FROM downloads ORDER BY timestamp")
+            .map_err(sqlite_error)?;
+        let rows = statement.query_map(params![], |row| Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(sqlite_error)?;
+        let mut entries: Vec<AuditEntry> = Vec::new();
+        for row in rows {
+            let (timestamp, client, sheet) = row.map_err(sqlite_error)?;
+            let timestamp = match sqlite::parse_timestamp(&timestamp) {
+                Some(timestamp) => timestamp,
+                None => { return Err(AuditError(format!("The audit database contains an invalid timestamp ({}).", timestamp))); }
+            };
+            entries.push(AuditEntry { timestamp, client, sheet });
+        }
+        Ok(entries)
+    }
+}
+
+fn file_error(path: &Path, e: std::io::Error) -> AuditError {
+    AuditError(format!("Could not use the audit log file {} ({}).", path.display(), e))
+}
+
+/// Keeps the audit log in a text file, with one JSON entry on each line. This is for deployments where
+/// SQLite can't be used, and is rewritten in full when it is purged.
+pub struct FileStore {
+    path: PathBuf,
+    lock: Mutex<()>
+}
+
+impl FileStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<FileStore, AuditError> {
+        let path = path.as_ref().to_path_buf();
+        // Check that the file can be written now, rather than on the first download
+        OpenOptions::new().create(true).append(true).open(&path).map_err(|e| file_error(&path, e))?;
+        Ok(FileStore { path, lock: Mutex::new(()) })
+    }
+
+    fn read_entries(&self) -> Result<Vec<AuditEntry>, AuditError> {
+        let file = File::open(&self.path).map_err(|e| file_error(&self.path, e))?;
+        let mut entries: Vec<AuditEntry> = Vec::new();
+        for (line_number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| file_error(&self.path, e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => { return Err(AuditError(format!("Line {} of the audit log file is invalid ({}).", line_number + 1, e))); }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+impl AuditStore for FileStore {
+    fn record(&self, entry: &AuditEntry) -> Result<(), AuditError> {
+        let _lock = self.lock.lock().unwrap();
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path).map_err(|e| file_error(&self.path, e))?;
+        let line = serde_json::to_string(entry).map_err(|e| AuditError(format!("Could not serialise the audit entry ({}).", e)))?;
+        writeln!(file, "{}", line).map_err(|e| file_error(&self.path, e))
+    }
+
+    fn purge(&self, before: NaiveDateTime) -> Result<usize, AuditError> {
+        let _lock = self.lock.lock().unwrap();
+        let entries = self.read_entries()?;
+        let (kept, purged): (Vec<AuditEntry>, Vec<AuditEntry>) = entries.into_iter().partition(|entry| entry.timestamp >= before);
+        if purged.is_empty() {
+            return Ok(0);
+        }
+        // Write to a temporary file and then rename it, so that the log isn't lost if writing fails part way through
+        let temporary_path = self.path.with_extension("purging");
+        let mut contents = String::new();
+        for entry in &kept {
+            contents.push_str(&serde_json::to_string(entry).map_err(|e| AuditError(format!("Could not serialise the audit entry ({}).", e)))?);
+            contents.push('\n');
+        }
+        fs::write(&temporary_path, contents).map_err(|e| file_error(&temporary_path, e))?;
+        fs::rename(&temporary_path, &self.path).map_err(|e| file_error(&self.path, e))?;
+        Ok(purged.len())
+    }
+
+    fn entries(&self) -> Result<Vec<AuditEntry>, AuditError> {
+        let _lock = self.lock.lock().unwrap();
+        let mut entries = self.read_entries()?;
+        entries.sort_by_key(|entry| entry.timestamp);
+        Ok(entries)
+    }
+}
+
+/// The audit log of downloads, which is kept in Rocket's managed state. It anonymises IP addresses as
+/// configured before they are stored, and purges entries which are older than the retention period.
+pub struct AuditLog {
+    store: Box<dyn AuditStore>,
+    ip_privacy: IpPrivacy,
+    salt: String,
+    retention: Duration,
+    last_purge: Mutex<Option<NaiveDateTime>>
+}
+
+impl AuditLog {
+    pub fn new(store: Box<dyn AuditStore>, ip_privacy: IpPrivacy, salt: String, retention_days: i64) -> AuditLog {
+        AuditLog {
+            store,
+            ip_privacy,
+            salt,
+            retention: Duration::days(retention_days),
+            last_purge: Mutex::new(None)
+        }
+    }
+
+    /// Creates the audit log from the audit_* settings in the site configuration (which have already been checked).
+    /// Returns None if the audit log has been turned off with audit_log = "none". If no audit_ip_salt is set, a random
+    /// salt is chosen each time the server starts, so hashes can only be linked until the server is restarted.
+    pub fn from_config(app_config: &AppConfig) -> Result<Option<AuditLog>, AuditError> {
+        let kind = match app_config.audit_log {
+            Some(kind) => kind,
+            None => { return Ok(None); }
+        };
+        let path = app_config.audit_path.clone().unwrap_or_else(|| kind.default_path().to_string());
+        let store: Box<dyn AuditStore> = match kind {
+            AuditStoreKind::Sqlite => Box::new(SqliteStore::open(path)?),
+            AuditStoreKind::File => Box::new(FileStore::open(path)?)
+        };
+        let salt = app_config.audit_ip_salt.clone().unwrap_or_else(random_salt);
+        Ok(Some(AuditLog::new(store, app_config.audit_ip, salt, app_config.audit_retention_days)))
+    }
+
+    /// Converts an IP address into the form which is stored, according to the IP address setting
+    pub fn anonymise(&self, ip: Option<IpAddr>) -> String {
+        let ip = match ip {
+            Some(ip) => ip,
+            None => { return UNKNOWN_CLIENT.to_string(); }
+        };
+        match self.ip_privacy {
+            IpPrivacy::Full => ip.to_string(),
+            IpPrivacy::Truncate => truncate_ip(ip).to_string(),
+            IpPrivacy::Hash => hash_ip(&self.salt, ip)
+        }
+    }
+
+    /// Records a download of a sheet, purging old entries if they haven't been purged recently
+    pub fn record(&self, ip: Option<IpAddr>, sheet: &str) -> Result<(), AuditError> {
+        let now = Utc::now().naive_utc();
+        self.store.record(&AuditEntry { timestamp: now, client: self.anonymise(ip), sheet: sheet.to_string() })?;
+        let purge_due = match *self.last_purge.lock().unwrap() {
+            Some(last_purge) => now - last_purge > Duration::hours(PURGE_INTERVAL_HOURS),
+            None => true
+        };
+        if purge_due {
+            self.purge()?;
+        }
+        Ok(())
+    }
+
+    /// Removes every entry which is older than the retention period, returning the number of entries removed
+    pub fn purge(&self) -> Result<usize, AuditError> {
+        let now = Utc::now().naive_utc();
+        let purged = self.store.purge(now - self.retention)?;
+        *self.last_purge.lock().unwrap() = Some(now);
+        Ok(purged)
+    }
+
+    pub fn entries(&self) -> Result<Vec<AuditEntry>, AuditError> {
+        self.store.entries()
+    }
+
+    /// The first day which every entry is still kept for. Earlier days may have been partly purged.
+    pub fn first_complete_day(&self) -> NaiveDate {
+        (Utc::now().naive_utc() - self.retention).date() + Duration::days(1)
+    }
+}
+
+/// Describes a chart (or other sheet made from a code) for the audit log, as the type of sheet, the optotype set
+/// and the mode of test which the code is for (e.g. "chart/sloan/telephone")
+pub fn describe_sheet(sheet: &str, arrangement: &OptotypeArrangement) -> String {
+    match CodeKind::from_code(&arrangement.code) {
+        Ok(kind) => format!("{}/{}/{}", sheet, arrangement.optotype_definition.name, TestMode::from(kind)),
+        Err(_) => format!("{}/{}", sheet, arrangement.optotype_definition.name)
+    }
+}
+
+/// Hashes an IP address with a salt, giving an identifier which can't be turned back into the address
+/// without knowing the salt
+pub fn hash_ip(salt: &str, ip: IpAddr) -> String {
+    let digest = Sha256::digest(format!("{}{}", salt, ip).as_bytes());
+    let mut hash = String::new();
+    for byte in digest.iter().take(8) {
+        hash.push_str(&format!("{:02x}", byte));
+    }
+    hash
+}
+
+/// Chooses a salt for hashing IP addresses when one hasn't been configured
+pub fn random_salt() -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(32).collect()
+}
+
+/// Removes the part of an IP address which identifies the individual host
+pub fn truncate_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            IpAddr::V4(Ipv4Addr::new(octets[0], octets[1], octets[2], 0))
+        },
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            IpAddr::V6(Ipv6Addr::new(segments[0], segments[1], segments[2], 0, 0, 0, 0, 0))
+        }
+    }
+}
+
+/// A request guard for routes which serve downloads, which records them in the audit log (if there is one).
+/// The client's IP address is found by config::client_ip, so the X-Real-IP header is only used when the request
+/// comes from a trusted proxy.
+pub struct Auditor<'r> {
+    log: Option<State<'r, AuditLog>>,
+    ip: Option<IpAddr>
+}
+
+impl Auditor<'_> {
+    /// Records a download. Failing to record a download doesn't stop the sheet from being served, so errors
+    /// are only logged.
+    pub fn record(&self, sheet: &str) {
+        if let Some(log) = &self.log {
+            if let Err(e) = log.record(self.ip, sheet) {
+                log::error!("{}", e);
+            }
+        }
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Auditor<'r> {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        Outcome::Success(Auditor {
+            log: request.guard::<State<AuditLog>>().succeeded(),
+            ip: config::client_ip(request)
+        })
+    }
+}
+
+/// This struct is used to represent errors when recording or purging the audit log.
+#[derive(Debug)]
+pub struct AuditError(pub String);
+impl fmt::Display for AuditError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error: {}", self.0)
+    }
+}
+impl Error for AuditError {}
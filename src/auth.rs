@@ -0,0 +1,83 @@
+use rocket::http::{Cookie, SameSite, Status};
+use rocket::request::{self, FromRequest, Request, State};
+use rocket::Outcome;
+use sha2::{Digest, Sha256};
+
+use crate::config::AppConfig;
+
+/// Compares a token given by a client with the configured token. Both are hashed first, and the hashes are compared
+/// without stopping at the first difference, so that the time taken doesn't reveal how much of the token was right
+/// (or how long it is).
+pub fn tokens_match(given: &str, expected: &str) -> bool {
+    let given = Sha256::digest(given.as_bytes());
+    let expected = Sha256::digest(expected.as_bytes());
+    given.iter().zip(expected.iter()).fold(0, |difference, (a, b)| difference | (a ^ b)) == 0
+}
+
+/// The token in the request's "Authorization: Bearer <token>" header, if there is one
+pub fn bearer_token<'a>(request: &'a Request) -> Option<&'a str> {
+    request.headers().get_one("Authorization")
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// The private cookie which keeps a clinician signed in to the stored results, once they have given the results token
+/// to the login form. The cookie is encrypted with Rocket's secret_key, and holds the token itself so that changing
+/// the token signs everybody out.
+pub const RESULTS_COOKIE: &str = "results_session";
+
+/// The cookie which is set when a clinician signs in with the results token. It is only sent back to this site, and
+/// can't be read by scripts on the page.
+pub fn results_cookie(token: String) -> Cookie<'static> {
+    Cookie::build(RESULTS_COOKIE, token)
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Strict)
+        .finish()
+}
+
+/// Lets a request through if the given token matches the token chosen from the configuration. If no token has been
+/// configured, nobody is let through, so that a route can't be left open by forgetting to set one.
+fn check_token<T>(request: &Request, given: Option<&str>, access: T, token: fn(&AppConfig) -> Option<&String>) -> request::Outcome<T, ()> {
+    let expected = match request.guard::<State<AppConfig>>().succeeded() {
+        Some(app_config) => token(&app_config).cloned(),
+        None => None
+    };
+    match (given, expected) {
+        (Some(given), Some(expected)) if tokens_match(given, &expected) => Outcome::Success(access),
+        _ => Outcome::Failure((Status::Unauthorized, ()))
+    }
+}
+
+/// A request guard for the usage statistics, which are only given to clients with the statistics_token
+pub struct StatisticsAccess;
+
+impl<'a, 'r> FromRequest<'a, 'r> for StatisticsAccess {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        check_token(request, bearer_token(request), StatisticsAccess, |app_config| app_config.statistics_token.as_ref())
+    }
+}
+
+/// A request guard for the Prometheus metrics, which are only given to clients with the metrics_token
+pub struct MetricsAccess;
+
+impl<'a, 'r> FromRequest<'a, 'r> for MetricsAccess {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        check_token(request, bearer_token(request), MetricsAccess, |app_config| app_config.metrics_token.as_ref())
+    }
+}
+
+/// A request guard for the stored results, which are only given to clients with the results_token. Other systems send
+/// the token as "Authorization: Bearer <token>", and clinicians using the pages sign in with it at /login.
+pub struct ResultsAccess;
+
+impl<'a, 'r> FromRequest<'a, 'r> for ResultsAccess {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let session = request.cookies().get_private(RESULTS_COOKIE).map(|cookie| cookie.value().to_string());
+        let given = bearer_token(request).or(session.as_deref());
+        check_token(request, given, ResultsAccess, |app_config| app_config.results_token.as_ref())
+    }
+}
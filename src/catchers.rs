@@ -0,0 +1,164 @@
+use rand::Rng;
+use rocket::{Catcher, Data, Request, Response};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, MediaType};
+use rocket::response::{self, Responder};
+use rocket_contrib::json::Json;
+use rocket_contrib::templates::Template;
+
+use crate::api::ApiError;
+use crate::i18n::{self, Locale, Localisation};
+use crate::openapi::API_PATH;
+use crate::rate_limit;
+
+/// The header which carries the request id. A request id set by a reverse proxy is used if there is one, so
+/// that support queries can be matched up with the proxy's logs.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Request ids from the reverse proxy which are longer than this (or contain anything other than letters,
+/// numbers and dashes) are replaced, so that they are safe to display and log
+const MAX_REQUEST_ID_LENGTH: usize = 64;
+
+/// An id for each request, which is shown on error pages so that it can be quoted in support queries
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// The id of a request, which is chosen when the request arrives
+    pub fn of(request: &Request) -> String {
+        request.local_cache(|| RequestId::new(None)).0.clone()
+    }
+
+    fn new(from_proxy: Option<&str>) -> RequestId {
+        match from_proxy {
+            Some(id) if !id.is_empty() && id.len() <= MAX_REQUEST_ID_LENGTH
+                && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') => RequestId(id.to_string()),
+            _ => {
+                let mut rng = rand::thread_rng();
+                RequestId((0..16).map(|_| std::char::from_digit(rng.gen_range(0, 16), 16).unwrap()).collect())
+            }
+        }
+    }
+}
+
+/// Gives every request an id, and returns it in the X-Request-Id header of every response
+pub struct RequestIdFairing;
+
+impl Fairing for RequestIdFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request ids",
+            kind: Kind::Request | Kind::Response
+        }
+    }
+
+    fn on_request(&self, request: &mut Request, _: &Data) {
+        let from_proxy = request.headers().get_one(REQUEST_ID_HEADER).map(String::from);
+        request.local_cache(|| RequestId::new(from_proxy.as_deref()));
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        response.set_header(Header::new(REQUEST_ID_HEADER, RequestId::of(request)));
+    }
+}
+
+/// The context for the error page
+#[derive(Serialize)]
+struct ErrorContext {
+    status: u16,
+    title: String,
+    message: String,
+    request_id: String
+}
+
+/// The body returned to API clients for errors which weren't handled by a route. This is an ApiError, with
+/// the request id added.
+#[derive(Serialize)]
+pub struct CaughtError {
+    #[serde(flatten)]
+    pub error: ApiError,
+    pub request_id: String
+}
+
+/// An error page for browsers, or a structured error for API clients
+#[derive(Responder)]
+pub enum ErrorResponse {
+    Html(Template),
+    Json(Json<CaughtError>)
+}
+
+/// API clients are those requesting anything under the API path, or which prefer JSON to HTML
+fn wants_json(request: &Request) -> bool {
+    request.uri().path().starts_with(API_PATH)
+        || request.accept().map(|accept| accept.preferred().media_type() == &MediaType::JSON).unwrap_or(false)
+}
+
+/// Responds with the error page, with the title and message translated using the given message ids. Errors for
+/// API clients are always in English, like the errors returned by the API routes.
+fn respond(request: &Request, status: u16, error: &str, title_id: &str, message_id: &str) -> ErrorResponse {
+    let request_id = RequestId::of(request);
+    if wants_json(request) {
+        ErrorResponse::Json(Json(CaughtError {
+            error: ApiError { error: error.to_string(), message: i18n::translate(Locale::English, message_id, &[]) },
+            request_id
+        }))
+    } else {
+        // The Localisation guard never fails
+        let localisation = request.guard::<Localisation>().unwrap();
+        ErrorResponse::Html(localisation.render("error", ErrorContext {
+            status,
+            title: localisation.translate(title_id, &[]),
+            message: localisation.translate(message_id, &[]),
+            request_id
+        }))
+    }
+}
+
+#[catch(400)]
+fn bad_request(request: &Request) -> ErrorResponse {
+    respond(request, 400, "BadRequest", "error-bad-request-title", "error-bad-request")
+}
+
+#[catch(401)]
+fn unauthorized(request: &Request) -> ErrorResponse {
+    respond(request, 401, "Unauthorized", "error-unauthorized-title", "error-unauthorized")
+}
+
+#[catch(404)]
+fn not_found(request: &Request) -> ErrorResponse {
+    respond(request, 404, "NotFound", "error-not-found-title", "error-not-found")
+}
+
+#[catch(422)]
+fn unprocessable_entity(request: &Request) -> ErrorResponse {
+    respond(request, 422, "UnprocessableEntity", "error-unprocessable-title", "error-unprocessable")
+}
+
+/// The error for a client which has been rate limited, with a Retry-After header giving the number of seconds to wait
+pub struct RetryAfter(ErrorResponse, Option<u64>);
+
+impl<'r> Responder<'r> for RetryAfter {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+        let mut response = self.0.respond_to(request)?;
+        if let Some(seconds) = self.1 {
+            response.set_header(Header::new("Retry-After", seconds.to_string()));
+        }
+        Ok(response)
+    }
+}
+
+#[catch(429)]
+fn too_many_requests(request: &Request) -> RetryAfter {
+    RetryAfter(respond(request, 429, "TooManyRequests", "error-too-many-requests-title", "error-too-many-requests"), rate_limit::retry_after(request))
+}
+
+#[catch(500)]
+fn internal_server_error(request: &Request) -> ErrorResponse {
+    // The request id is logged, so that it can be found when it is quoted in a support query
+    log::error!("Internal server error for request {} ({} {})", RequestId::of(request), request.method(), request.uri());
+    respond(request, 500, "InternalServerError", "error-internal-title", "error-internal")
+}
+
+/// The catchers to register with Rocket
+pub fn catchers() -> Vec<Catcher> {
+    catchers![bad_request, unauthorized, not_found, unprocessable_entity, too_many_requests, internal_server_error]
+}
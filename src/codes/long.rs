@@ -1,5 +1,6 @@
 use crate::optotypes::{OptotypeDefinition, OptotypeArrangement};
-use crate::codes::{NUM_OPTOTYPES_ON_ROW, NUM_ROWS, crc, CodeError};
+use crate::codes::{NUM_OPTOTYPES_ON_ROW, crc, CodeError, CodeErrorKind};
+use crate::constraints::{self, GenerationError};
 use ux::{u2, u4};
 use std::str::FromStr;
 use std::str;
@@ -41,7 +42,9 @@ pub struct LongCode {
 /// This serialises a long code (string) representation of the values stored within this ShortCode object. 
 /// The convention is to format the field in blocks of four digits, because these are easier to remember and
 /// check when typing them in (see ShortCode ToString implementation for more discussion of this).
+#[allow(clippy::to_string_trait_impl)]
 impl ToString for LongCode {
+    #[allow(clippy::needless_return, clippy::useless_vec)]
     fn to_string(&self) -> String {
         // Join together the individual fields. The casts get a bit messy here - in the future, the author should review the cost vs benefits of using 
         // custom integer
@@ -58,48 +61,67 @@ impl ToString for LongCode {
 /// Parses long codes and extract the fields and OptotypeArrangement which is encoded within it.
 /// May return an error if the CRC fails or there are invalid characters. Code parsing is agnostic
 /// to the '-' characters within the code - a user can enter as many or as few as they wish.
+/// 
+/// Codes which pass the CRC are also validated, and rejected if they contain anything that
+/// `generate_random` could never have produced (see `LongCode::validate`).
 impl FromStr for LongCode {
     type Err = CodeError;
     fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let (processed_code, warnings) = LongCode::from_str_lenient(code)?;
+        match warnings.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(processed_code)
+        }
+    }
+}
+
+/// This parsing is slightly different to shortcodes because uses the concept of a header and data body, the latter
+/// of which can potentially have variable length, if the data type is changed from a u64 and the number of optotypes
+/// on each row is altered in the optotypes configuration file.
+impl LongCode {
+    /// Parses a long code in lenient mode. This is the same as parsing it with `from_str`, except that the
+    /// problems found by `validate` are returned alongside the code as warnings, rather than rejecting it.
+    /// Optotypes outside the optotype set are still rejected, because there is no way to display them.
+    pub fn from_str_lenient(code: &str) -> Result<(LongCode, Vec<CodeError>), CodeError> {
         // Convert the base-32 code into the number it encodes
         let num = match super::get_number_from_code(code.to_string()) {
             Some(binary) => { binary },
-            None => { return Err(CodeError("Failed to parse long code. It may have contained invalid characters.".into())); }
+            None => { return Err(CodeError::new(CodeErrorKind::InvalidCharacter, "Failed to parse long code. It may have contained invalid characters.")); }
         };
         // Check the number encoded: it shouldn't exceed 2^80 - 1 unless something has gone very wrong
         if num > 2_u128.pow(80) - 1 {
-            return Err(CodeError("The long code encoded a value which was outside the allowed range.".into()));
+            return Err(CodeError::new(CodeErrorKind::OutOfRange, "The long code encoded a value which was outside the allowed range."));
         }
         // Take out the fields
-        let version = ((0b11 << 16) & num) as u8;
-        let optotype_definition = OptotypeDefinition::from(((0b11 << 18) & num) as u32);
+        // The header (see get_header_without_crc) is the version followed by the optotype definition, starting at bit 16
+        let version = ((num >> 18) & 0b11) as u8;
+        let optotype_definition = OptotypeDefinition::from(((num >> 16) & 0b11) as u32);
         // Work out how many 30-bit blocks of letter definitions the message contains. This should be 2, unless 
         // the optotype definitions are changed
         let num_letter_blocks = get_num_body_blocks();
         let mut optotype_list: Vec<u8> = Vec::new();
-        let num_possible_optotypes = optotype_definition.optotypes.len() as u128;
         for block in 0..num_letter_blocks {
-            // Parse the block and add it to the list
+            // Parse the block and add it to the list. Each optotype is a digit of the block (see get_body).
             let shift = 20 + block * 30;
             let mut block_total = (((2_u128.pow(30) - 1) << shift) & num) >> shift;
             for _ in 0..9 {
                 if block_total > 0 {
-                    let current_optotype_id = (block_total % num_possible_optotypes) as u8;
+                    let current_optotype_id = (block_total % BODY_RADIX as u128) as u8;
                     optotype_list.push(current_optotype_id);
-                    block_total /= num_possible_optotypes;
+                    block_total /= BODY_RADIX as u128;
                 } else {
                     optotype_list.push(0);
                 }
             }
             if block_total != 0 {
-                return Err(CodeError("An unexpected value was encountered when decoding the optotypes encoded by this code.".into()));
+                return Err(CodeError::new(CodeErrorKind::InvalidField, "An unexpected value was encountered when decoding the optotypes encoded by this code."));
             }
         }
         optotype_list.reverse();
         // Store all the fields
         let processed_code = LongCode {
             version: u2::new(version),
-            optotype_definition: optotype_definition,
+            optotype_definition,
             optotypes: optotype_list
         };
         // Check the CRC is correct. The CRC is just the last 16 bits of the message, so simply casting the message to a u16 will
@@ -107,16 +129,54 @@ impl FromStr for LongCode {
         let message_crc = num as u16;
         let calculated_crc = processed_code.get_crc();
         if message_crc != calculated_crc {
-            return Err(super::CodeError(format!("The long code was entered incorrectly (CRC mismatch. Code contained CRC {}, but the calculated value was {}).", message_crc, calculated_crc)));
+            return Err(CodeError::new(CodeErrorKind::CrcMismatch, format!("The long code was entered incorrectly (CRC mismatch. Code contained CRC {}, but the calculated value was {}).", message_crc, calculated_crc)));
         }
-        Ok(processed_code)
+        let mut warnings = processed_code.validate();
+        // Even in lenient mode, optotypes outside the optotype set can't be shown on a chart
+        if let Some(index) = warnings.iter().position(|error| error.kind == CodeErrorKind::OptotypeOutOfRange) {
+            return Err(warnings.swap_remove(index));
+        }
+        Ok((processed_code, warnings))
     }
-}
-
-/// This parsing is slightly different to shortcodes because uses the concept of a header and data body, the latter
-/// of which can potentially have variable length, if the data type is changed from a u64 and the number of optotypes
-/// on each row is altered in the optotypes configuration file.
-impl LongCode {
+    /// Checks the decoded optotypes for things that `generate_random` never produces: optotype indices
+    /// outside the optotype set, and optotypes which appear more than once on the same row. A code which
+    /// has these but passes the CRC has most likely been tampered with, or mistyped in a way that happens
+    /// to collide with a valid CRC. Returns every problem found, or an empty list if the code is valid.
+    pub fn validate(&self) -> Vec<CodeError> {
+        let mut errors: Vec<CodeError> = Vec::new();
+        let num_possible_optotypes = self.optotype_definition.optotypes.len();
+        for (row_number, row) in self.get_rows().iter().enumerate() {
+            for (position, optotype) in row.iter().enumerate() {
+                if *optotype as usize >= num_possible_optotypes {
+                    errors.push(CodeError::new(CodeErrorKind::OptotypeOutOfRange, format!("The long code contained optotype number {} on row {}, but there are only {} optotypes in the \"{}\" set.", optotype, row_number + 1, num_possible_optotypes, self.optotype_definition.name)));
+                } else if row[0..position].contains(optotype) {
+                    errors.push(CodeError::new(CodeErrorKind::RepeatedOptotype, format!("The long code contained the same optotype more than once on row {}.", row_number + 1)));
+                }
+            }
+        }
+        errors
+    }
+    /// Splits the list of optotypes into the rows of the chart, from the top row to the bottom row, with each
+    /// row in the order it is displayed (left to right).
+    pub fn get_rows(&self) -> Vec<Vec<u8>> {
+        let mut rows: Vec<Vec<u8>> = Vec::new();
+        let mut optotype_index = 0;
+        // The order of the list of optotypes needs to be reversed for this chart, because the rows are generated top-down,
+        // but the list of optotypes is stored from the bottom-up.
+        let mut optotype_list = self.optotypes.clone();
+        optotype_list.reverse();
+        for &num_optotypes in NUM_OPTOTYPES_ON_ROW.iter() {
+            let mut optotypes: Vec<u8> = Vec::new();
+            for _ in 0..num_optotypes {
+                optotypes.push(optotype_list[optotype_index]);
+                optotype_index += 1;
+            }
+            optotypes.reverse();
+            rows.push(optotypes);
+        }
+        rows
+    }
+    #[allow(clippy::needless_return)]
     pub fn get_header_without_crc(&self) -> u4 {
         // Due to the implementation of u2 in the package used to provide the u2 type, the conversion must occur via
         // u16 intermediates, which must then be cast into u8 for conversion into the u4 which is returned.
@@ -130,15 +190,16 @@ impl LongCode {
             // For each block, sum the optotype indices
             let mut sum_block: u64 = 0;
             for i in (0..9).rev() {
-                sum_block += (self.optotypes[optotype_idx] as u64) * 10_u64.pow(i);
+                sum_block += (self.optotypes[optotype_idx] as u64) * BODY_RADIX.pow(i);
                 optotype_idx += 1;
             }
             // Then add the new block into the body
-            body = body | (sum_block << (30*block_num))
+            body |= sum_block << (30*block_num)
         }
-        return body;
+        body
     }
     /// This is a CRC-16/ARC of the body (optotypes) and the header of the long code.
+    #[allow(clippy::identity_op, clippy::needless_return)]
     pub fn get_crc(&self) -> u16 {
         let mut crc: u16 = 0;
         // For compatibility reasons, we first parse this into a string representation of the binary
@@ -152,34 +213,64 @@ impl LongCode {
         // but it maintains backwards compatibility with codes already issued in the older software.
         return (crc as f32).round() as u16
     }
-    /// Generates a random new shortcode for the specificed optotypes
-    pub fn generate_random(optotypes: OptotypeDefinition) -> LongCode {
-        // Generate row offsets
-        let mut optotype_list: Vec<u8> = Vec::new();
-        for row in 0..NUM_ROWS {
-            let mut row_optotypes: Vec<u8> = Vec::new();
-            let mut num_unique_optotypes_on_row = 0;
-            while num_unique_optotypes_on_row < NUM_OPTOTYPES_ON_ROW[row] {
-                // Generate a random optotype
-                let optotype = rand::thread_rng().gen_range(0, optotypes.optotypes.len()) as u8;
-                // Check if it was already on row
-                if !row_optotypes.contains(&optotype) {
-                    // If not, then append it and move on
-                    row_optotypes.push(optotype);
-                    num_unique_optotypes_on_row += 1;
+    /// Generates a random new long code for the specified optotypes. Each row is generated at random
+    /// (without repeating an optotype on the row), and is regenerated until it satisfies the generation
+    /// constraints of the optotype definition - so confusable optotypes are not placed next to each
+    /// other, the difficulty of each row is balanced, and no two rows are identical. Returns an error
+    /// if a row which satisfies the constraints can't be found within MAX_ROW_ATTEMPTS attempts.
+    pub fn generate_random(optotypes: OptotypeDefinition) -> Result<LongCode, GenerationError> {
+        let mut rows: Vec<Vec<u8>> = Vec::new();
+        for (row, &num_optotypes) in NUM_OPTOTYPES_ON_ROW.iter().enumerate() {
+            // Keep trying new rows until one satisfies the constraints. The number of attempts is capped so
+            // that a set of constraints which can't be satisfied doesn't hang the server.
+            let mut attempts = 0;
+            let row_optotypes = loop {
+                if attempts >= MAX_ROW_ATTEMPTS {
+                    return Err(GenerationError(format!("No arrangement of the {} optotypes satisfied the generation constraints for row {} after {} attempts.",
+                        optotypes.name, row + 1, MAX_ROW_ATTEMPTS)));
                 }
-                // Otherwise, generate a new optotype to try
-            }
-            optotype_list.append(&mut row_optotypes);
+                let candidate = generate_random_row(&optotypes, num_optotypes);
+                attempts += 1;
+                let is_duplicate = !optotypes.constraints.allow_identical_rows && rows.contains(&candidate);
+                if constraints::check_row(&optotypes, row, &candidate).is_empty() && !is_duplicate {
+                    break candidate;
+                }
+            };
+            rows.push(row_optotypes);
         }
+        let mut optotype_list: Vec<u8> = rows.into_iter().flatten().collect();
         optotype_list.reverse();
-        // Return representation of the shortcode
-        LongCode {
+        // Return representation of the long code
+        Ok(LongCode {
             version: u2::new(0),
             optotype_definition: optotypes,
             optotypes: optotype_list
+        })
+    }
+}
+
+/// Each optotype in the body of a long code is stored as a single decimal digit
+pub const BODY_RADIX: u64 = 10;
+
+/// The maximum number of times a row will be regenerated when trying to satisfy the generation constraints
+pub const MAX_ROW_ATTEMPTS: u32 = 10000;
+
+/// Generates a row of random optotypes, with no optotype repeated on the row
+fn generate_random_row(optotypes: &OptotypeDefinition, num_optotypes: u32) -> Vec<u8> {
+    let mut row_optotypes: Vec<u8> = Vec::new();
+    let mut num_unique_optotypes_on_row = 0;
+    while num_unique_optotypes_on_row < num_optotypes {
+        // Generate a random optotype
+        let optotype = rand::thread_rng().gen_range(0, optotypes.optotypes.len()) as u8;
+        // Check if it was already on row
+        if !row_optotypes.contains(&optotype) {
+            // If not, then append it and move on
+            row_optotypes.push(optotype);
+            num_unique_optotypes_on_row += 1;
         }
+        // Otherwise, generate a new optotype to try
     }
+    row_optotypes
 }
 
 /// This function is a helper to calculate the number of body blocks (each storing 9 optotypes) needed to store
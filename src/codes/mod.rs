@@ -1,5 +1,11 @@
 use std::fmt;
 use std::error::Error;
+use schemars::JsonSchema;
+
+use crate::constraints::GenerationError;
+use crate::optotypes::OptotypeDefinition;
+use self::short::ShortCode;
+use self::long::LongCode;
 
 pub mod short;
 pub mod long;
@@ -23,6 +29,7 @@ pub const BASE: [char; 32]= ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'J', 'K', '
 /// Returns null if the code is invalid or could not be parsed - for example, characters
 /// with accents, ligatures, or characters not contained within the alphabet used for the
 /// base 32 code generation.
+#[allow(clippy::needless_return)]
 pub fn get_number_from_code(code: String) -> Option<u128> {
     let sanitised_code = code.replace("-", "");
     let mut sum: u128 = 0;
@@ -41,6 +48,7 @@ pub fn get_number_from_code(code: String) -> Option<u128> {
 
 /// Convert a number into the characters in the base-32 encoded representation of that number.
 /// This zero-pads the output to the length specified in len.
+#[allow(clippy::needless_range_loop, clippy::needless_return)]
 pub fn get_code_from_number(mut x: u128, len: usize) -> Vec<char> {
     let mut result = vec!['A'; len];
     for i in 0..len {
@@ -65,12 +73,121 @@ pub fn get_character_value(character: char) -> Option<usize> {
     BASE.iter().position(|&c| c == character) 
 }
 
-/// This struct is used to represent errors when parsing codes given by users.
+/// The types of code which can identify a chart. Short codes (6 characters) are easy to read out over
+/// the telephone, and long codes (16 characters) can represent any arrangement of optotypes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum CodeKind {
+    Short,
+    Long
+}
+
+/// The number of characters (ignoring dashes) in each kind of code
+pub const SHORT_CODE_LENGTH: usize = 6;
+pub const LONG_CODE_LENGTH: usize = 16;
+
+impl CodeKind {
+    /// Works out which kind of code has been entered from its length, ignoring any dashes
+    pub fn from_code(code: &str) -> Result<CodeKind, CodeError> {
+        match code.replace("-", "").chars().count() {
+            SHORT_CODE_LENGTH => Ok(CodeKind::Short),
+            LONG_CODE_LENGTH => Ok(CodeKind::Long),
+            _ => Err(CodeError::new(CodeErrorKind::InvalidLength, "Invalid code length."))
+        }
+    }
+}
+
+/// The ways a new chart can be administered, which determine the kind of code it is given
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TestMode {
+    /// Short codes, which are easy to read out over the telephone
+    Telephone,
+    /// Long codes, which are used for postal and self-assessment tests
+    #[serde(rename = "self")]
+    SelfAssessment
+}
+
+/// Each kind of code is used for one mode of test
+impl From<CodeKind> for TestMode {
+    fn from(kind: CodeKind) -> Self {
+        match kind {
+            CodeKind::Short => TestMode::Telephone,
+            CodeKind::Long => TestMode::SelfAssessment
+        }
+    }
+}
+
+/// Modes are displayed as they are serialised (e.g. "telephone" or "self")
+impl fmt::Display for TestMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TestMode::Telephone => write!(f, "telephone"),
+            TestMode::SelfAssessment => write!(f, "self")
+        }
+    }
+}
+
+impl TestMode {
+    /// Generates the code for a new randomised chart of the given optotypes, using the kind of code for this mode
+    pub fn generate_code(&self, optotype_definition: OptotypeDefinition) -> Result<(CodeKind, String), GenerationError> {
+        match self {
+            TestMode::Telephone => Ok((CodeKind::Short, ShortCode::generate_random(optotype_definition).to_string())),
+            TestMode::SelfAssessment => Ok((CodeKind::Long, LongCode::generate_random(optotype_definition)?.to_string()))
+        }
+    }
+}
+
+/// This struct is used to represent errors when parsing codes given by users. The kind of error
+/// allows callers to distinguish (for example) a mistyped code from one which has been tampered with,
+/// and the message gives the details to show to the user.
 #[derive(Debug)]
-pub struct CodeError(pub String);
+pub struct CodeError {
+    pub kind: CodeErrorKind,
+    pub message: String
+}
+impl CodeError {
+    pub fn new<S: Into<String>>(kind: CodeErrorKind, message: S) -> CodeError {
+        CodeError { kind, message: message.into() }
+    }
+}
 impl fmt::Display for CodeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Error: {}", self.0)
+        write!(f, "Error: {}", self.message)
     }
 }
 impl Error for CodeError {}
+
+/// The different reasons that a code can fail to parse
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum CodeErrorKind {
+    /// The code contained characters which aren't in the base-32 alphabet
+    InvalidCharacter,
+    /// The code was not the length of any of the types of code
+    InvalidLength,
+    /// The code encoded a number which was too large for that type of code
+    OutOfRange,
+    /// A field within the code held a value which can't have been produced by the encoder
+    InvalidField,
+    /// The CRC in the code didn't match the CRC calculated from its contents
+    CrcMismatch,
+    /// The code referred to an optotype which isn't in its optotype set
+    OptotypeOutOfRange,
+    /// The code placed the same optotype more than once on a row
+    RepeatedOptotype
+}
+
+impl CodeErrorKind {
+    /// The name of the kind of error, as it is serialised. This is given to API clients and used to label metrics,
+    /// so it must not change when the enum is renamed or reordered.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CodeErrorKind::InvalidCharacter => "InvalidCharacter",
+            CodeErrorKind::InvalidLength => "InvalidLength",
+            CodeErrorKind::OutOfRange => "OutOfRange",
+            CodeErrorKind::InvalidField => "InvalidField",
+            CodeErrorKind::CrcMismatch => "CrcMismatch",
+            CodeErrorKind::OptotypeOutOfRange => "OptotypeOutOfRange",
+            CodeErrorKind::RepeatedOptotype => "RepeatedOptotype"
+        }
+    }
+}
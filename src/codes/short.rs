@@ -1,5 +1,5 @@
-use crate::optotypes::OptotypeDefinition;
-use crate::codes::{NUM_ROWS, crc, CodeError};
+use crate::optotypes::{OptotypeDefinition, get_max_start_row};
+use crate::codes::{NUM_ROWS, crc, CodeError, CodeErrorKind};
 use rand::Rng;
 use ux::{u1,u4,u6,u24,u30};
 use std::str::FromStr;
@@ -24,7 +24,9 @@ pub struct ShortCode {
 /// This serialises a short code (string) representation of the values stored within this ShortCode object. 
 /// The convention is to have a "-" in the middle, because it splits the code into two sets of 3 digits, which
 /// are easier to remember (via chunking, see https://doi.org/10.1037/h0043158 for a discussion).
+#[allow(clippy::to_string_trait_impl)]
 impl ToString for ShortCode {
+    #[allow(clippy::needless_return)]
     fn to_string(&self) -> String {
         let x = u32::from(self.get_numerical_representation());
         let mut result = super::get_code_from_number(x as u128, 6);
@@ -43,11 +45,11 @@ impl FromStr for ShortCode {
         // Convert the base-32 code into the number it encodes
         let num = match super::get_number_from_code(code.to_string()) {
             Some(binary) => { binary },
-            None => { return Err(super::CodeError("Failed to parse short code. It may have contained invalid characters.".into())); }
+            None => { return Err(CodeError::new(CodeErrorKind::InvalidCharacter, "Failed to parse short code. It may have contained invalid characters.")); }
         };
         // Double check the encoded number is in the right range (0 to 2^30 - 1)
         if num > 2_u128.pow(30) - 1 {
-            return Err(super::CodeError("Code did not encode a number within a valid range.".into()));
+            return Err(CodeError::new(CodeErrorKind::OutOfRange, "Code did not encode a number within a valid range."));
         }
         // Calculate the fields via bitshifts - they're all fixed-width
         let version = u1::from(num & (1 << 29) != 0);
@@ -63,20 +65,24 @@ impl FromStr for ShortCode {
         // Do a sanity check to make sure the combined_offsets number wasn't too big. If it was, the 
         // code was likely entered incorrectly.
         if combined_offsets != 0 {
-            return Err(super::CodeError("The row offsets number appeared to be invalid.".into()));
+            return Err(CodeError::new(CodeErrorKind::InvalidField, "The row offsets number appeared to be invalid."));
+        }
+        // The largest few start rows don't correspond to a valid bottom row for the chart
+        if start_row > get_max_start_row(&optotype_definition) {
+            return Err(CodeError::new(CodeErrorKind::InvalidField, "The start row number appeared to be invalid."));
         }
         // Store all the fields in a ShortCode struct
         let processed_code = ShortCode {
-            version: version,
-            optotype_definition: optotype_definition,
-            start_row: start_row,
-            offsets: offsets,
+            version,
+            optotype_definition,
+            start_row,
+            offsets,
         };
         // Check the CRC is correct
-        let message_crc = u6::new((num & 63 << 0) as u8);
+        let message_crc = u6::new((num & 63) as u8);
         let calculated_crc = processed_code.get_crc();
         if message_crc != calculated_crc {
-            return Err(super::CodeError(format!("The short code was entered incorrectly (CRC mismatch. Code contained CRC {}, but the calculated value was {}).", message_crc, calculated_crc)));
+            return Err(CodeError::new(CodeErrorKind::CrcMismatch, format!("The short code was entered incorrectly (CRC mismatch. Code contained CRC {}, but the calculated value was {}).", message_crc, calculated_crc)));
         }
         Ok(processed_code)
     }
@@ -86,6 +92,7 @@ impl ShortCode {
     /// This returns a number representing the state stored within a ShortCode struct.
     /// This number is calculated through converting the fields into binary representations
     /// of their values, and then appending them to oneanother via bitshift operations.
+    #[allow(clippy::needless_return)]
     pub fn get_numerical_representation_without_crc(&self) -> u24 {
         // An u32 is used internally to avoid needing to do too many conversions into u24, which make the code less
         // idiomatic and harder to follow. The 8 most significant bytes are removed at the end of the function.
@@ -106,6 +113,7 @@ impl ShortCode {
     /// legacy code, this is returned as a u6 but it actually can only take values
     /// from 0 - 32 (i.e a u5). This might be changed in a future version to make 
     /// use of the wasted bit, which is currently set to 0.
+    #[allow(clippy::identity_op, clippy::needless_return)]
     pub fn get_crc(&self) -> u6 {
         let mut crc: u8 = 0;
         // For compatibility reasons, we first parse this into a string representation of the binary
@@ -126,28 +134,32 @@ impl ShortCode {
     pub fn get_numerical_representation(&self) -> u30 {
         let data_body = self.get_numerical_representation_without_crc();
         let crc = self.get_crc();
-        return u30::new(u32::from(data_body << 6) | u32::from(crc << 0));
+        u30::new((u32::from(data_body) << 6) | u32::from(crc))
     }
     /// Generates a random new shortcode for the specified optotypes
     pub fn generate_random(optotypes: OptotypeDefinition) -> ShortCode {
-        // Generate row offsets
+        // Generate row offsets. Every row apart from the bottom (reference) row is derived by adding its
+        // offset to the reference row, so the offsets must all be non-zero (otherwise the row would be a
+        // copy of the reference row) and distinct (otherwise two rows would be copies of one another).
+        // Offsets are stored as single digits, and wrap around the number of optotypes in the set.
+        let max_offset = (optotypes.optotypes.len() as u8 - 1).min(9);
         let mut offsets: [u4; NUM_ROWS-1] = [u4::new(0); NUM_ROWS-1];
-        for i in 0..NUM_ROWS-2 {
+        for i in 0..NUM_ROWS-1 {
             loop {
-                let new_offset = u4::new(rand::thread_rng().gen::<u8>() % 10);
+                let new_offset = u4::new(1 + rand::thread_rng().gen::<u8>() % max_offset);
                 if !&offsets.contains(&new_offset) {
                     offsets[i] = new_offset;
                     break;
                 }
             }
-            
         }
         // Return representation of the shortcode
+        let max_start_row = get_max_start_row(&optotypes);
         ShortCode {
             version: u1::new(0),
             optotype_definition: optotypes,
-            start_row: rand::thread_rng().gen(),
-            offsets: offsets
+            start_row: rand::thread_rng().gen_range(0, max_start_row as u16 + 1) as u8,
+            offsets
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,365 @@
+use std::fmt;
+use std::error::Error;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::net::IpAddr;
+use rocket::{Config, Request, State};
+use rocket::config::Value;
+use schemars::JsonSchema;
+
+use crate::audit::{AuditStoreKind, IpPrivacy, DEFAULT_RETENTION_DAYS};
+use crate::i18n::{self, Locale};
+use crate::optotypes::{OptotypeArrangement, OptotypeDefinition};
+use crate::pdf::PaperSize;
+use crate::scoring::{self, ScoringError, STANDARD_CHART_DISTANCE, MIN_VIEWING_DISTANCE, MAX_VIEWING_DISTANCE};
+
+/// Environment variables starting with this override the settings in Rocket.toml, e.g. HAT_PAPER_SIZE=letter
+/// overrides paper_size
+pub const ENV_PREFIX: &str = "HAT_";
+
+/// The settings for the site, which are read from Rocket.toml (or HAT_* environment variables) when the server
+/// starts and checked before it is launched. The defaults are the settings used by homeacuitytest.org.
+///
+/// The optotype set which unknown optotype ids in codes fall back to (DEFAULT_OPTOTYPES) can't be configured,
+/// because every server must decode the same code to the same chart.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct AppConfig {
+    /// The viewing distance (in cm) which charts are printed for, and assumed to have been printed for when
+    /// they are scored, if no distance is given
+    pub default_distance: f64,
+    /// The shortest and longest viewing distances (in cm) which charts can be downloaded for
+    pub min_distance: f64,
+    pub max_distance: f64,
+    /// Viewing distances (in cm) which are suggested on the forms
+    pub common_distances: Vec<f64>,
+    /// The optotype set used for codes issued through the API, if the request doesn't name one
+    pub default_optotypes: String,
+    /// The standardised charts, which are linked to from the homepage in this order
+    pub standard_charts: Vec<StandardChart>,
+    /// The paper which the PDFs are laid out for
+    pub paper_size: PaperSize,
+    /// Where the feedback form on the homepage is sent (and the page shown after it has been sent). The form,
+    /// and the paragraph about it in the privacy policy, are left out if this is not set.
+    pub feedback_form: Option<String>,
+    pub feedback_redirect: Option<String>,
+    /// The addresses of the reverse proxies in front of the server. The X-Real-IP header is only believed when a
+    /// request comes from one of these, as anyone else could set it to hide their address.
+    pub trusted_proxies: Vec<IpAddr>,
+    /// The token which clients must send (as "Authorization: Bearer <token>") to get the usage statistics. The
+    /// statistics can't be downloaded by anyone if this is not set. Tokens are never passed to the templates.
+    #[serde(skip)]
+    pub statistics_token: Option<String>,
+    /// The token which Prometheus must send to scrape /metrics, which is unavailable if this is not set
+    #[serde(skip)]
+    pub metrics_token: Option<String>,
+    /// The token which clinicians (or the clinic's systems) must give to record and read stored results. The server
+    /// refuses to start with results being stored and no token set.
+    #[serde(skip)]
+    pub results_token: Option<String>,
+    /// Where downloads are recorded for the audit log described in the privacy policy, or None if they aren't
+    pub audit_log: Option<AuditStoreKind>,
+    /// Where the audit log is kept. The default depends on the kind of store (see AuditStoreKind::default_path).
+    #[serde(skip)]
+    pub audit_path: Option<String>,
+    /// How much of each client's IP address is kept in the audit log
+    pub audit_ip: IpPrivacy,
+    /// The salt used when hashing IP addresses for the audit log and the rate limits. A random salt is chosen
+    /// when the server starts if this is not set.
+    #[serde(skip)]
+    pub audit_ip_salt: Option<String>,
+    /// How many days downloads are kept in the audit log for
+    pub audit_retention_days: i64
+}
+
+impl Default for AppConfig {
+    fn default() -> AppConfig {
+        AppConfig {
+            default_distance: STANDARD_CHART_DISTANCE,
+            min_distance: MIN_VIEWING_DISTANCE,
+            max_distance: MAX_VIEWING_DISTANCE,
+            common_distances: vec![100.0, 150.0, 190.0, 300.0],
+            default_optotypes: "sloan".to_string(),
+            standard_charts: vec![
+                StandardChart { name: "letters".to_string(), code: "RFDCAM".to_string(), optotypes: "sloan".to_string(), description: "Letters".to_string(), label_key: "standard-chart-letters".to_string() },
+                StandardChart { name: "symbols".to_string(), code: "EBC3JF".to_string(), optotypes: "aukland".to_string(), description: "Symbols".to_string(), label_key: "standard-chart-symbols".to_string() }
+            ],
+            paper_size: PaperSize::A4,
+            feedback_form: Some("https://formspree.io/xpzlgwwp".to_string()),
+            feedback_redirect: Some("https://homeacuitytest.com/received".to_string()),
+            trusted_proxies: Vec::new(),
+            statistics_token: None,
+            metrics_token: None,
+            results_token: None,
+            audit_log: Some(AuditStoreKind::Sqlite),
+            audit_path: None,
+            audit_ip: IpPrivacy::Hash,
+            audit_ip_salt: None,
+            audit_retention_days: DEFAULT_RETENTION_DAYS
+        }
+    }
+}
+
+/// A standardised chart, which is the same every time it is downloaded. These are for patients who are only tested
+/// once or twice, so that the clinician can know the answers in advance. Each is served at /standard/<name>.
+#[derive(Debug, PartialEq, Clone, Serialize, JsonSchema)]
+pub struct StandardChart {
+    /// The name of the chart in its address (e.g. "letters")
+    pub name: String,
+    pub code: String,
+    /// The name of the optotype set which the code uses (e.g. "sloan")
+    pub optotypes: String,
+    /// The description of the chart in English, which is given by the API
+    pub description: String,
+    /// The id of the message in the Fluent catalogues which labels the chart's button on the homepage
+    /// (e.g. "standard-chart-letters")
+    pub label_key: String
+}
+
+/// Looks up settings in the environment variables, and then in Rocket's configuration
+struct Settings<'a> {
+    config: &'a Config,
+    env: &'a HashMap<String, String>
+}
+
+impl Settings<'_> {
+    fn env(&self, name: &str) -> Option<&String> {
+        self.env.get(&format!("{}{}", ENV_PREFIX, name.to_uppercase()))
+    }
+
+    fn string(&self, name: &str) -> Result<Option<String>, ConfigError> {
+        if let Some(value) = self.env(name) {
+            return Ok(Some(value.clone()));
+        }
+        match self.config.get_extra(name) {
+            Ok(Value::String(value)) => Ok(Some(value.clone())),
+            Ok(_) => Err(ConfigError(format!("The {} setting must be a string.", name))),
+            Err(_) => Ok(None)
+        }
+    }
+
+    fn number(&self, name: &str) -> Result<Option<f64>, ConfigError> {
+        if let Some(value) = self.env(name) {
+            return value.trim().parse::<f64>().map(Some)
+                .map_err(|_| ConfigError(format!("The {} setting must be a number.", name)));
+        }
+        match self.config.get_extra(name) {
+            Ok(value) => to_number(value).map(Some).ok_or_else(|| ConfigError(format!("The {} setting must be a number.", name))),
+            Err(_) => Ok(None)
+        }
+    }
+
+    /// A list of numbers, which are separated by commas in environment variables (e.g. HAT_COMMON_DISTANCES=100,150)
+    fn numbers(&self, name: &str) -> Result<Option<Vec<f64>>, ConfigError> {
+        let error = || ConfigError(format!("The {} setting must be a list of numbers.", name));
+        if let Some(value) = self.env(name) {
+            return value.split(',').map(|number| number.trim().parse::<f64>().map_err(|_| error())).collect::<Result<Vec<f64>, ConfigError>>().map(Some);
+        }
+        match self.config.get_extra(name) {
+            Ok(Value::Array(values)) => values.iter().map(|value| to_number(value).ok_or_else(error)).collect::<Result<Vec<f64>, ConfigError>>().map(Some),
+            Ok(_) => Err(error()),
+            Err(_) => Ok(None)
+        }
+    }
+
+    /// A list of strings, which are separated by commas in environment variables (e.g. HAT_TRUSTED_PROXIES=127.0.0.1,::1)
+    fn strings(&self, name: &str) -> Result<Option<Vec<String>>, ConfigError> {
+        let error = || ConfigError(format!("The {} setting must be a list of strings.", name));
+        if let Some(value) = self.env(name) {
+            return Ok(Some(value.split(',').map(|text| text.trim().to_string()).filter(|text| !text.is_empty()).collect()));
+        }
+        match self.config.get_extra(name) {
+            Ok(Value::Array(values)) => values.iter().map(|value| value.as_str().map(String::from).ok_or_else(error)).collect::<Result<Vec<String>, ConfigError>>().map(Some),
+            Ok(_) => Err(error()),
+            Err(_) => Ok(None)
+        }
+    }
+
+    /// The registry of standardised charts, which is a list of tables. This can't be set by a HAT_* environment
+    /// variable, because environment variables only hold simple values.
+    fn standard_charts(&self, name: &str) -> Result<Option<Vec<StandardChart>>, ConfigError> {
+        let error = || ConfigError(format!("The {} setting must be a list of tables, each with a name, code, optotypes, description and label_key.", name));
+        let charts = match self.config.get_extra(name) {
+            Ok(Value::Array(charts)) => charts,
+            Ok(_) => { return Err(error()); },
+            Err(_) => { return Ok(None); }
+        };
+        charts.iter()
+            .map(|chart| {
+                let field = |field: &str| chart.get(field).and_then(Value::as_str).map(String::from).ok_or_else(error);
+                Ok(StandardChart { name: field("name")?, code: field("code")?, optotypes: field("optotypes")?, description: field("description")?, label_key: field("label_key")? })
+            })
+            .collect::<Result<Vec<StandardChart>, ConfigError>>()
+            .map(Some)
+    }
+}
+
+/// TOML distinguishes integers from floats, but distances can be written either way (e.g. 150 or 150.0)
+fn to_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Float(number) => Some(*number),
+        Value::Integer(number) => Some(*number as f64),
+        _ => None
+    }
+}
+
+impl AppConfig {
+    /// Reads the settings from Rocket's configuration, overridden by any HAT_* environment variables
+    pub fn from_config(config: &Config) -> Result<AppConfig, ConfigError> {
+        let env: HashMap<String, String> = std::env::vars().filter(|(name, _)| name.starts_with(ENV_PREFIX)).collect();
+        AppConfig::from_settings(config, &env)
+    }
+
+    /// Reads the settings from Rocket's configuration and the given environment variables, and checks them
+    pub fn from_settings(config: &Config, env: &HashMap<String, String>) -> Result<AppConfig, ConfigError> {
+        let settings = Settings { config, env };
+        let defaults = AppConfig::default();
+        // An empty feedback form setting turns the form off
+        let optional_url = |name: &str, default: Option<String>| -> Result<Option<String>, ConfigError> {
+            Ok(match settings.string(name)? {
+                Some(url) => Some(url).filter(|url| !url.trim().is_empty()),
+                None => default
+            })
+        };
+        let app_config = AppConfig {
+            default_distance: settings.number("default_distance")?.unwrap_or(defaults.default_distance),
+            min_distance: settings.number("min_distance")?.unwrap_or(defaults.min_distance),
+            max_distance: settings.number("max_distance")?.unwrap_or(defaults.max_distance),
+            common_distances: settings.numbers("common_distances")?.unwrap_or(defaults.common_distances),
+            default_optotypes: settings.string("default_optotypes")?.unwrap_or(defaults.default_optotypes),
+            standard_charts: settings.standard_charts("standard_charts")?.unwrap_or(defaults.standard_charts),
+            paper_size: match settings.string("paper_size")? {
+                Some(paper_size) => PaperSize::from_str(&paper_size).map_err(|e| ConfigError(e.0))?,
+                None => defaults.paper_size
+            },
+            feedback_form: optional_url("feedback_form", defaults.feedback_form)?,
+            feedback_redirect: optional_url("feedback_redirect", defaults.feedback_redirect)?,
+            trusted_proxies: match settings.strings("trusted_proxies")? {
+                Some(proxies) => proxies.iter()
+                    .map(|proxy| IpAddr::from_str(proxy).map_err(|_| ConfigError(format!("The trusted proxy \"{}\" is not an IP address.", proxy))))
+                    .collect::<Result<Vec<IpAddr>, ConfigError>>()?,
+                None => defaults.trusted_proxies
+            },
+            statistics_token: settings.string("statistics_token")?.filter(|token| !token.trim().is_empty()),
+            metrics_token: settings.string("metrics_token")?.filter(|token| !token.trim().is_empty()),
+            results_token: settings.string("results_token")?.filter(|token| !token.trim().is_empty()),
+            audit_log: match settings.string("audit_log")?.map(|kind| kind.trim().to_lowercase()).as_deref() {
+                Some("sqlite") => Some(AuditStoreKind::Sqlite),
+                Some("file") => Some(AuditStoreKind::File),
+                Some("none") => None,
+                Some(other) => { return Err(ConfigError(format!("\"{}\" is not a recognised audit log (use sqlite, file or none).", other))); },
+                None => defaults.audit_log
+            },
+            audit_path: settings.string("audit_path")?.filter(|path| !path.trim().is_empty()),
+            audit_ip: match settings.string("audit_ip")? {
+                Some(audit_ip) => IpPrivacy::from_str(&audit_ip).map_err(|e| ConfigError(e.0))?,
+                None => defaults.audit_ip
+            },
+            audit_ip_salt: settings.string("audit_ip_salt")?.filter(|salt| !salt.is_empty()),
+            audit_retention_days: match settings.number("audit_retention_days")? {
+                Some(days) if days.fract() == 0.0 => days as i64,
+                Some(_) => { return Err(ConfigError("The audit_retention_days setting must be a whole number of days.".into())); },
+                None => defaults.audit_retention_days
+            }
+        };
+        app_config.validate()?;
+        Ok(app_config)
+    }
+
+    /// Checks that the settings are consistent, so that problems are found when the server starts rather than
+    /// when someone downloads a chart
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(MIN_VIEWING_DISTANCE..=MAX_VIEWING_DISTANCE).contains(&self.min_distance)
+            || !(MIN_VIEWING_DISTANCE..=MAX_VIEWING_DISTANCE).contains(&self.max_distance)
+            || self.min_distance > self.max_distance {
+            return Err(ConfigError(format!("The min_distance and max_distance settings must be in order, and between {}cm and {}cm.",
+                MIN_VIEWING_DISTANCE, MAX_VIEWING_DISTANCE)));
+        }
+        if self.check_distance(self.default_distance).is_err() {
+            return Err(ConfigError("The default_distance setting must be between min_distance and max_distance.".into()));
+        }
+        if let Some(distance) = self.common_distances.iter().find(|&&distance| self.check_distance(distance).is_err()) {
+            return Err(ConfigError(format!("The common distance {}cm is not between min_distance and max_distance.", distance)));
+        }
+        if OptotypeDefinition::from_name(&self.default_optotypes).is_none() {
+            return Err(ConfigError(format!("The default_optotypes setting (\"{}\") is not the name of an optotype set.", self.default_optotypes)));
+        }
+        for (i, chart) in self.standard_charts.iter().enumerate() {
+            if chart.name.is_empty() || !chart.name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+                return Err(ConfigError(format!("The standardised chart name \"{}\" may only use lower case letters, numbers and dashes.", chart.name)));
+            }
+            if self.standard_charts[..i].iter().any(|other| other.name == chart.name) {
+                return Err(ConfigError(format!("There is more than one standardised chart called \"{}\".", chart.name)));
+            }
+            // The label is translated on the homepage, so it must be in the reference catalogue (the other
+            // catalogues fall back to English)
+            if !i18n::message_ids(Locale::English).contains(&chart.label_key) {
+                return Err(ConfigError(format!("The label_key \"{}\" of the standardised chart \"{}\" is not a message in the English catalogue.", chart.label_key, chart.name)));
+            }
+            // The declared optotype set is checked against the code, so that a valid code for the wrong optotype
+            // set can't be given to patients by mistake
+            match OptotypeArrangement::from_code(&chart.code) {
+                Ok(arrangement) if arrangement.optotype_definition.name == chart.optotypes => {},
+                Ok(arrangement) => {
+                    return Err(ConfigError(format!("The standardised chart \"{}\" is declared to use {}, but its code {} uses {}.",
+                        chart.name, chart.optotypes, chart.code, arrangement.optotype_definition.name)));
+                },
+                Err(e) => {
+                    return Err(ConfigError(format!("The code {} of the standardised chart \"{}\" is not valid ({}).", chart.code, chart.name, e)));
+                }
+            }
+        }
+        if self.audit_retention_days < 1 {
+            return Err(ConfigError("The audit_retention_days setting must be at least one day.".into()));
+        }
+        for url in self.feedback_form.iter().chain(self.feedback_redirect.iter()) {
+            if !url.starts_with("https://") {
+                return Err(ConfigError(format!("The feedback address {} must use https.", url)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Looks up a standardised chart by its name
+    pub fn standard_chart(&self, name: &str) -> Option<&StandardChart> {
+        self.standard_charts.iter().find(|chart| chart.name == name)
+    }
+
+    /// The address of the client which made a request. This is the address which the request came from, unless it
+    /// came from a trusted proxy, in which case it is taken from the X-Real-IP header which the proxy sets.
+    pub fn client_ip(&self, request: &Request) -> Option<IpAddr> {
+        let remote = request.remote().map(|address| address.ip());
+        match remote {
+            Some(ip) if self.trusted_proxies.contains(&ip) => request.real_ip().or(remote),
+            _ => remote
+        }
+    }
+
+    /// Checks that charts can be downloaded for a viewing distance (in cm)
+    pub fn check_distance(&self, distance: f64) -> Result<(), ScoringError> {
+        scoring::check_distance(distance)?;
+        if (self.min_distance..=self.max_distance).contains(&distance) {
+            Ok(())
+        } else {
+            Err(ScoringError::distance_out_of_range(self.min_distance, self.max_distance))
+        }
+    }
+}
+
+/// The address of the client which made a request, found with AppConfig::client_ip. If the configuration isn't
+/// managed (which only happens in tests), the X-Real-IP header is never believed.
+pub fn client_ip(request: &Request) -> Option<IpAddr> {
+    match request.guard::<State<AppConfig>>().succeeded() {
+        Some(app_config) => app_config.client_ip(request),
+        None => request.remote().map(|address| address.ip())
+    }
+}
+
+/// This struct is used to represent errors in the settings, which stop the server from starting.
+#[derive(Debug)]
+pub struct ConfigError(pub String);
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error: {}", self.0)
+    }
+}
+impl Error for ConfigError {}
@@ -0,0 +1,102 @@
+use std::fmt;
+use std::error::Error;
+use crate::optotypes::OptotypeDefinition;
+use schemars::JsonSchema;
+
+/// Constraints on how optotypes may be arranged when a random chart is generated. These are
+/// configured per OptotypeDefinition, because what makes two optotypes confusable (or an optotype
+/// hard to read) depends on the set of optotypes being used.
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct GenerationConstraints {
+    /// Pairs of optotypes which are easily confused with one another, and so may not be placed
+    /// next to each other on a row (in either order).
+    pub forbidden_adjacent_pairs: Vec<(char, char)>,
+    /// The relative difficulty of each optotype, in the same order as the optotypes in the
+    /// OptotypeDefinition. A weight of 1.0 is average difficulty, and higher weights are harder.
+    pub difficulty: Vec<f64>,
+    /// The largest amount by which the mean difficulty of the optotypes on a row may differ from
+    /// the mean difficulty of the whole set of optotypes. This is infinite for unconstrained sets,
+    /// which is serialised to JSON as null.
+    #[schemars(with = "Option<f64>")]
+    pub max_row_difficulty_deviation: f64,
+    /// Whether two rows of the chart are allowed to contain the same optotypes in the same order.
+    pub allow_identical_rows: bool,
+}
+
+impl GenerationConstraints {
+    /// Constraints which allow any arrangement with no repeated optotypes on a row. These are used for
+    /// optotype sets where no legibility data is available.
+    pub fn unconstrained(num_optotypes: usize) -> GenerationConstraints {
+        GenerationConstraints {
+            forbidden_adjacent_pairs: Vec::new(),
+            difficulty: vec![1.0; num_optotypes],
+            max_row_difficulty_deviation: f64::INFINITY,
+            allow_identical_rows: false,
+        }
+    }
+}
+
+/// Describes a way in which an arrangement of optotypes breaks the generation constraints for its
+/// optotype set. Rows are numbered from the top of the chart, starting at 0.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum ConstraintViolation {
+    /// Two confusable optotypes are next to each other on a row, starting at the given position
+    ForbiddenAdjacentPair { row: usize, position: usize },
+    /// The mean difficulty of the optotypes on a row is too far from the mean for the set
+    UnbalancedRow { row: usize, mean_difficulty: f64 },
+    /// Two rows contain the same optotypes in the same order
+    IdenticalRows { first_row: usize, second_row: usize },
+}
+
+/// Checks a single row of optotypes (given as indices into the OptotypeDefinition) against the
+/// constraints which apply within a row. The row number is only used to label the violations.
+pub fn check_row(optotype_definition: &OptotypeDefinition, row_number: usize, row: &[u8]) -> Vec<ConstraintViolation> {
+    let constraints = &optotype_definition.constraints;
+    let mut violations: Vec<ConstraintViolation> = Vec::new();
+    // Look for confusable optotypes next to each other
+    for position in 1..row.len() {
+        let first = optotype_definition.optotypes[row[position - 1] as usize];
+        let second = optotype_definition.optotypes[row[position] as usize];
+        let forbidden = constraints.forbidden_adjacent_pairs.iter()
+            .any(|&(a, b)| (a == first && b == second) || (a == second && b == first));
+        if forbidden {
+            violations.push(ConstraintViolation::ForbiddenAdjacentPair { row: row_number, position: position - 1 });
+        }
+    }
+    // Check the difficulty of the row is close to the difficulty of the set as a whole
+    if !row.is_empty() {
+        let set_mean = constraints.difficulty.iter().sum::<f64>() / constraints.difficulty.len() as f64;
+        let row_mean = row.iter().map(|&optotype| constraints.difficulty[optotype as usize]).sum::<f64>() / row.len() as f64;
+        // A small tolerance is allowed, so that rounding doesn't depend on the order of the optotypes on the row
+        if (row_mean - set_mean).abs() > constraints.max_row_difficulty_deviation + 1e-9 {
+            violations.push(ConstraintViolation::UnbalancedRow { row: row_number, mean_difficulty: row_mean });
+        }
+    }
+    violations
+}
+
+/// Checks a list of rows (from the top of the chart down) against the generation constraints for their optotype
+/// set, including the constraints which apply across rows
+pub fn check_rows(optotype_definition: &OptotypeDefinition, rows: &[&[u8]]) -> Vec<ConstraintViolation> {
+    let mut violations: Vec<ConstraintViolation> = Vec::new();
+    for (row_number, row) in rows.iter().enumerate() {
+        violations.append(&mut check_row(optotype_definition, row_number, row));
+        if !optotype_definition.constraints.allow_identical_rows {
+            if let Some(first_row) = rows[0..row_number].iter().position(|previous_row| previous_row == row) {
+                violations.push(ConstraintViolation::IdenticalRows { first_row, second_row: row_number });
+            }
+        }
+    }
+    violations
+}
+
+/// This struct is used to represent errors when a random chart can't be generated, because no arrangement
+/// which satisfies the generation constraints was found.
+#[derive(Debug)]
+pub struct GenerationError(pub String);
+impl fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error: {}", self.0)
+    }
+}
+impl Error for GenerationError {}
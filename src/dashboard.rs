@@ -0,0 +1,159 @@
+use chrono::NaiveDateTime;
+
+use crate::acuity::{Acuity, round_to_hundredths};
+use crate::monitoring::{self, ChangeClassification, ChangeReport, Eye, MonitoringError, Session};
+use crate::results::StoredResult;
+
+/// The eyes in the order they are shown on the dashboard
+const EYES: [Eye; 3] = [Eye::Right, Eye::Left, Eye::Both];
+
+/// The size (in px) of the trend chart, and the space around the plot which is left for the axis labels
+const TREND_WIDTH: f64 = 640.0;
+const TREND_HEIGHT: f64 = 260.0;
+const TREND_MARGIN_LEFT: f64 = 56.0;
+const TREND_MARGIN_RIGHT: f64 = 24.0;
+const TREND_MARGIN_TOP: f64 = 16.0;
+const TREND_MARGIN_BOTTOM: f64 = 40.0;
+
+/// The colours used for each classification of change, which match the table on the dashboard
+const WORSENED_COLOUR: &str = "#dc3545";
+const IMPROVED_COLOUR: &str = "#28a745";
+const STABLE_COLOUR: &str = "#6c757d";
+
+/// A stored result on the dashboard, along with how the acuity changed since the previous scored result for the same eye
+#[derive(Debug, PartialEq, Serialize)]
+pub struct HistoryEntry {
+    #[serde(flatten)]
+    pub result: StoredResult,
+    /// The change in logMAR since the previous scored result. Positive changes are worse vision.
+    pub difference: Option<f64>,
+    pub classification: Option<ChangeClassification>
+}
+
+/// Every result for one of a patient's eyes, in the order the tests were done
+#[derive(Debug, PartialEq, Serialize)]
+pub struct EyeHistory {
+    pub eye: Eye,
+    pub entries: Vec<HistoryEntry>,
+    /// The comparison of the scored results, if there are at least two
+    pub report: Option<ChangeReport>,
+    /// An SVG chart of the scored results over time, if there are any
+    pub trend: Option<String>
+}
+
+/// Splits a patient's results (in the order the tests were done) by eye, and compares the scored results for each
+/// eye against the test-retest limit. Results where no optotypes were read have no acuity, so they are listed
+/// but can't be compared or plotted.
+pub fn eye_histories(results: &[StoredResult], test_retest_limit: f64) -> Result<Vec<EyeHistory>, MonitoringError> {
+    let mut histories: Vec<EyeHistory> = Vec::new();
+    for &eye in EYES.iter() {
+        let results: Vec<&StoredResult> = results.iter().filter(|stored| stored.result.eye == eye).collect();
+        if results.is_empty() {
+            continue;
+        }
+        let sessions: Vec<Session> = results.iter()
+            .filter_map(|stored| stored.result.acuity.map(|acuity| Session { date: stored.result.tested_at.date(), eye, acuity }))
+            .collect();
+        let report = if sessions.len() >= 2 { Some(monitoring::compare(&sessions, test_retest_limit)?) } else { None };
+
+        // Each scored result is compared with the scored result before it, skipping any results in between which
+        // weren't scored
+        let mut previous: Option<Acuity> = None;
+        let mut entries: Vec<HistoryEntry> = Vec::new();
+        for stored in results {
+            let difference = match (previous, stored.result.acuity) {
+                (Some(previous), Some(acuity)) => Some(round_to_hundredths(acuity.log_mar() - previous.log_mar())),
+                _ => None
+            };
+            previous = stored.result.acuity.or(previous);
+            entries.push(HistoryEntry {
+                result: stored.clone(),
+                difference,
+                classification: difference.map(|difference| monitoring::classify(difference, test_retest_limit))
+            });
+        }
+        let trend = trend_svg(&entries);
+        histories.push(EyeHistory { eye, entries, report, trend });
+    }
+    Ok(histories)
+}
+
+fn classification_colour(classification: Option<ChangeClassification>) -> &'static str {
+    match classification {
+        Some(ChangeClassification::Worsened) => WORSENED_COLOUR,
+        Some(ChangeClassification::Improved) => IMPROVED_COLOUR,
+        _ => STABLE_COLOUR
+    }
+}
+
+/// Draws the scored results for an eye as an SVG line chart of logMAR against the date of the test. Better vision
+/// (a lower logMAR) is higher up, and the line into each result is coloured by how the acuity changed, so that
+/// significant changes stand out. Returns None if none of the results were scored.
+pub fn trend_svg(entries: &[HistoryEntry]) -> Option<String> {
+    let points: Vec<(NaiveDateTime, f64, Option<ChangeClassification>)> = entries.iter()
+        .filter_map(|entry| entry.result.result.acuity.map(|acuity| (entry.result.result.tested_at, acuity.log_mar(), entry.classification)))
+        .collect();
+    if points.is_empty() {
+        return None;
+    }
+    let plot_width = TREND_WIDTH - TREND_MARGIN_LEFT - TREND_MARGIN_RIGHT;
+    let plot_height = TREND_HEIGHT - TREND_MARGIN_TOP - TREND_MARGIN_BOTTOM;
+
+    // The logMAR axis is in tenths, with at least a tenth of space above and below the results
+    let lowest = points.iter().map(|point| point.1).fold(f64::INFINITY, f64::min);
+    let highest = points.iter().map(|point| point.1).fold(f64::NEG_INFINITY, f64::max);
+    let lowest_tenth = (lowest * 10.0).floor() as i32 - 1;
+    let highest_tenth = (highest * 10.0).ceil() as i32 + 1;
+    let y = |log_mar: f64| TREND_MARGIN_TOP + (log_mar * 10.0 - lowest_tenth as f64) / (highest_tenth - lowest_tenth) as f64 * plot_height;
+
+    // A single test (or several on the same day) is drawn in the middle of the chart
+    let first = points[0].0;
+    let span = (points[points.len() - 1].0 - first).num_seconds() as f64;
+    let x = |time: NaiveDateTime| if span > 0.0 {
+        TREND_MARGIN_LEFT + (time - first).num_seconds() as f64 / span * plot_width
+    } else {
+        TREND_MARGIN_LEFT + plot_width / 2.0
+    };
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\" width=\"100%\" role=\"img\" aria-label=\"Acuity (logMAR) over time\" style=\"max-width: {}px; font-family: sans-serif; font-size: 11px;\">",
+        TREND_WIDTH, TREND_HEIGHT, TREND_WIDTH
+    );
+    // Grid lines every tenth of a logMAR (or every two tenths for a wide range), labelled on the left
+    let step = if highest_tenth - lowest_tenth > 10 { 2 } else { 1 };
+    for tenth in (lowest_tenth..=highest_tenth).filter(|tenth| tenth % step == 0) {
+        let line_y = y(tenth as f64 / 10.0);
+        svg.push_str(&format!(
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#dee2e6\" /><text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\" fill=\"#495057\">{:.1}</text>",
+            TREND_MARGIN_LEFT, line_y, TREND_WIDTH - TREND_MARGIN_RIGHT, line_y, TREND_MARGIN_LEFT - 6.0, line_y + 4.0, tenth as f64 / 10.0
+        ));
+    }
+    svg.push_str(&format!(
+        "<text x=\"12\" y=\"{:.1}\" transform=\"rotate(-90 12 {:.1})\" text-anchor=\"middle\" fill=\"#495057\">logMAR</text>",
+        TREND_MARGIN_TOP + plot_height / 2.0, TREND_MARGIN_TOP + plot_height / 2.0
+    ));
+    // The dates of the first and last tests
+    let label_y = TREND_HEIGHT - TREND_MARGIN_BOTTOM + 20.0;
+    svg.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\" fill=\"#495057\">{}</text>",
+        x(first), label_y, if span > 0.0 { "start" } else { "middle" }, first.date()));
+    if span > 0.0 {
+        let last = points[points.len() - 1].0;
+        svg.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\" fill=\"#495057\">{}</text>", x(last), label_y, last.date()));
+    }
+
+    for pair in points.windows(2) {
+        svg.push_str(&format!(
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"2\" />",
+            x(pair[0].0), y(pair[0].1), x(pair[1].0), y(pair[1].1), classification_colour(pair[1].2)
+        ));
+    }
+    for &(time, log_mar, classification) in points.iter() {
+        let significant = matches!(classification, Some(ChangeClassification::Worsened) | Some(ChangeClassification::Improved));
+        svg.push_str(&format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"{}\"><title>{}: {:.2} logMAR</title></circle>",
+            x(time), y(log_mar), if significant { 6 } else { 4 }, classification_colour(classification), time.date(), log_mar
+        ));
+    }
+    svg.push_str("</svg>");
+    Some(svg)
+}
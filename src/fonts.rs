@@ -0,0 +1,124 @@
+use std::fmt;
+use std::error::Error;
+use std::fs;
+use rusttype::{Font, Scale};
+
+use crate::optotypes::OptotypeDefinition;
+
+/// The web fonts which are served to browsers for each set of optotypes, alongside the
+/// magic number (the first four bytes of the file) which each type of font file starts with.
+/// The answers template loads these from `/static/fonts/<name>-webfont.<extension>`.
+pub const WEB_FONT_FORMATS: [(&str, &[u8; 4]); 2] = [("woff2", b"wOF2"), ("woff", b"wOFF")];
+
+/// Returns the path of the TrueType font for a particular set of optotypes. This is the font which
+/// is embedded into the PDFs, and which is checked for glyph coverage.
+pub fn get_ttf_path(optotype_definition: &OptotypeDefinition) -> String {
+    format!("static/fonts/{}.ttf", optotype_definition.name)
+}
+
+/// Returns the path of a web font (used by the answer sheets) for a particular set of optotypes
+pub fn get_web_font_path(optotype_definition: &OptotypeDefinition, extension: &str) -> String {
+    format!("static/fonts/{}-webfont.{}", optotype_definition.name, extension)
+}
+
+/// Checks that the fonts for a set of optotypes exist and are usable, so that a missing or
+/// incomplete font is reported when the server starts rather than being shown to patients as
+/// blank boxes.
+///
+/// The TrueType font is parsed, and every optotype must map to a glyph which has an outline and
+/// a non-zero advance width. The font must also have sensible vertical metrics. The web fonts
+/// are compressed, so these are only checked for existence, a valid signature, and a length
+/// which matches the length declared in their header.
+pub fn check_optotype_fonts(optotype_definition: &OptotypeDefinition) -> Result<(), Vec<FontError>> {
+    let mut errors: Vec<FontError> = Vec::new();
+    let name = &optotype_definition.name;
+
+    // Check the TrueType font first, because this is the only one which can be checked for glyphs
+    let ttf_path = get_ttf_path(optotype_definition);
+    match fs::read(&ttf_path) {
+        Ok(bytes) => match Font::from_bytes(bytes) {
+            Ok(font) => { errors.append(&mut check_glyphs(&font, optotype_definition)); },
+            Err(e) => { errors.push(FontError(format!("The font {} for the \"{}\" optotypes could not be parsed ({}).", ttf_path, name, e))); }
+        },
+        Err(e) => { errors.push(FontError(format!("The font {} for the \"{}\" optotypes could not be read ({}).", ttf_path, name, e))); }
+    };
+
+    // Then check each of the web fonts
+    for (extension, signature) in WEB_FONT_FORMATS.iter() {
+        let path = get_web_font_path(optotype_definition, extension);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                errors.push(FontError(format!("The web font {} for the \"{}\" optotypes could not be read ({}).", path, name, e)));
+                continue;
+            }
+        };
+        // Both WOFF and WOFF2 headers start with the signature, a 4 byte "flavour", and then the total
+        // length of the file as a big-endian u32.
+        if bytes.len() < 12 || &bytes[0..4] != *signature {
+            errors.push(FontError(format!("The web font {} for the \"{}\" optotypes is not a valid {} file.", path, name, extension)));
+            continue;
+        }
+        let declared_length = u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize;
+        if declared_length != bytes.len() {
+            errors.push(FontError(format!("The web font {} for the \"{}\" optotypes appears to be truncated (expected {} bytes, found {}).", path, name, declared_length, bytes.len())));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Checks that the font contains a usable glyph for every optotype, and that its metrics are sensible
+fn check_glyphs(font: &Font, optotype_definition: &OptotypeDefinition) -> Vec<FontError> {
+    let mut errors: Vec<FontError> = Vec::new();
+    let name = &optotype_definition.name;
+    let v_metrics = font.v_metrics_unscaled();
+    if font.units_per_em() == 0 || v_metrics.ascent <= v_metrics.descent {
+        errors.push(FontError(format!("The font for the \"{}\" optotypes has invalid vertical metrics.", name)));
+    }
+    for optotype in optotype_definition.optotypes.iter() {
+        let glyph = font.glyph(*optotype);
+        // Glyph 0 is the ".notdef" glyph, which fonts fall back to when they don't contain a character
+        if glyph.id().0 == 0 {
+            errors.push(FontError(format!("The font for the \"{}\" optotypes does not contain the optotype '{}'.", name, optotype)));
+            continue;
+        }
+        let scaled_glyph = glyph.scaled(Scale::uniform(100.0));
+        if scaled_glyph.exact_bounding_box().is_none() {
+            errors.push(FontError(format!("The font for the \"{}\" optotypes has an empty glyph for the optotype '{}'.", name, optotype)));
+        }
+        if scaled_glyph.h_metrics().advance_width <= 0.0 {
+            errors.push(FontError(format!("The font for the \"{}\" optotypes has a zero-width glyph for the optotype '{}'.", name, optotype)));
+        }
+    }
+    errors
+}
+
+/// Checks the fonts for every optotype definition, returning all of the problems which were found
+pub fn check_all_fonts() -> Result<(), Vec<FontError>> {
+    let mut errors: Vec<FontError> = Vec::new();
+    for optotype_definition in OptotypeDefinition::all() {
+        if let Err(mut definition_errors) = check_optotype_fonts(&optotype_definition) {
+            errors.append(&mut definition_errors);
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// This struct is used to represent problems found when checking the optotype fonts.
+#[derive(Debug)]
+pub struct FontError(pub String);
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error: {}", self.0)
+    }
+}
+impl Error for FontError {}
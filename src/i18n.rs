@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use fluent_bundle::concurrent::FluentBundle;
+use rocket::{Outcome, State};
+use rocket::request::{self, FromRequest, Request};
+use rocket_contrib::templates::Template;
+use rocket_contrib::templates::tera;
+use serde::Serialize;
+use serde_json::Value;
+use unic_langid::LanguageIdentifier;
+
+use crate::codes::{CodeError, CodeErrorKind, NUM_ROWS};
+use crate::config::AppConfig;
+use crate::monitoring::{MonitoringError, MonitoringErrorKind};
+use crate::scoring::{Score, ScoringError, ScoringErrorKind};
+
+/// The query parameter which chooses the language of a page, overriding the Accept-Language header
+pub const LANGUAGE_PARAMETER: &str = "lang";
+
+/// The languages which the site is translated into. The messages for each are kept in a Fluent catalogue
+/// in the "locales" directory, named after the language code.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Locale {
+    English,
+    Welsh,
+    Polish
+}
+
+impl Locale {
+    pub fn all() -> [Locale; 3] {
+        [Locale::English, Locale::Welsh, Locale::Polish]
+    }
+
+    /// The ISO 639-1 code for the language
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::Welsh => "cy",
+            Locale::Polish => "pl"
+        }
+    }
+
+    /// Finds the locale for a language tag (e.g. "cy" or "cy-GB"), ignoring the region
+    pub fn from_code(code: &str) -> Option<Locale> {
+        let language = code.trim().split(['-', '_']).next().unwrap_or("").to_lowercase();
+        Locale::all().iter().cloned().find(|locale| locale.code() == language)
+    }
+
+    /// Chooses the most preferred locale from an Accept-Language header (e.g. "pl-PL,pl;q=0.9,en;q=0.8").
+    /// Returns None if none of the languages in the header are supported.
+    pub fn from_accept_language(header: &str) -> Option<Locale> {
+        let mut languages: Vec<(f64, Locale)> = header.split(',')
+            .filter_map(|language| {
+                let mut parts = language.split(';');
+                let locale = Locale::from_code(parts.next()?)?;
+                let quality = parts.filter_map(|part| part.trim().strip_prefix("q="))
+                    .find_map(|quality| quality.parse::<f64>().ok())
+                    .unwrap_or(1.0);
+                Some((quality, locale))
+            })
+            .filter(|&(quality, _)| quality > 0.0)
+            .collect();
+        // The sort is stable, so languages with the same quality stay in the order they were given
+        languages.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        languages.first().map(|&(_, locale)| locale)
+    }
+
+    fn catalogue(&self) -> &'static str {
+        match self {
+            Locale::English => include_str!("../locales/en.ftl"),
+            Locale::Welsh => include_str!("../locales/cy.ftl"),
+            Locale::Polish => include_str!("../locales/pl.ftl")
+        }
+    }
+}
+
+/// Parses the Fluent catalogue for a locale. The catalogues are compiled in, so errors in them are found by the tests.
+pub fn load_bundle(locale: Locale) -> Result<FluentBundle<FluentResource>, String> {
+    let resource = FluentResource::try_new(locale.catalogue().to_string())
+        .map_err(|(_, errors)| format!("The {} catalogue could not be parsed ({:?}).", locale.code(), errors))?;
+    let language: LanguageIdentifier = locale.code().parse().map_err(|e| format!("{:?}", e))?;
+    let mut bundle = FluentBundle::new_concurrent(vec![language]);
+    // Unicode isolation marks around arguments would be printed in the PDFs, which only use the text font's glyphs
+    bundle.set_use_isolating(false);
+    bundle.add_resource(resource).map_err(|errors| format!("The {} catalogue has duplicate messages ({:?}).", locale.code(), errors))?;
+    Ok(bundle)
+}
+
+fn bundles() -> &'static HashMap<Locale, FluentBundle<FluentResource>> {
+    static BUNDLES: OnceLock<HashMap<Locale, FluentBundle<FluentResource>>> = OnceLock::new();
+    BUNDLES.get_or_init(|| Locale::all().iter()
+        .filter_map(|&locale| match load_bundle(locale) {
+            Ok(bundle) => Some((locale, bundle)),
+            Err(e) => {
+                log::error!("Error: {}", e);
+                None
+            }
+        })
+        .collect())
+}
+
+fn format_message(locale: Locale, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+    let bundle = bundles().get(&locale)?;
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut errors = Vec::new();
+    Some(bundle.format_pattern(pattern, args, &mut errors).into_owned())
+}
+
+/// Translates a message into the given locale, falling back to English if the message hasn't been translated.
+/// If the message doesn't exist at all, its id is returned so that the missing message is easy to spot.
+pub fn translate(locale: Locale, id: &str, args: &[(&str, String)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, FluentValue::from(value.clone()));
+    }
+    format_message(locale, id, Some(&fluent_args))
+        .or_else(|| format_message(Locale::English, id, Some(&fluent_args)))
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Lists the ids of every message in a locale's catalogue
+pub fn message_ids(locale: Locale) -> Vec<String> {
+    match FluentResource::try_new(locale.catalogue().to_string()) {
+        Ok(resource) => resource.entries()
+            .filter_map(|entry| match entry {
+                fluent_syntax::ast::Entry::Message(message) => Some(message.id.name.to_string()),
+                _ => None
+            })
+            .collect(),
+        Err(_) => Vec::new()
+    }
+}
+
+/// Describes an error in a code in the given locale. The message depends only on the kind of error, so the
+/// technical details in the English CodeError message (such as the CRC values) are not shown.
+pub fn describe_code_error(locale: Locale, error: &CodeError) -> String {
+    let id = match error.kind {
+        CodeErrorKind::InvalidCharacter => "code-error-invalid-character",
+        CodeErrorKind::InvalidLength => "code-error-invalid-length",
+        CodeErrorKind::OutOfRange => "code-error-out-of-range",
+        CodeErrorKind::InvalidField => "code-error-invalid-field",
+        CodeErrorKind::CrcMismatch => "code-error-crc-mismatch",
+        CodeErrorKind::OptotypeOutOfRange => "code-error-optotype-out-of-range",
+        CodeErrorKind::RepeatedOptotype => "code-error-repeated-optotype"
+    };
+    translate(locale, id, &[])
+}
+
+/// Describes an error in scoring or marking a chart in the given locale
+pub fn describe_scoring_error(locale: Locale, error: &ScoringError) -> String {
+    let (id, args) = match error.kind {
+        ScoringErrorKind::TooManyCorrect { optotypes } => ("scoring-error-too-many-correct", vec![("optotypes", optotypes.to_string())]),
+        ScoringErrorKind::UnexpectedLayout => ("scoring-error-unexpected-layout", vec![]),
+        ScoringErrorKind::DistanceOutOfRange { min, max } => ("scoring-error-distance-out-of-range", vec![("min", min.to_string()), ("max", max.to_string())]),
+        ScoringErrorKind::MissingRows => ("scoring-error-missing-rows", vec![]),
+        ScoringErrorKind::TooManyCorrectOnRow { row, optotypes, correct } => ("scoring-error-too-many-correct-on-row",
+            vec![("row", row.to_string()), ("optotypes", optotypes.to_string()), ("correct", correct.to_string())]),
+        ScoringErrorKind::TooManyRows { responses, rows } => ("scoring-error-too-many-rows", vec![("responses", responses.to_string()), ("rows", rows.to_string())]),
+        ScoringErrorKind::TooManyResponses { row, optotypes, responses } => ("scoring-error-too-many-responses",
+            vec![("row", row.to_string()), ("optotypes", optotypes.to_string()), ("responses", responses.to_string())])
+    };
+    translate(locale, id, &args)
+}
+
+/// Describes an error in comparing sessions in the given locale. Unrecognised acuities are described by their
+/// line, rather than by the English details of the AcuityError.
+pub fn describe_monitoring_error(locale: Locale, error: &MonitoringError) -> String {
+    let (id, line) = match error.kind {
+        MonitoringErrorKind::UnknownEye => ("monitoring-error-unknown-eye", None),
+        MonitoringErrorKind::TooFewSessions => ("monitoring-error-too-few-sessions", None),
+        MonitoringErrorKind::InvalidLimit => ("monitoring-error-invalid-limit", None),
+        MonitoringErrorKind::MixedEyes => ("monitoring-error-mixed-eyes", None),
+        MonitoringErrorKind::IncompleteLine { line } => ("monitoring-error-incomplete-line", Some(line)),
+        MonitoringErrorKind::InvalidDate { line } => ("monitoring-error-invalid-date", Some(line)),
+        MonitoringErrorKind::InvalidAcuity { line } => ("monitoring-error-invalid-acuity", Some(line))
+    };
+    let args: Vec<(&str, String)> = line.iter().map(|line| ("line", line.to_string())).collect();
+    translate(locale, id, &args)
+}
+
+/// Describes a score in the given locale. In English, this is the same as the description in the score itself.
+pub fn describe_score(locale: Locale, score: &Score) -> String {
+    let describe_row = |row: usize| format!("{} ({})", score.row_acuity[row], score.row_acuity[row].snellen_metric());
+    match score.threshold_row {
+        None => translate(locale, "score-worse-than", &[("acuity", describe_row(0))]),
+        Some(row) if row == NUM_ROWS - 1 => translate(locale, "score-at-least", &[("acuity", describe_row(row))]),
+        Some(row) => translate(locale, "score-between", &[("acuity", describe_row(row)), ("next", describe_row(row + 1))])
+    }
+}
+
+/// Escapes text so that it can be included in HTML
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&#x27;")
+}
+
+/// The "t" function for templates, which translates a message: {{ t(key="score-heading", lang=lang) }}. Any other
+/// arguments are passed to the message, so {{ t(key="score-code", lang=lang, code=code) }} sets $code. The arguments
+/// are escaped, because messages containing markup are marked as safe in the templates.
+pub fn tera_translate(args: HashMap<String, Value>) -> tera::Result<Value> {
+    let id = match args.get("key").and_then(Value::as_str) {
+        Some(id) => id,
+        None => { return Err("The t function needs a key.".into()); }
+    };
+    let locale = args.get("lang").and_then(Value::as_str).and_then(Locale::from_code).unwrap_or(Locale::English);
+    let message_args: Vec<(&str, String)> = args.iter()
+        .filter(|&(name, _)| name != "key" && name != "lang")
+        .map(|(name, value)| (name.as_str(), match value {
+            Value::String(text) => escape_html(text),
+            // Whole numbers are shown without a decimal point (e.g. "150" rather than "150.0")
+            Value::Number(number) => number.as_f64().map(|number| number.to_string()).unwrap_or_else(|| number.to_string()),
+            other => other.to_string()
+        }))
+        .collect();
+    Ok(Value::String(translate(locale, id, &message_args)))
+}
+
+/// A link to the current page in another language
+#[derive(Serialize)]
+pub struct LanguageLink {
+    pub code: &'static str,
+    pub name: String,
+    pub href: String
+}
+
+/// The context for a template, with the language added so that the template can translate its messages. The
+/// site's settings are added too, because the forms and links on most pages depend on them.
+#[derive(Serialize)]
+struct Localised<'a, T: Serialize> {
+    lang: &'static str,
+    languages: Vec<LanguageLink>,
+    site: &'a AppConfig,
+    #[serde(flatten)]
+    context: T
+}
+
+/// A request guard which chooses the locale for a request, from the "lang" query parameter if there is one and
+/// otherwise from the Accept-Language header. Pages are shown in English if neither is given or supported. The
+/// choice isn't remembered between pages, because patients are never given cookies.
+pub struct Localisation {
+    pub locale: Locale,
+    /// The path and query of the request, without the "lang" parameter
+    uri: (String, Vec<String>),
+    site: AppConfig
+}
+
+impl Localisation {
+    /// Renders a template, adding the language and links to the page in the other languages to the context
+    pub fn render<T: Serialize>(&self, name: &'static str, context: T) -> Template {
+        let (path, query) = &self.uri;
+        let languages = Locale::all().iter()
+            .map(|locale| {
+                let mut parameters = query.clone();
+                parameters.push(format!("{}={}", LANGUAGE_PARAMETER, locale.code()));
+                LanguageLink {
+                    code: locale.code(),
+                    name: translate(*locale, "language-name", &[]),
+                    href: format!("{}?{}", path, parameters.join("&"))
+                }
+            })
+            .collect();
+        Template::render(name, Localised { lang: self.locale.code(), languages, site: &self.site, context })
+    }
+
+    pub fn translate(&self, id: &str, args: &[(&str, String)]) -> String {
+        translate(self.locale, id, args)
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Localisation {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let from_query = request.get_query_value::<String>(LANGUAGE_PARAMETER)
+            .and_then(|code| code.ok())
+            .and_then(|code| Locale::from_code(&code));
+        let from_header = || request.headers().get_one("Accept-Language").and_then(Locale::from_accept_language);
+        let query = request.uri().query().unwrap_or("")
+            .split('&')
+            .filter(|parameter| !parameter.is_empty() && !parameter.starts_with(&format!("{}=", LANGUAGE_PARAMETER)))
+            .map(String::from)
+            .collect();
+        Outcome::Success(Localisation {
+            locale: from_query.or_else(from_header).unwrap_or(Locale::English),
+            uri: (request.uri().path().to_string(), query),
+            site: request.guard::<State<AppConfig>>().succeeded().map(|app_config| app_config.clone()).unwrap_or_default()
+        })
+    }
+}
@@ -4,75 +4,726 @@
 extern crate rocket_contrib;
 extern crate rand;
 extern crate ux;
+extern crate printpdf;
+extern crate rusttype;
 #[macro_use] extern crate serde;
 
 #[cfg(test)] mod tests;
+mod acuity;
+mod audit;
 mod codes;
+mod constraints;
+mod fonts;
 mod optotypes;
+mod pdf;
+mod scoring;
+mod marking;
+mod monitoring;
+mod statistics;
+mod metrics;
+mod catchers;
+mod config;
+mod i18n;
+mod api;
+mod rate_limit;
+mod openapi;
+mod results;
+mod dashboard;
+mod auth;
+mod sqlite;
 
 use rocket_contrib::templates::Template;
 use std::collections::HashMap;
-use rocket::http::RawStr;
-use rocket_contrib::serve::StaticFiles;
 use std::str::FromStr;
+use rocket::http::{Cookie, Cookies, RawStr, Status, ContentType, Accept, MediaType};
+use rocket::http::uri::Uri;
+use rocket::response::status;
+use rocket::Request;
+use rocket::response::{self, Redirect, Responder};
+use rocket::response::content::Content;
+use rocket::fairing::AdHoc;
+use rocket::State;
+use rocket::request::LenientForm;
+use rocket_contrib::serve::StaticFiles;
+use rocket_contrib::json::Json;
 
-use crate::optotypes::OptotypeArrangement;
-use crate::codes::{short::ShortCode, long::LongCode};
+use crate::audit::{AuditLog, Auditor};
+use crate::auth::ResultsAccess;
+use crate::results::{IssuedChart, PatientId, ResultRepository};
+use crate::codes::TestMode;
+use crate::rate_limit::RateLimit;
+use crate::config::AppConfig;
+use crate::i18n::Localisation;
+use crate::optotypes::{OptotypeArrangement, OptotypeDefinition};
+use crate::scoring::Score;
+use crate::marking::{MarkedChart, TerminationRule};
+use crate::monitoring::{ChangeReport, Eye, DEFAULT_TEST_RETEST_LIMIT};
 
 #[get("/")]
-fn index() -> Template {
-    Template::render("index", HashMap::<String, String>::new())
+fn index(localisation: Localisation) -> Template {
+    localisation.render("index", HashMap::<String, String>::new())
 }
 
 /// If the user attempts to access the answer form without specifying the code, then simply
 /// display the form into which they can enter the code.
 #[get("/answers")]
-fn code_form() -> Template {
+fn code_form(localisation: Localisation) -> Template {
     let context = HashMap::<String, String>::new();
-    Template::render("code-form", context)
+    localisation.render("code-form", context)
 }
 
-/// Display a (not to scale) answer sheet for any given code.
-#[get("/answers?<code>")]
-fn answer_display(code: &RawStr) -> Template {
+/// The formats in which the answer sheet can be returned
+#[derive(Debug, PartialEq)]
+enum AnswerFormat {
+    Html,
+    Json,
+    Pdf
+}
 
-    // Define some HashMaps to store the template context generated by attempting to parse the code
-    let mut error_messages = HashMap::<String, String>::new();
-    let mut context = HashMap::<String, OptotypeArrangement>::new();
+impl AnswerFormat {
+    /// Chooses the format of the answer sheet. An explicit "format" query parameter takes priority, so that
+    /// links can ask for a particular format, and otherwise the preferred media type in the Accept header is
+    /// used. Browsers (and anything else which doesn't ask for JSON or a PDF) get the HTML page.
+    fn choose(format: Option<&str>, accept: Option<&Accept>) -> Option<AnswerFormat> {
+        match format.map(|format| format.to_lowercase()) {
+            Some(format) => match format.as_str() {
+                "html" => Some(AnswerFormat::Html),
+                "json" => Some(AnswerFormat::Json),
+                "pdf" => Some(AnswerFormat::Pdf),
+                _ => None
+            },
+            None => {
+                let media_type = accept.map(|accept| accept.preferred().media_type());
+                if media_type == Some(&MediaType::JSON) {
+                    Some(AnswerFormat::Json)
+                } else if media_type == Some(&MediaType::PDF) {
+                    Some(AnswerFormat::Pdf)
+                } else {
+                    Some(AnswerFormat::Html)
+                }
+            }
+        }
+    }
+}
 
-    // Attempt to parse the code using the length as an indicator of what type of code it is
-    match code.to_string().replace("-", "").len() {
-        6 => {
-            match ShortCode::from_str(&code.to_string()) {
-                Ok(parsed_code) => { context.insert("arrangement".to_string(), OptotypeArrangement::from(parsed_code)); },
-                Err(e) => { error_messages.insert("error".to_string(), format!("{}", e)); }
-            };
+/// The answer sheet, in whichever format was asked for
+#[derive(Responder)]
+enum AnswerResponse {
+    Html(Template),
+    Json(Result<Json<api::DecodedCode>, status::Custom<Json<api::ApiError>>>),
+    Pdf(Result<Content<Vec<u8>>, Status>),
+    Invalid(Status)
+}
+
+/// The answer sheet, marked as depending on the Accept header so that caches don't give a PDF to a browser which
+/// asked for the HTML page (or the other way round)
+struct NegotiatedAnswers(AnswerResponse);
+
+impl<'r> Responder<'r> for NegotiatedAnswers {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+        let mut response = self.0.respond_to(request)?;
+        response.set_raw_header("Vary", "Accept");
+        Ok(response)
+    }
+}
+
+/// Display a (not to scale) answer sheet for any given code. The same link can also return the decoded code
+/// as JSON, or a printable PDF answer key, depending on the "format" parameter or the Accept header.
+#[get("/answers?<code>&<format>")]
+fn answer_display(code: &RawStr, format: Option<String>, accept: Option<&Accept>, _rate_limit: RateLimit, auditor: Auditor, localisation: Localisation, app_config: State<AppConfig>) -> NegotiatedAnswers {
+    let format = match AnswerFormat::choose(format.as_deref(), accept) {
+        Some(format) => format,
+        None => { return NegotiatedAnswers(AnswerResponse::Invalid(Status::BadRequest)); }
+    };
+    NegotiatedAnswers(match format {
+        AnswerFormat::Json => AnswerResponse::Json(api::decode(code.as_str(), app_config.default_distance, false).map(Json).map_err(api::code_error_response)),
+        AnswerFormat::Pdf => AnswerResponse::Pdf(match OptotypeArrangement::from_code(code.as_str()).inspect_err(metrics::record_code_error) {
+            Ok(arrangement) => match metrics::time_pdf("answer-key", || pdf::answers::generate(&arrangement, app_config.default_distance, localisation.locale, app_config.paper_size)) {
+                Ok(bytes) => {
+                    auditor.record(&audit::describe_sheet("answer-key", &arrangement));
+                    Ok(Content(ContentType::PDF, bytes))
+                },
+                Err(_) => Err(Status::InternalServerError)
+            },
+            // The same statuses as the JSON format, so that scripts can handle errors in the same way
+            Err(e) => Err(api::code_error_status(e.kind))
+        }),
+        // Attempt to parse the code. Display errors if there were any, if not, display the optotypes
+        AnswerFormat::Html => AnswerResponse::Html(match OptotypeArrangement::from_code(code.as_str()).inspect_err(metrics::record_code_error) {
+            Ok(arrangement) => {
+                let mut context = HashMap::<String, OptotypeArrangement>::new();
+                context.insert("arrangement".to_string(), arrangement);
+                localisation.render("answers", context)
+            },
+            Err(e) => {
+                let mut error_messages = HashMap::<String, String>::new();
+                error_messages.insert("error".to_string(), i18n::describe_code_error(localisation.locale, &e));
+                localisation.render("code-form", error_messages)
+            }
+        })
+    })
+}
+
+/// The template context for the score page
+#[derive(Serialize)]
+struct ScoreContext {
+    code: Option<String>,
+    /// The link to the answers for the code, with the code percent-encoded
+    answers_link: Option<String>,
+    chart_distance: f64,
+    score: Option<Score>,
+    error: Option<String>
+}
+
+/// Score a test from the viewing distance and the number of optotypes which were read correctly. The code is
+/// optional (every chart has the same layout of rows), but if it is given it is checked and linked back to.
+/// Charts are assumed to be printed for the default distance, unless the distance they were printed for is given.
+#[get("/score?<code>&<distance>&<correct>&<chart_distance>")]
+fn score_display(code: Option<String>, distance: Option<f64>, correct: Option<u32>, chart_distance: Option<f64>, localisation: Localisation, app_config: State<AppConfig>) -> Template {
+    let code = code.filter(|code| !code.is_empty());
+    let chart_distance = chart_distance.unwrap_or(app_config.default_distance);
+    let result = match (&code, distance, correct) {
+        (_, None, _) | (_, _, None) => Err(localisation.translate("score-missing-values", &[])),
+        (Some(code), Some(distance), Some(correct)) => match OptotypeArrangement::from_code(code).inspect_err(metrics::record_code_error) {
+            Ok(arrangement) => scoring::score_arrangement(&arrangement, distance, chart_distance, correct).map_err(|e| i18n::describe_scoring_error(localisation.locale, &e)),
+            Err(e) => Err(i18n::describe_code_error(localisation.locale, &e))
         },
-        16 => {
-            match LongCode::from_str(&code.to_string()) {
-                Ok(parsed_code) => { context.insert("arrangement".to_string(), OptotypeArrangement::from(parsed_code)); },
-                Err(e) => { error_messages.insert("error".to_string(), format!("{}", e)); }
-            };
+        (None, Some(distance), Some(correct)) => scoring::score_count(distance, chart_distance, correct).map_err(|e| i18n::describe_scoring_error(localisation.locale, &e))
+    };
+    let answers_link = code.as_ref().map(|code| format!("/answers?code={}", Uri::percent_encode(code)));
+    let context = match result {
+        Ok(mut score) => {
+            score.description = i18n::describe_score(localisation.locale, &score);
+            ScoreContext { code, answers_link, chart_distance, score: Some(score), error: None }
+        },
+        Err(error) => ScoreContext { code, answers_link, chart_distance, score: None, error: Some(error) }
+    };
+    localisation.render("score", context)
+}
+
+/// The template context for the marking page
+#[derive(Serialize)]
+struct MarkContext {
+    arrangement: OptotypeArrangement,
+    distance: Option<f64>,
+    chart_distance: f64,
+    responses: Option<String>,
+    max_errors: Option<u32>,
+    marked: Option<MarkedChart>,
+    error: Option<String>
+}
+
+/// Mark what the patient said for each optotype on a chart. Without any responses this just shows the
+/// form; otherwise the responses are marked and shown on an answer sheet along with the score.
+#[get("/mark?<code>&<distance>&<responses>&<max_errors>&<chart_distance>")]
+fn mark_display(code: &RawStr, distance: Option<f64>, responses: Option<String>, max_errors: Option<u32>, chart_distance: Option<f64>, localisation: Localisation, app_config: State<AppConfig>) -> Template {
+    let chart_distance = chart_distance.unwrap_or(app_config.default_distance);
+    let arrangement = match OptotypeArrangement::from_code(code.as_str()).inspect_err(metrics::record_code_error) {
+        Ok(arrangement) => arrangement,
+        Err(e) => {
+            let mut error_messages = HashMap::<String, String>::new();
+            error_messages.insert("error".to_string(), i18n::describe_code_error(localisation.locale, &e));
+            return localisation.render("code-form", error_messages);
+        }
+    };
+    let responses = responses.filter(|responses| !responses.trim().is_empty());
+    let result = match (&responses, distance) {
+        (None, _) => Ok(None),
+        (Some(_), None) => Err(localisation.translate("mark-missing-distance", &[])),
+        (Some(responses), Some(distance)) => {
+            let termination_rule = TerminationRule { max_errors_per_row: max_errors };
+            marking::mark(&arrangement, &marking::parse_responses(responses), termination_rule, distance, chart_distance)
+                .map(|mut marked| {
+                    marked.score.description = i18n::describe_score(localisation.locale, &marked.score);
+                    Some(marked)
+                })
+                .map_err(|e| i18n::describe_scoring_error(localisation.locale, &e))
+        }
+    };
+    let (marked, error) = match result {
+        Ok(marked) => (marked, None),
+        Err(error) => (None, Some(error))
+    };
+    localisation.render("mark", MarkContext {
+        arrangement,
+        distance,
+        chart_distance,
+        responses,
+        max_errors,
+        marked,
+        error
+    })
+}
+
+/// The self-checking form. Each page submits the rows entered so far (as hidden responses) along with the row
+/// which has just been read, so no state is kept on the server between rows.
+#[derive(FromForm)]
+struct SelfCheckForm {
+    code: Option<String>,
+    distance: Option<f64>,
+    chart_distance: Option<f64>,
+    responses: Option<String>,
+    row: Option<String>,
+    finish: Option<bool>
+}
+
+/// The template context for the self-checking page. Until the chart is marked, only the optotype set is
+/// included (for the names which can be typed), so the page never contains the answers to the rows.
+#[derive(Serialize)]
+struct SelfCheckContext {
+    code: Option<String>,
+    distance: Option<f64>,
+    chart_distance: f64,
+    responses: String,
+    /// The row (from the top of the chart, starting at 1) which the patient should read next
+    row: Option<usize>,
+    num_rows: usize,
+    num_optotypes: usize,
+    optotype_definition: Option<OptotypeDefinition>,
+    arrangement: Option<OptotypeArrangement>,
+    marked: Option<MarkedChart>,
+    error: Option<String>
+}
+
+/// Lets a patient check their own answers to a self-assessment chart. They enter the code, then type what
+/// they read on each row from the top down, and only see the marked chart and score once they have finished.
+#[get("/self-check?<form..>")]
+fn self_check(form: LenientForm<SelfCheckForm>, localisation: Localisation, app_config: State<AppConfig>) -> Template {
+    let form = form.into_inner();
+    let code = form.code.filter(|code| !code.trim().is_empty());
+    let mut context = SelfCheckContext {
+        code: code.clone(),
+        distance: form.distance,
+        chart_distance: form.chart_distance.unwrap_or(app_config.default_distance),
+        responses: String::new(),
+        row: None,
+        num_rows: 0,
+        num_optotypes: 0,
+        optotype_definition: None,
+        arrangement: None,
+        marked: None,
+        error: None
+    };
+    // Without a valid code and distances, the patient is asked for them again
+    let code = match code {
+        Some(code) => code,
+        None => { return localisation.render("self-check", context); }
+    };
+    let arrangement = match OptotypeArrangement::from_code(&code).inspect_err(metrics::record_code_error) {
+        // Short codes are read over the telephone, and the clinician marks the answers
+        Ok(_) if !matches!(codes::CodeKind::from_code(&code), Ok(codes::CodeKind::Long)) => {
+            context.error = Some(localisation.translate("self-check-short-code", &[]));
+            return localisation.render("self-check", context);
         },
-        _ => { error_messages.insert("error".to_string(), "Error: Invalid code length.".to_string()); }
-    }   
+        Ok(arrangement) => arrangement,
+        Err(e) => {
+            context.error = Some(i18n::describe_code_error(localisation.locale, &e));
+            return localisation.render("self-check", context);
+        }
+    };
+    let distance = match form.distance {
+        Some(distance) => distance,
+        None => {
+            context.error = Some(localisation.translate("self-check-missing-distance", &[]));
+            return localisation.render("self-check", context);
+        }
+    };
+    if let Err(e) = app_config.check_distance(distance).and_then(|_| app_config.check_distance(context.chart_distance)) {
+        context.error = Some(i18n::describe_scoring_error(localisation.locale, &e));
+        return localisation.render("self-check", context);
+    }
+
+    let mut responses = marking::parse_responses(form.responses.as_deref().unwrap_or(""));
+    let finish = form.finish.unwrap_or(false);
+    if let (Some(row), Some(presented)) = (form.row, arrangement.rows.get(responses.len())) {
+        let mut row_responses = marking::parse_responses(&row).concat();
+        if row_responses.len() > presented.optotypes.len() {
+            context.error = Some(localisation.translate("self-check-too-many", &[("typed", row_responses.len().to_string()), ("count", presented.optotypes.len().to_string())]));
+        } else if !(finish && row_responses.is_empty()) {
+            // Positions which were left blank at the end of the row still count as not read
+            row_responses.resize(presented.optotypes.len(), String::new());
+            responses.push(row_responses);
+        }
+    }
 
-    // Display errors if there were any, if not, display the optotypes
-    if error_messages.len() > 0 {
-        Template::render("code-form", error_messages)
+    if context.error.is_none() && (finish || responses.len() >= arrangement.rows.len()) {
+        let termination_rule = TerminationRule { max_errors_per_row: None };
+        match marking::mark(&arrangement, &responses, termination_rule, distance, context.chart_distance) {
+            Ok(marked) => {
+                let mut marked = marked.stopped_after(responses.len());
+                marked.score.description = i18n::describe_score(localisation.locale, &marked.score);
+                context.marked = Some(marked);
+                context.arrangement = Some(arrangement);
+            },
+            Err(e) => { context.error = Some(i18n::describe_scoring_error(localisation.locale, &e)); }
+        }
     } else {
-        Template::render("answers", context)
+        context.row = Some(responses.len() + 1);
+        context.num_rows = arrangement.rows.len();
+        context.num_optotypes = arrangement.rows[responses.len()].optotypes.len();
+        context.responses = marking::format_responses(&responses);
+        context.optotype_definition = Some(arrangement.optotype_definition);
+    }
+    localisation.render("self-check", context)
+}
+
+/// The template context for the monitoring page
+#[derive(Serialize)]
+struct MonitorContext {
+    sessions: Option<String>,
+    eye: Option<Eye>,
+    limit: f64,
+    report: Option<ChangeReport>,
+    error: Option<String>
+}
+
+/// Compare the results of two or more tests of the same eye, to see whether the vision has changed by more than
+/// the test-retest limit. The results are typed in as one test per line (see `monitoring::parse_sessions`).
+#[get("/monitor?<sessions>&<eye>&<limit>")]
+fn monitor_display(sessions: Option<String>, eye: Option<String>, limit: Option<f64>, localisation: Localisation) -> Template {
+    let sessions = sessions.filter(|sessions| !sessions.trim().is_empty());
+    let limit = limit.unwrap_or(DEFAULT_TEST_RETEST_LIMIT);
+    let result = match (&sessions, eye.map(|eye| eye.parse::<Eye>()).transpose()) {
+        (_, Err(e)) => Err(e),
+        (None, Ok(eye)) => Ok((eye, None)),
+        (Some(sessions), Ok(eye)) => monitoring::parse_sessions(sessions, eye.unwrap_or(Eye::Right))
+            .and_then(|parsed| monitoring::compare(&parsed, limit))
+            .map(|report| (eye, Some(report)))
+    };
+    let (eye, report, error) = match result {
+        Ok((eye, report)) => (eye, report, None),
+        Err(e) => (None, None, Some(i18n::describe_monitoring_error(localisation.locale, &e)))
+    };
+    localisation.render("monitor", MonitorContext {
+        sessions,
+        eye,
+        limit,
+        report,
+        error
+    })
+}
+
+/// The template context for the list of patients
+#[derive(Serialize)]
+struct PatientsContext {
+    patients: Vec<PatientId>
+}
+
+/// Sends a clinician who hasn't given the results token yet to the sign in page, in the language they were using
+#[derive(Debug)]
+struct SignIn(i18n::Locale);
+
+impl<'r> Responder<'r> for SignIn {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+        Redirect::to(format!("/login?lang={}", self.0.code())).respond_to(request)
     }
 }
 
+/// Why a page of stored results wasn't shown: either the clinician hasn't signed in yet, or the page failed
+#[derive(Debug, Responder)]
+enum ResultsPageError {
+    SignIn(SignIn),
+    Failed(Status)
+}
+
+/// Renders a page of stored results if results are being stored, and the clinician has signed in to see them.
+/// Clinicians who haven't signed in are sent to the sign in page.
+fn results_page<'r>(repository: Option<State<'r, ResultRepository>>, access: Option<ResultsAccess>, localisation: Localisation,
+                    page: impl FnOnce(State<'r, ResultRepository>, Localisation) -> Result<Template, Status>) -> Result<Template, ResultsPageError> {
+    match (repository, access) {
+        (None, _) => Err(ResultsPageError::Failed(Status::NotFound)),
+        (Some(_), None) => Err(ResultsPageError::SignIn(SignIn(localisation.locale))),
+        (Some(repository), Some(_)) => page(repository, localisation).map_err(ResultsPageError::Failed)
+    }
+}
+
+/// List the patients with stored results, linking to the dashboard for each. This is only found when results
+/// are being stored.
+#[get("/patients")]
+fn patient_list(repository: Option<State<ResultRepository>>, access: Option<ResultsAccess>, localisation: Localisation) -> Result<Template, ResultsPageError> {
+    results_page(repository, access, localisation, |repository, localisation| {
+        let patients = repository.patients().map_err(|e| {
+            log::error!("{}", e);
+            Status::InternalServerError
+        })?;
+        Ok(localisation.render("patients", PatientsContext { patients }))
+    })
+}
+
+/// The template context for a patient's dashboard
+#[derive(Serialize)]
+struct PatientContext {
+    patient: PatientId,
+    limit: f64,
+    eyes: Vec<dashboard::EyeHistory>,
+    /// The charts which have been issued to the patient through the API
+    issued: Vec<IssuedChart>
+}
+
+/// Show a patient's stored results for each eye, with a chart of their acuity over time. Changes which are larger
+/// than the test-retest limit are highlighted, and each result links to the answer sheet for its chart.
+#[get("/patients/<patient>?<limit>")]
+fn patient_dashboard(patient: String, limit: Option<f64>, repository: Option<State<ResultRepository>>, access: Option<ResultsAccess>, localisation: Localisation) -> Result<Template, ResultsPageError> {
+    results_page(repository, access, localisation, |repository, localisation| {
+        let patient = PatientId::from_str(&patient).map_err(|_| Status::BadRequest)?;
+        let limit = limit.unwrap_or(DEFAULT_TEST_RETEST_LIMIT);
+        let results = repository.results_for(&patient).map_err(|e| {
+            log::error!("{}", e);
+            Status::InternalServerError
+        })?;
+        let issued = repository.issued_to(&patient).map_err(|e| {
+            log::error!("{}", e);
+            Status::InternalServerError
+        })?;
+        if results.is_empty() && issued.is_empty() {
+            return Err(Status::NotFound);
+        }
+        let eyes = dashboard::eye_histories(&results, limit).map_err(|_| Status::BadRequest)?;
+        Ok(localisation.render("patient", PatientContext {
+            patient,
+            limit,
+            eyes,
+            issued
+        }))
+    })
+}
+
+/// The form which clinicians sign in to the stored results with
+#[derive(FromForm)]
+struct LoginForm {
+    token: String
+}
+
+/// The template context for the sign in page
+#[derive(Serialize)]
+struct LoginContext {
+    failed: bool
+}
+
+/// Ask a clinician for the results token, so that they can see the stored results. This is only found when results
+/// are being stored.
+#[get("/login")]
+fn login_form(repository: Option<State<ResultRepository>>, localisation: Localisation) -> Result<Template, Status> {
+    repository.ok_or(Status::NotFound)?;
+    Ok(localisation.render("login", LoginContext { failed: false }))
+}
+
+/// Sign a clinician in to the stored results, if they gave the results token, by setting the results cookie. The
+/// form is shown again if they didn't, and nothing is found if results aren't being stored.
+#[post("/login", data = "<form>")]
+fn login(form: LenientForm<LoginForm>, mut cookies: Cookies, repository: Option<State<ResultRepository>>, localisation: Localisation, app_config: State<AppConfig>) -> Result<Redirect, Option<status::Custom<Template>>> {
+    repository.ok_or(None)?;
+    match &app_config.results_token {
+        Some(token) if auth::tokens_match(&form.token, token) => {
+            cookies.add_private(auth::results_cookie(token.clone()));
+            Ok(Redirect::to(format!("/patients?lang={}", localisation.locale.code())))
+        },
+        _ => Err(Some(status::Custom(Status::Unauthorized, localisation.render("login", LoginContext { failed: true }))))
+    }
+}
+
+/// Sign a clinician out of the stored results, by removing the results cookie
+#[post("/logout")]
+fn logout(mut cookies: Cookies) -> Redirect {
+    cookies.remove_private(Cookie::named(auth::RESULTS_COOKIE));
+    Redirect::to("/")
+}
+
+/// Generate a printable chart for a code, sized to be read from the given viewing distance (in cm). Charts are
+/// printed for the default distance if no distance is given. This is ranked below generate_random_chart, so that
+/// the download buttons on the homepage don't take a token from the rate limit before being redirected here.
+#[get("/generate?<code>&<distance>", rank = 2)]
+fn generate_chart(code: &RawStr, distance: Option<f64>, _rate_limit: RateLimit, auditor: Auditor, localisation: Localisation, app_config: State<AppConfig>) -> Result<Content<Vec<u8>>, Status> {
+    let arrangement = match OptotypeArrangement::from_code(code.as_str()).inspect_err(metrics::record_code_error) {
+        Ok(arrangement) => arrangement,
+        Err(_) => { return Err(Status::BadRequest); }
+    };
+    chart_pdf(&arrangement, distance, &auditor, &localisation, &app_config)
+}
+
+/// The query string of the download buttons for randomised charts on the homepage
+#[derive(FromForm)]
+struct RandomChartForm {
+    /// "adult" for a chart of letters, or "child" for a chart of symbols
+    #[form(field = "type")]
+    chart_type: String,
+    /// "telephone" for a chart with a short code, or "self" for a self-assessment chart with a long code
+    mode: String,
+    distance: Option<f64>
+}
+
+/// Issue a new randomised chart for one of the download buttons on the homepage, and redirect to its PDF. Adults
+/// are given Sloan letters and children Aukland symbols. This is ranked above generate_chart, and forwards to it
+/// when there is no chart type.
+#[get("/generate?<form..>", rank = 1)]
+fn generate_random_chart(form: LenientForm<RandomChartForm>) -> Result<Redirect, Status> {
+    let form = form.into_inner();
+    let optotypes = match form.chart_type.as_str() {
+        "adult" => "sloan",
+        "child" => "aukland",
+        _ => { return Err(Status::BadRequest); }
+    };
+    let mode = match form.mode.as_str() {
+        "telephone" => TestMode::Telephone,
+        "self" => TestMode::SelfAssessment,
+        _ => { return Err(Status::BadRequest); }
+    };
+    let optotype_definition = OptotypeDefinition::from_name(optotypes).ok_or(Status::InternalServerError)?;
+    let (_, code) = mode.generate_code(optotype_definition).map_err(|_| Status::InternalServerError)?;
+    metrics::metrics().codes_issued.with_label_values(&[&mode.to_string()]).inc();
+    match form.distance {
+        Some(distance) => Ok(Redirect::to(format!("/generate?code={}&distance={}", code, distance))),
+        None => Ok(Redirect::to(format!("/generate?code={}", code)))
+    }
+}
+
+/// Generate one of the standardised charts in the configuration (e.g. "/standard/letters"), which are the same
+/// every time they are downloaded
+#[get("/standard/<name>?<distance>")]
+fn standard_chart(name: String, distance: Option<f64>, _rate_limit: RateLimit, auditor: Auditor, localisation: Localisation, app_config: State<AppConfig>) -> Result<Content<Vec<u8>>, Status> {
+    let chart = match app_config.standard_chart(&name) {
+        Some(chart) => chart,
+        None => { return Err(Status::NotFound); }
+    };
+    // The codes are checked when the server starts, so this only fails if the code has stopped decoding
+    let arrangement = match OptotypeArrangement::from_code(&chart.code) {
+        Ok(arrangement) => arrangement,
+        Err(_) => { return Err(Status::InternalServerError); }
+    };
+    chart_pdf(&arrangement, distance, &auditor, &localisation, &app_config)
+}
+
+/// Generates the PDF of a chart, recording the download in the audit log
+fn chart_pdf(arrangement: &OptotypeArrangement, distance: Option<f64>, auditor: &Auditor, localisation: &Localisation, app_config: &AppConfig) -> Result<Content<Vec<u8>>, Status> {
+    let distance = distance.unwrap_or(app_config.default_distance);
+    if app_config.check_distance(distance).is_err() {
+        return Err(Status::BadRequest);
+    }
+    match metrics::time_pdf("chart", || pdf::chart::generate(arrangement, distance, localisation.locale, app_config.paper_size)) {
+        Ok(bytes) => {
+            auditor.record(&audit::describe_sheet("chart", arrangement));
+            Ok(Content(ContentType::PDF, bytes))
+        },
+        Err(_) => Err(Status::InternalServerError)
+    }
+}
+
+/// Generate a naming/practice sheet for a particular set of optotypes, identified by the name of
+/// the optotype definition (e.g. "/naming-sheet/aukland").
+#[get("/naming-sheet/<name>")]
+fn naming_sheet(name: String, _rate_limit: RateLimit, auditor: Auditor, localisation: Localisation, app_config: State<AppConfig>) -> Result<Content<Vec<u8>>, Status> {
+    let optotype_definition = match OptotypeDefinition::from_name(&name) {
+        Some(definition) => definition,
+        None => { return Err(Status::NotFound); }
+    };
+    match metrics::time_pdf("naming-sheet", || pdf::naming::generate(&optotype_definition, localisation.locale, app_config.paper_size)) {
+        Ok(bytes) => {
+            auditor.record(&format!("naming-sheet/{}", optotype_definition.name));
+            Ok(Content(ContentType::PDF, bytes))
+        },
+        Err(_) => Err(Status::InternalServerError)
+    }
+}
+
+/// Creates the server from the settings in Rocket.toml (and the ROCKET_* environment variables)
 fn rocket() -> rocket::Rocket {
-    return rocket::ignite()
-        .attach(Template::fairing())
-        .mount("/", routes![index, code_form, answer_display])
-        .mount("/static", StaticFiles::from("static/"));
-} 
+    build(rocket::ignite())
+}
+
+/// Attaches the fairings, routes and catchers to a server which has been created from its configuration
+fn build(rocket: rocket::Rocket) -> rocket::Rocket {
+    let rocket = rocket
+        .attach(Template::custom(|engines| {
+            engines.tera.register_function("t", Box::new(i18n::tera_translate));
+        }))
+        .attach(AdHoc::on_attach("Optotype font check", |rocket| {
+            // Refuse to start if any of the fonts are missing glyphs, rather than serving blank boxes
+            match fonts::check_all_fonts() {
+                Ok(()) => Ok(rocket),
+                Err(errors) => {
+                    for error in errors {
+                        log::error!("{}", error);
+                    }
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::on_attach("Application configuration", |rocket| {
+            // Refuse to start with inconsistent settings, rather than failing when a chart is downloaded
+            match AppConfig::from_config(rocket.config()) {
+                Ok(app_config) => Ok(rocket.manage(app_config)),
+                Err(e) => {
+                    log::error!("{}", e);
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(catchers::RequestIdFairing)
+        .attach(metrics::MetricsFairing)
+        .attach(rate_limit::RateLimitFairing)
+        .attach(AdHoc::on_attach("Audit log", |rocket| {
+            // Downloads are recorded as described in the privacy policy, and nothing older than the retention period is kept.
+            // Without the site configuration (which has already failed) there is nothing to record.
+            let audit_log = match rocket.state::<AppConfig>() {
+                Some(app_config) => AuditLog::from_config(app_config),
+                None => Ok(None)
+            };
+            match audit_log {
+                Ok(Some(log)) => match log.purge() {
+                    Ok(_) => Ok(rocket.manage(log)),
+                    Err(e) => {
+                        log::error!("{}", e);
+                        Err(rocket)
+                    }
+                },
+                Ok(None) => Ok(rocket),
+                Err(e) => {
+                    log::error!("{}", e);
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::on_attach("Result storage", |rocket| {
+            // Stored results are only given to clients with the results token, so they would be out of reach without one
+            match ResultRepository::from_config(rocket.config()) {
+                Ok(Some(_)) if rocket.state::<AppConfig>().and_then(|app_config| app_config.results_token.as_ref()).is_none() => {
+                    log::error!("Error: The results_token setting must be set when results are being stored.");
+                    Err(rocket)
+                },
+                Ok(Some(repository)) => Ok(rocket.manage(repository)),
+                Ok(None) => Ok(rocket),
+                Err(e) => {
+                    log::error!("{}", e);
+                    Err(rocket)
+                }
+            }
+        }))
+        .mount("/", routes![index, code_form, answer_display, score_display, mark_display, self_check, generate_chart, generate_random_chart, standard_chart, monitor_display, patient_list, patient_dashboard, login_form, login, logout, naming_sheet, metrics::export])
+        .mount("/static", StaticFiles::from("static/"))
+        .register(catchers::catchers());
+    // The API is mounted from the same list of routes as the OpenAPI document is generated from, so the document
+    // describes all of them
+    let api_routes = api::routes();
+    let document = openapi::generate(&api_routes);
+    api_routes.into_iter()
+        .fold(rocket, |rocket, api_route| rocket.mount(api_route.base, api_route.routes))
+        .manage(openapi::OpenApiDocument(document.to_string()))
+}
 
 fn main() {
+    // The "check-fonts" subcommand runs the same font checks as startup, without starting the server
+    if std::env::args().nth(1).as_deref() == Some("check-fonts") {
+        match fonts::check_all_fonts() {
+            Ok(()) => { println!("All optotype fonts contain every optotype."); },
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("{}", error);
+                }
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
     // Start the web server
     rocket().launch();
 }
@@ -0,0 +1,143 @@
+use crate::optotypes::{OptotypeArrangement, OptotypeDefinition};
+use crate::scoring::{self, Score, ScoringError, ScoringErrorKind};
+
+/// A rule for when the clinician should stop presenting rows of the chart. Rows below the one on
+/// which the test was terminated are marked as not presented, and count as incorrect when scoring.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct TerminationRule {
+    /// Stop after the first row with at least this many errors. If this is None, every row is marked.
+    pub max_errors_per_row: Option<u32>
+}
+
+/// A single optotype on a marked chart, along with what the patient said for it
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct MarkedOptotype {
+    /// The index of the optotype within the OptotypeDefinition
+    pub optotype: u8,
+    /// What the patient said, or None if nothing was recorded for this position
+    pub response: Option<String>,
+    /// Whether the response matched the optotype (or one of its synonyms)
+    pub correct: bool,
+    /// Whether this optotype was presented, which is false for rows after the test was terminated
+    pub presented: bool
+}
+
+/// A row of a marked chart, from the top of the chart down
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct MarkedRow {
+    pub optotypes: Vec<MarkedOptotype>,
+    pub num_correct: u32
+}
+
+/// The result of marking a patient's responses against a chart. This contains everything needed to
+/// display a marked-up answer sheet, as well as the score calculated from the marks.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct MarkedChart {
+    pub rows: Vec<MarkedRow>,
+    /// The row (from the top of the chart, starting at 0) after which the test was stopped by the termination rule
+    pub terminated_after_row: Option<usize>,
+    pub score: Score
+}
+
+/// Checks whether a response given by the patient identifies an optotype. Responses may be the optotype's
+/// name or one of its synonyms, and are compared ignoring case and surrounding spaces. For letter optotypes,
+/// the letter itself is also accepted. The characters of picture optotypes are only how the font draws them
+/// (e.g. "0" is the Aukland rabbit), so they are never a correct response.
+pub fn is_correct(optotype_definition: &OptotypeDefinition, optotype: u8, response: &str) -> bool {
+    let response = response.trim().to_lowercase();
+    let index = optotype as usize;
+    if response.is_empty() || index >= optotype_definition.optotypes.len() {
+        return false;
+    }
+    let glyph = optotype_definition.optotypes[index];
+    (glyph.is_alphabetic() && response == glyph.to_lowercase().to_string()) ||
+        response == optotype_definition.names[index].to_lowercase() ||
+        optotype_definition.synonyms[index].iter().any(|synonym| response == synonym.to_lowercase())
+}
+
+/// Marks the responses for each row (from the top of the chart down) against the chart, and scores the result.
+/// Rows and positions without a response are marked as incorrect, so responses only need to be given
+/// for the rows which were presented.
+pub fn mark(arrangement: &OptotypeArrangement, responses: &[Vec<String>], termination_rule: TerminationRule, viewing_distance: f64, chart_distance: f64) -> Result<MarkedChart, ScoringError> {
+    if responses.len() > arrangement.rows.len() {
+        return Err(ScoringError::new(ScoringErrorKind::TooManyRows { responses: responses.len(), rows: arrangement.rows.len() },
+            format!("Responses were given for {} rows, but the chart only has {} rows.", responses.len(), arrangement.rows.len())));
+    }
+    let mut rows: Vec<MarkedRow> = Vec::new();
+    let mut terminated_after_row: Option<usize> = None;
+    for (row_number, row) in arrangement.rows.iter().enumerate() {
+        let row_responses: &[String] = responses.get(row_number).map(|row| row.as_slice()).unwrap_or(&[]);
+        if row_responses.len() > row.optotypes.len() {
+            return Err(ScoringError::new(ScoringErrorKind::TooManyResponses { row: row_number + 1, optotypes: row.optotypes.len(), responses: row_responses.len() },
+                format!("Row {} only has {} optotypes, but {} responses were given.", row_number + 1, row.optotypes.len(), row_responses.len())));
+        }
+        let presented = terminated_after_row.is_none();
+        let optotypes: Vec<MarkedOptotype> = row.optotypes.iter().enumerate().map(|(position, &optotype)| {
+            let response = row_responses.get(position).filter(|response| !response.trim().is_empty()).cloned();
+            MarkedOptotype {
+                optotype,
+                correct: presented && response.as_ref().is_some_and(|response| is_correct(&arrangement.optotype_definition, optotype, response)),
+                response,
+                presented
+            }
+        }).collect();
+        let num_correct = optotypes.iter().filter(|optotype| optotype.correct).count() as u32;
+        let num_errors = optotypes.len() as u32 - num_correct;
+        if presented && termination_rule.max_errors_per_row.is_some_and(|max_errors| num_errors >= max_errors) {
+            terminated_after_row = Some(row_number);
+        }
+        rows.push(MarkedRow { optotypes, num_correct });
+    }
+    let correct_per_row: Vec<u32> = rows.iter().map(|row| row.num_correct).collect();
+    let score = scoring::score_arrangement_rows(arrangement, viewing_distance, chart_distance, &correct_per_row)?;
+    Ok(MarkedChart {
+        rows,
+        terminated_after_row,
+        score
+    })
+}
+
+impl MarkedChart {
+    /// Marks the rows after the first `rows_read` as not presented, for a patient who stopped reading the chart
+    /// part of the way down. Those rows already count as incorrect, so this only changes how they are shown.
+    pub fn stopped_after(mut self, rows_read: usize) -> MarkedChart {
+        if rows_read < self.rows.len() {
+            self.terminated_after_row = rows_read.checked_sub(1);
+            for row in self.rows.iter_mut().skip(rows_read) {
+                for optotype in row.optotypes.iter_mut() {
+                    optotype.presented = false;
+                }
+            }
+        }
+        self
+    }
+}
+
+/// Splits responses typed by a clinician into rows and positions. Each line is one row of the chart (from the
+/// top down), and the responses within a row are separated by spaces or commas. A "-" records a position
+/// where the patient gave no response, so that the responses after it stay in the right place.
+pub fn parse_responses(text: &str) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = text.lines()
+        .map(|line| line.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|response| !response.is_empty())
+            .map(|response| if response == "-" { String::new() } else { response.to_string() })
+            .collect())
+        .collect();
+    // Blank lines at the end of the text are not rows which were presented
+    while rows.last().is_some_and(|row| row.is_empty()) {
+        rows.pop();
+    }
+    rows
+}
+
+/// Joins responses back into the text read by `parse_responses`, with one line for each row and a "-" for
+/// each position without a response
+pub fn format_responses(responses: &[Vec<String>]) -> String {
+    responses.iter()
+        .map(|row| row.iter()
+            .map(|response| if response.trim().is_empty() { "-" } else { response.trim() })
+            .collect::<Vec<&str>>()
+            .join(" "))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,124 @@
+use std::sync::OnceLock;
+use std::time::Instant;
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+use rocket::{Data, Request, Response};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::ContentType;
+use rocket::response::content::Content;
+
+use crate::auth::MetricsAccess;
+use crate::codes::CodeError;
+
+/// Buckets (in seconds) for the PDF generation time histogram. PDFs take much longer than other requests,
+/// because the optotype font is embedded in each one.
+const PDF_BUCKETS: [f64; 10] = [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// The metrics exported to Prometheus. There is a single set of metrics for the whole process, because codes
+/// are parsed (and so parse failures are counted) outside of any request.
+pub struct Metrics {
+    registry: Registry,
+    pub requests: IntCounterVec,
+    pub request_duration: HistogramVec,
+    pub pdf_generation: HistogramVec,
+    pub code_parse_failures: IntCounterVec,
+    pub codes_issued: IntCounterVec
+}
+
+impl Metrics {
+    fn new() -> Metrics {
+        let requests = IntCounterVec::new(
+            Opts::new("hat_http_requests_total", "Number of HTTP requests, by route, method and status"),
+            &["route", "method", "status"]).unwrap();
+        let request_duration = HistogramVec::new(
+            HistogramOpts::new("hat_http_request_duration_seconds", "Time taken to respond to HTTP requests, by route and method"),
+            &["route", "method"]).unwrap();
+        let pdf_generation = HistogramVec::new(
+            HistogramOpts::new("hat_pdf_generation_seconds", "Time taken to generate PDFs, by type of sheet").buckets(PDF_BUCKETS.to_vec()),
+            &["sheet"]).unwrap();
+        let code_parse_failures = IntCounterVec::new(
+            Opts::new("hat_code_parse_failures_total", "Number of codes which could not be parsed, by kind of error"),
+            &["kind"]).unwrap();
+        let codes_issued = IntCounterVec::new(
+            Opts::new("hat_codes_issued_total", "Number of codes issued through the API, by mode of test"),
+            &["mode"]).unwrap();
+
+        let registry = Registry::new();
+        registry.register(Box::new(requests.clone())).unwrap();
+        registry.register(Box::new(request_duration.clone())).unwrap();
+        registry.register(Box::new(pdf_generation.clone())).unwrap();
+        registry.register(Box::new(code_parse_failures.clone())).unwrap();
+        registry.register(Box::new(codes_issued.clone())).unwrap();
+        Metrics {
+            registry,
+            requests,
+            request_duration,
+            pdf_generation,
+            code_parse_failures,
+            codes_issued
+        }
+    }
+
+    /// The metrics in the Prometheus text format
+    pub fn encode(&self) -> String {
+        let mut buffer: Vec<u8> = Vec::new();
+        // Encoding into a Vec can't fail, as the metrics are all valid
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// The metrics for this process
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+/// Counts a code which couldn't be parsed, by the kind of error (e.g. "CrcMismatch")
+pub fn record_code_error(error: &CodeError) {
+    metrics().code_parse_failures.with_label_values(&[error.kind.as_str()]).inc();
+}
+
+/// Generates a PDF, recording how long it took
+pub fn time_pdf<T, F: FnOnce() -> T>(sheet: &str, generate: F) -> T {
+    let _timer = metrics().pdf_generation.with_label_values(&[sheet]).start_timer();
+    generate()
+}
+
+/// Stored in the request-local cache when the request arrives, so that the time taken to respond can be measured
+struct RequestStart(Option<Instant>);
+
+/// Counts every request and measures how long it took to respond to, labelled by the route which handled it.
+/// Routes are identified by their URI templates (e.g. "/api/v1/codes/<code>") so the number of labels is limited.
+pub struct MetricsFairing;
+
+impl Fairing for MetricsFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Prometheus metrics",
+            kind: Kind::Request | Kind::Response
+        }
+    }
+
+    fn on_request(&self, request: &mut Request, _: &Data) {
+        request.local_cache(|| RequestStart(Some(Instant::now())));
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        let route = match request.route() {
+            Some(route) => route.uri.path().to_string(),
+            None => "unmatched".to_string()
+        };
+        let method = request.method().as_str();
+        metrics().requests.with_label_values(&[&route, method, &response.status().code.to_string()]).inc();
+        if let RequestStart(Some(start)) = request.local_cache(|| RequestStart(None)) {
+            metrics().request_duration.with_label_values(&[&route, method]).observe(start.elapsed().as_secs_f64());
+        }
+    }
+}
+
+/// The metrics in the Prometheus text format, for scraping by clients with the metrics token. This is not part of
+/// the API.
+#[get("/metrics")]
+pub fn export(_access: MetricsAccess) -> Content<String> {
+    Content(ContentType::with_params("text", "plain", ("version", "0.0.4")), metrics().encode())
+}
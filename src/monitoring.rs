@@ -0,0 +1,192 @@
+use std::fmt;
+use std::error::Error;
+use std::str::FromStr;
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+
+use crate::acuity::{Acuity, round_to_hundredths};
+
+/// The default test-retest limit (in logMAR). Differences between two sessions which are no larger than
+/// this could be explained by the variability of the test, so are not reported as a change in vision.
+pub const DEFAULT_TEST_RETEST_LIMIT: f64 = 0.15;
+
+/// The eye (or eyes) which were tested in a session
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub enum Eye {
+    Left,
+    Right,
+    Both
+}
+
+impl FromStr for Eye {
+    type Err = MonitoringError;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_lowercase().as_str() {
+            "left" | "l" | "os" => Ok(Eye::Left),
+            "right" | "r" | "od" => Ok(Eye::Right),
+            "both" | "b" | "ou" => Ok(Eye::Both),
+            _ => Err(MonitoringError::new(MonitoringErrorKind::UnknownEye, format!("\"{}\" is not a recognised eye.", text)))
+        }
+    }
+}
+
+/// Eyes are displayed in lower case (e.g. "left"), which can be read back by from_str
+impl fmt::Display for Eye {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Eye::Left => write!(f, "left"),
+            Eye::Right => write!(f, "right"),
+            Eye::Both => write!(f, "both")
+        }
+    }
+}
+
+/// The result of a single scored test
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub date: NaiveDate,
+    pub eye: Eye,
+    pub acuity: Acuity
+}
+
+/// How the acuity changed between two sessions
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum ChangeClassification {
+    /// The acuity got better by more than the test-retest limit
+    Improved,
+    /// The change was within the test-retest limit
+    Stable,
+    /// The acuity got worse by more than the test-retest limit
+    Worsened
+}
+
+/// The change in acuity between two sessions, which are identified by their position in the
+/// (date ordered) list of sessions in the report.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    pub from_session: usize,
+    pub to_session: usize,
+    /// The change in logMAR from the earlier session to the later one. Positive changes are worse vision.
+    pub difference: f64,
+    pub classification: ChangeClassification
+}
+
+/// A comparison of two or more sessions for the same eye
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChangeReport {
+    /// The sessions which were compared, in date order
+    pub sessions: Vec<Session>,
+    pub test_retest_limit: f64,
+    /// The change between each session and the one before it
+    pub changes: Vec<Change>,
+    /// The change between the first and last sessions
+    pub overall: Change
+}
+
+/// Classifies a change in logMAR (where positive changes are worse vision) against the test-retest limit.
+/// A change only counts as an improvement or worsening if it is larger than the limit.
+pub fn classify(difference: f64, test_retest_limit: f64) -> ChangeClassification {
+    // The difference is rounded first, so that a change of exactly the limit is never reported due to rounding
+    let difference = round_to_hundredths(difference);
+    if difference > test_retest_limit {
+        ChangeClassification::Worsened
+    } else if difference < -test_retest_limit {
+        ChangeClassification::Improved
+    } else {
+        ChangeClassification::Stable
+    }
+}
+
+/// Compares sessions for the same eye, reporting whether the acuity changed by more than the test-retest
+/// limit between each pair of consecutive sessions, and between the first and last sessions. The sessions
+/// do not need to be given in date order.
+pub fn compare(sessions: &[Session], test_retest_limit: f64) -> Result<ChangeReport, MonitoringError> {
+    if sessions.len() < 2 {
+        return Err(MonitoringError::new(MonitoringErrorKind::TooFewSessions, "At least two sessions are needed to look for a change in vision."));
+    }
+    if test_retest_limit.is_nan() || test_retest_limit < 0.0 {
+        return Err(MonitoringError::new(MonitoringErrorKind::InvalidLimit, "The test-retest limit must be zero or more."));
+    }
+    if sessions.iter().any(|session| session.eye != sessions[0].eye) {
+        return Err(MonitoringError::new(MonitoringErrorKind::MixedEyes, "All of the sessions must be for the same eye."));
+    }
+    let mut sessions = sessions.to_vec();
+    sessions.sort_by_key(|session| session.date);
+
+    let change_between = |from_session: usize, to_session: usize| {
+        let difference = round_to_hundredths(sessions[to_session].acuity.log_mar() - sessions[from_session].acuity.log_mar());
+        Change {
+            from_session,
+            to_session,
+            difference,
+            classification: classify(difference, test_retest_limit)
+        }
+    };
+    let changes: Vec<Change> = (1..sessions.len()).map(|session| change_between(session - 1, session)).collect();
+    let overall = change_between(0, sessions.len() - 1);
+    Ok(ChangeReport {
+        sessions,
+        test_retest_limit,
+        changes,
+        overall
+    })
+}
+
+/// Reads sessions typed by a clinician, with one session on each line written as a date (YYYY-MM-DD)
+/// followed by the acuity in any unit which Acuity can parse (e.g. "2020-06-01 6/12"). Blank lines are ignored.
+pub fn parse_sessions(text: &str, eye: Eye) -> Result<Vec<Session>, MonitoringError> {
+    let mut sessions: Vec<Session> = Vec::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (date, acuity) = match line.find(char::is_whitespace) {
+            Some(index) => (&line[..index], &line[index..]),
+            None => { return Err(MonitoringError::new(MonitoringErrorKind::IncompleteLine { line: line_number + 1 }, format!("Line {} needs both a date and an acuity.", line_number + 1))); }
+        };
+        let date = match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => { return Err(MonitoringError::new(MonitoringErrorKind::InvalidDate { line: line_number + 1 }, format!("Line {} does not start with a date in the format YYYY-MM-DD.", line_number + 1))); }
+        };
+        let acuity = match Acuity::from_str(acuity) {
+            Ok(acuity) => acuity,
+            Err(e) => { return Err(MonitoringError::new(MonitoringErrorKind::InvalidAcuity { line: line_number + 1 }, format!("Line {}: {}", line_number + 1, e.0))); }
+        };
+        sessions.push(Session { date, eye, acuity });
+    }
+    Ok(sessions)
+}
+
+/// This struct is used to represent errors when comparing sessions, such as sessions for different eyes. The kind
+/// of error gives the message shown on the pages (see i18n::describe_monitoring_error).
+#[derive(Debug)]
+pub struct MonitoringError {
+    pub kind: MonitoringErrorKind,
+    pub message: String
+}
+impl MonitoringError {
+    pub fn new<S: Into<String>>(kind: MonitoringErrorKind, message: S) -> MonitoringError {
+        MonitoringError { kind, message: message.into() }
+    }
+}
+impl fmt::Display for MonitoringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error: {}", self.message)
+    }
+}
+
+/// The different reasons that sessions can't be compared, with the line (counting from 1) for errors in typed sessions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MonitoringErrorKind {
+    UnknownEye,
+    TooFewSessions,
+    /// The test-retest limit was negative (or not a number)
+    InvalidLimit,
+    MixedEyes,
+    /// A line didn't have both a date and an acuity
+    IncompleteLine { line: usize },
+    InvalidDate { line: usize },
+    InvalidAcuity { line: usize }
+}
+impl Error for MonitoringError {}
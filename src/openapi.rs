@@ -0,0 +1,139 @@
+use rocket::Route;
+use schemars::JsonSchema;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::Schema;
+use serde_json::{json, Map, Value};
+
+/// Routes under this path are part of the API, and are included in the OpenAPI document
+pub const API_PATH: &str = "/api/";
+
+/// The generated OpenAPI document, which is created once at startup and kept in Rocket's managed state
+pub struct OpenApiDocument(pub String);
+
+/// A parameter taken by an API route, either in the path or in the query string
+pub struct ParameterDescription {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub required: bool,
+    pub schema: Schema
+}
+
+/// A possible response from an API route. Responses without a schema have an empty body.
+pub struct ResponseDescription {
+    pub status: u16,
+    pub description: &'static str,
+    pub schema: Option<Schema>
+}
+
+/// The parts of an operation which can't be read from the Rocket route itself
+pub struct OperationDescription {
+    pub summary: &'static str,
+    pub parameters: Vec<ParameterDescription>,
+    pub request_body: Option<Schema>,
+    pub responses: Vec<ResponseDescription>
+}
+
+/// An API route, along with the description of its operation in the OpenAPI document. The API is mounted from a
+/// list of these (see `api::routes`), so every route has a description, which is kept next to its handler.
+pub struct ApiRoute {
+    /// Where the route is mounted (e.g. "/api/v1")
+    pub base: &'static str,
+    pub routes: Vec<Route>,
+    pub describe: fn(&mut SchemaGenerator) -> OperationDescription
+}
+
+impl ApiRoute {
+    /// The full path of each of the routes, once they are mounted
+    pub fn paths(&self) -> Vec<String> {
+        self.routes.iter().map(|route| format!("{}{}", self.base, route.uri.path())).collect()
+    }
+}
+
+pub fn parameter<T: JsonSchema>(gen: &mut SchemaGenerator, name: &'static str, required: bool, description: &'static str) -> ParameterDescription {
+    ParameterDescription { name, description, required, schema: gen.subschema_for::<T>() }
+}
+
+pub fn response<T: JsonSchema>(gen: &mut SchemaGenerator, status: u16, description: &'static str) -> ResponseDescription {
+    ResponseDescription { status, description, schema: Some(gen.subschema_for::<T>()) }
+}
+
+pub fn empty_response(status: u16, description: &'static str) -> ResponseDescription {
+    ResponseDescription { status, description, schema: None }
+}
+
+/// Lists the names of the dynamic parameters (e.g. "<code>") in part of a route URI
+pub fn get_parameter_names(uri_part: &str) -> Vec<String> {
+    uri_part.split(['/', '&'])
+        .filter(|segment| segment.starts_with('<') && segment.ends_with('>'))
+        .map(|segment| segment.trim_start_matches('<').trim_end_matches('>').trim_end_matches("..").to_string())
+        .collect()
+}
+
+/// Converts the path of a Rocket route (e.g. "/api/v1/codes/<code>") into an OpenAPI path ("/api/v1/codes/{code}")
+fn to_openapi_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| if segment.starts_with('<') && segment.ends_with('>') {
+            format!("{{{}}}", segment.trim_start_matches('<').trim_end_matches('>').trim_end_matches(".."))
+        } else {
+            segment.to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+fn to_json(schema: &Schema) -> Value {
+    serde_json::to_value(schema).unwrap_or(Value::Null)
+}
+
+/// Generates the OpenAPI 3 document describing the API routes. The methods, paths and parameter names are read
+/// from the routes, and the schemas are generated from the Rust types.
+pub fn generate(api_routes: &[ApiRoute]) -> Value {
+    let mut gen = SchemaSettings::openapi3().into_generator();
+    let mut paths = Map::new();
+    for api_route in api_routes {
+        let description = (api_route.describe)(&mut gen);
+        for (route, full_path) in api_route.routes.iter().zip(api_route.paths()) {
+            let mut parameters: Vec<Value> = Vec::new();
+            let query = route.uri.query().unwrap_or("");
+            let locations = get_parameter_names(route.uri.path()).into_iter().map(|name| (name, "path"))
+                .chain(get_parameter_names(query).into_iter().map(|name| (name, "query")));
+            for (parameter_name, location) in locations {
+                let mut parameter = json!({ "name": parameter_name, "in": location, "required": location == "path" });
+                if let Some(parameter_description) = description.parameters.iter().find(|parameter| parameter.name == parameter_name) {
+                    parameter["description"] = json!(parameter_description.description);
+                    parameter["required"] = json!(location == "path" || parameter_description.required);
+                    parameter["schema"] = to_json(&parameter_description.schema);
+                }
+                parameters.push(parameter);
+            }
+
+            let mut operation = json!({ "operationId": route.name.unwrap_or(""), "summary": description.summary, "parameters": parameters });
+            if let Some(request_body) = &description.request_body {
+                operation["requestBody"] = json!({ "required": true, "content": { "application/json": { "schema": to_json(request_body) } } });
+            }
+            let mut responses = Map::new();
+            for response in description.responses.iter() {
+                let mut body = json!({ "description": response.description });
+                if let Some(schema) = &response.schema {
+                    body["content"] = json!({ "application/json": { "schema": to_json(schema) } });
+                }
+                responses.insert(response.status.to_string(), body);
+            }
+            operation["responses"] = Value::Object(responses);
+
+            let path = paths.entry(to_openapi_path(&full_path)).or_insert_with(|| json!({}));
+            path[route.method.as_str().to_lowercase()] = operation;
+        }
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Home Acuity Test API",
+            "description": env!("CARGO_PKG_DESCRIPTION"),
+            "version": env!("CARGO_PKG_VERSION")
+        },
+        "paths": paths,
+        "components": { "schemas": gen.definitions() }
+    })
+}
@@ -1,4 +1,7 @@
-use crate::codes::{NUM_ROWS, NUM_OPTOTYPES_ON_ROW, short::ShortCode, long::LongCode};
+use crate::codes::{NUM_ROWS, NUM_OPTOTYPES_ON_ROW, CodeError, CodeKind, short::ShortCode, long::LongCode};
+use std::str::FromStr;
+use crate::constraints::{self, GenerationConstraints};
+use schemars::JsonSchema;
 
 /// Stores the name, numeric ID, and possible optotypes for a particular
 /// group of optotypes.
@@ -8,11 +11,19 @@ use crate::codes::{NUM_ROWS, NUM_OPTOTYPES_ON_ROW, short::ShortCode, long::LongC
 /// necessarily ideal, but the addition of new optotypes is expected to be extremely
 /// rare. Future versions may shift optotype definitions into a configuration file and
 /// parse that instead.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct OptotypeDefinition {
     pub name: String,
     pub id: u8,
     pub optotypes: Vec<char>,
+    /// The human-readable name of each optotype, in the same order as `optotypes`. These
+    /// are printed beneath each symbol on the naming/practice sheet.
+    pub names: Vec<String>,
+    /// Other words which patients commonly use for each optotype, in the same order as `optotypes`.
+    /// Responses matching these are marked as correct, in addition to the optotype and its name.
+    pub synonyms: Vec<Vec<String>>,
+    /// Constraints which randomly generated charts using these optotypes must satisfy
+    pub constraints: GenerationConstraints,
 }
 
 /// Obtains optotype definition from the numeric ID of that parameter
@@ -24,27 +35,67 @@ pub struct OptotypeDefinition {
 /// This is where new optotype definitions can be added. Please note that
 /// these optotype definitions must also be matched by a new CSS class in 
 /// `src/templates/answers.html.tera` defining a font-family with the same
-/// name as the optotype definition has.
+/// name as the optotype definition has, and must give a name for each optotype so that
+/// the naming sheet can be generated (along with any synonyms accepted when marking responses).
 /// 
 /// Due to limitations on the amount of information which is possible to encode in the codes
 /// which identify a test, the OptotypeDefinition must contain 10 or fewer optotype characters.
 impl From<u32> for OptotypeDefinition {
     fn from(id: u32) -> Self {
         match id {
-            0 => OptotypeDefinition { name: "aukland".to_string(), id: 0, optotypes:  vec!['0', '1', '2', '3', '4', '5', '6', '7', '8', '9']},
-            1 => OptotypeDefinition { name: "sloan".to_string(), id: 1, optotypes:  vec!['C', 'D', 'H', 'K', 'N', 'O', 'R', 'S', 'V', 'Z']},
+            0 => OptotypeDefinition { name: "aukland".to_string(), id: 0, optotypes:  vec!['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'],
+                names: to_names(&["Rabbit", "Flower", "Car", "Butterfly", "Bell", "Duck", "Heart", "House", "Moon", "Tree"]),
+                synonyms: vec![to_names(&["Bunny", "Hare"]), to_names(&["Daisy"]), to_names(&["Automobile"]), Vec::new(), Vec::new(),
+                    to_names(&["Bird", "Duckling"]), Vec::new(), to_names(&["Home"]), Vec::new(), Vec::new()],
+                constraints: GenerationConstraints::unconstrained(10)},
+            // The Sloan letters are not equally legible: C, D, O and S are the hardest to identify
+            // near threshold, and Z and V the easiest. C, D and O are also commonly confused with one another.
+            // The order follows the relative letter difficulties reported by Ferris et al. (1993), "Relative letter
+            // and position difficulty on visual acuity charts from the Early Treatment Diabetic Retinopathy Study",
+            // Am J Ophthalmol 116(6):735-740. The weights themselves are a relative scale for balancing rows, rather
+            // than values taken from the paper.
+            1 => OptotypeDefinition { name: "sloan".to_string(), id: 1, optotypes:  vec!['C', 'D', 'H', 'K', 'N', 'O', 'R', 'S', 'V', 'Z'],
+                names: to_names(&["C", "D", "H", "K", "N", "O", "R", "S", "V", "Z"]),
+                synonyms: vec![Vec::new(); 10],
+                constraints: GenerationConstraints {
+                    forbidden_adjacent_pairs: vec![('C', 'O'), ('C', 'D'), ('D', 'O')],
+                    difficulty: vec![1.3, 1.2, 0.9, 0.9, 0.9, 1.2, 1.0, 1.1, 0.8, 0.7],
+                    max_row_difficulty_deviation: 0.15,
+                    allow_identical_rows: false,
+                }},
             _ => OptotypeDefinition::from(DEFAULT_OPTOTYPES)
         }
     }
 }
 
+impl OptotypeDefinition {
+    /// Returns every optotype definition known to the server, in order of ID.
+    pub fn all() -> Vec<OptotypeDefinition> {
+        (0..NUM_OPTOTYPE_DEFINITIONS).map(OptotypeDefinition::from).collect()
+    }
+    /// Looks up an optotype definition by its name (e.g. "sloan"). Unlike the From<u32>
+    /// implementation, this does not fall back to the default optotypes, because names come
+    /// from user-facing URLs where an unknown name should be reported rather than hidden.
+    pub fn from_name(name: &str) -> Option<OptotypeDefinition> {
+        OptotypeDefinition::all().into_iter().find(|definition| definition.name == name)
+    }
+}
+
+/// Helper to avoid repeating `.to_string()` for every optotype name in the definitions above.
+fn to_names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
 /// Defines the ID of the default optotypes (used if a code specifies an optotype ID which
 /// is not specified in the implementation above).
 pub const DEFAULT_OPTOTYPES: u32 = 1;
 
+/// The number of optotype definitions implemented above. IDs run from 0 to one less than this.
+pub const NUM_OPTOTYPE_DEFINITIONS: u32 = 2;
+
 /// An OptotypeRow defines a given row of the chart - both the optotype char's contained
 /// within that row, and the font/border size.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct OptotypeRow {
     pub text_size: f64,
     pub border_size: f64,
@@ -55,11 +106,43 @@ pub struct OptotypeRow {
 /// row, a string representation of the code used to generate that chart, and the
 /// definition of the optotypes used for that chart. This is enough information to
 /// uniquely regenerate a chart.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct OptotypeArrangement {
     pub rows: Vec<OptotypeRow>,
     pub code: String,
-    pub optotype_definition: OptotypeDefinition
+    pub optotype_definition: OptotypeDefinition,
+    /// Problems with the chart which were found when decoding it. These don't stop the chart from
+    /// being used, but are shown to clinicians alongside the answers.
+    pub warnings: Vec<ArrangementWarning>
+}
+
+/// Warnings which can be attached to an OptotypeArrangement when it is decoded
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum ArrangementWarning {
+    /// Two or more rows of the chart start with the same optotypes in the same order. Codes issued
+    /// before the short code generator was fixed could have a row offset of zero (or repeated offsets),
+    /// which copies a row verbatim and makes the chart easier to memorise.
+    DuplicateRows,
+    /// At least one row contains the same optotype more than once. Random charts never do this, so the
+    /// code has probably been mistyped or tampered with. Parsing rejects these codes, so this can only happen
+    /// for codes decoded in lenient mode (or a LongCode which was built directly).
+    RepeatedOptotypes,
+    /// The chart breaks the generation constraints for its optotype set (for example, by placing confusable
+    /// optotypes next to each other). Long codes issued before the constraints were introduced can do this.
+    BreaksConstraints
+}
+
+impl OptotypeArrangement {
+    /// Decodes a code entered by a user into the arrangement of optotypes it represents, using the
+    /// length of the code (ignoring dashes) to work out whether it is a short or a long code. Parsing has
+    /// no side effects: the routes which take codes from users record the errors in the metrics.
+    pub fn from_code(code: &str) -> Result<OptotypeArrangement, CodeError> {
+        match CodeKind::from_code(code) {
+            Ok(CodeKind::Short) => ShortCode::from_str(code).map(OptotypeArrangement::from),
+            Ok(CodeKind::Long) => LongCode::from_str(code).map(OptotypeArrangement::from),
+            Err(e) => Err(e)
+        }
+    }
 }
 
 /// Convert a ShortCode into an optotype arrangement for display
@@ -85,7 +168,7 @@ impl From<ShortCode> for OptotypeArrangement {
                 // the entire range of possible combinations, we multiply it by a scale such that the max start_row is now
                 // approximately corresponding to the iteration of the loop which the last valid optotype arrangement calculated by this function occurs.
                 let radix = optotype_definition.optotypes.len() as u32;
-                let scale = ((f64::from(factorial(radix))/f64::from(factorial(radix-NUM_OPTOTYPES_ON_ROW[row]))) / f64::from(u8::MAX)).round() as u32;
+                let scale = get_start_row_scale(radix);
                 // Once scale is calculated, iteration through the permissible combinations can begin
                 let mut valid_combination_iteration: u32 = 0;
                 for i in 0..(radix.pow(NUM_OPTOTYPES_ON_ROW[row]) - 1) {
@@ -93,7 +176,7 @@ impl From<ShortCode> for OptotypeArrangement {
                     let mut optotypes_on_potential_row: Vec<u8> = Vec::new();
                     // Now extract a list containing the position of each optotype character in the character array which defines that set of optotypes
                     // This is essentially converting into an arbirary base (base = the number of optotype characters in that set of optotypes)
-                    let mut temp_for_conversion = i as u32;
+                    let mut temp_for_conversion = i;
                     for _ in 0..NUM_OPTOTYPES_ON_ROW[row] {
                         if temp_for_conversion == 0 {
                             optotypes_on_potential_row.push(0_u8);
@@ -134,21 +217,61 @@ impl From<ShortCode> for OptotypeArrangement {
             rows.push(OptotypeRow {
                 text_size: row_text_size,
                 border_size: row_text_size/5.0,
-                optotypes: optotypes
+                optotypes
             });
         };
         // Rows is calculated in reverse order (bottom first, top last) but for PDF generation, it makes sense to reverse them
         // and store them from the top to bottom of the page.
         rows.reverse();
+        // Legacy codes may have produced rows which are copies of one another, so flag these
+        let mut warnings: Vec<ArrangementWarning> = Vec::new();
+        if has_duplicate_rows(&rows) {
+            warnings.push(ArrangementWarning::DuplicateRows);
+        }
         // Return the generated arrangement
         OptotypeArrangement {
-            optotype_definition: optotype_definition,
-            code: code,
-            rows: rows
+            optotype_definition,
+            code,
+            rows,
+            warnings
         }
     }
 }
 
+/// Checks whether any two rows start with the same optotypes in the same order. Rows of different lengths
+/// are compared over the length of the shorter row. For short codes, every row is derived from the
+/// bottom row by adding an offset, so this happens exactly when two rows share an offset (or a row
+/// has an offset of zero).
+fn has_duplicate_rows(rows: &[OptotypeRow]) -> bool {
+    for (i, first_row) in rows.iter().enumerate() {
+        for second_row in rows[i + 1..].iter() {
+            if first_row.optotypes.iter().zip(second_row.optotypes.iter()).all(|(a, b)| a == b) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Calculates the number of different bottom rows (with no repeated optotypes) that can be made from a set of `radix` optotypes
+fn get_num_reference_rows(radix: u32) -> u32 {
+    factorial(radix) / factorial(radix - NUM_OPTOTYPES_ON_ROW[NUM_ROWS - 1])
+}
+
+/// Calculates the scale factor by which the start_row of a short code is multiplied to select the bottom row of the chart
+/// (see the conversion from a ShortCode to an OptotypeArrangement above).
+fn get_start_row_scale(radix: u32) -> u32 {
+    (f64::from(get_num_reference_rows(radix)) / f64::from(u8::MAX)).round() as u32
+}
+
+/// Returns the largest start_row value which selects a valid bottom row for a set of optotypes. Because the scale factor
+/// is rounded, the largest few values of the u8 start_row can fall beyond the last valid bottom row, and must not be used.
+pub fn get_max_start_row(optotype_definition: &OptotypeDefinition) -> u8 {
+    let radix = optotype_definition.optotypes.len() as u32;
+    let max_start_row = (get_num_reference_rows(radix) - 1) / get_start_row_scale(radix);
+    max_start_row.min(u32::from(u8::MAX)) as u8
+}
+
 /// This is just a helper function for use in calculating the number of potential optotype combinations that exist on a given row.
 /// It's a simple implementation of the factorial function
 fn factorial(n: u32) -> u32 {
@@ -163,30 +286,34 @@ fn factorial(n: u32) -> u32 {
 impl From<LongCode> for OptotypeArrangement {
     fn from(code: LongCode) -> Self {
         let mut optotype_rows: Vec<OptotypeRow> = Vec::new();
-        let mut optotype_index = 0;
-        // The order of the list of optotypes needs to be reversed for this chart, because the rows are generated top-down,
-        // but the list of optotypes is stored from the bottom-up.
-        let mut optotype_list = code.optotypes.clone();
-        optotype_list.reverse();
-        for row in 0..NUM_ROWS {
-            let mut optotypes: Vec<u8> = Vec::new();
-            for _ in 0..NUM_OPTOTYPES_ON_ROW[row] {
-                optotypes.push(optotype_list[optotype_index]);
-                optotype_index += 1;
+        let mut warnings: Vec<ArrangementWarning> = Vec::new();
+        let rows = code.get_rows();
+        // The constraints can only be checked for optotypes which are in the set, which parsing makes sure of
+        let num_possible_optotypes = code.optotype_definition.optotypes.len();
+        if rows.iter().flatten().all(|&optotype| (optotype as usize) < num_possible_optotypes) {
+            let row_slices: Vec<&[u8]> = rows.iter().map(|row| row.as_slice()).collect();
+            if !constraints::check_rows(&code.optotype_definition, &row_slices).is_empty() {
+                warnings.push(ArrangementWarning::BreaksConstraints);
+            }
+        }
+        for (row, optotypes) in rows.into_iter().enumerate() {
+            // Codes parsed in lenient mode may repeat an optotype on a row, which should be flagged
+            if optotypes.iter().enumerate().any(|(i, optotype)| optotypes[0..i].contains(optotype)) && !warnings.contains(&ArrangementWarning::RepeatedOptotypes) {
+                warnings.push(ArrangementWarning::RepeatedOptotypes);
             }
-            optotypes.reverse();
             let row_text_size = (NUM_ROWS - row) as f64;
             optotype_rows.push(OptotypeRow {
                 border_size: row_text_size/5.0,
                 text_size: row_text_size,
-                optotypes: optotypes
+                optotypes
             });
         }
         // Return the arrangement that was just generated
         OptotypeArrangement {
             code: code.to_string(),
             optotype_definition: code.optotype_definition,
-            rows: optotype_rows
+            rows: optotype_rows,
+            warnings
         }
     }
 }
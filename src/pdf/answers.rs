@@ -0,0 +1,65 @@
+use printpdf::{PdfDocument, Mm};
+
+use crate::optotypes::{OptotypeArrangement, ArrangementWarning};
+use crate::i18n::{self, Locale};
+use crate::pdf::{PaperSize, POINTS_PER_MM, PdfError, add_optotype_font, add_text_font, save_to_bytes};
+use crate::scoring::get_row_acuity;
+
+/// Height (in mm) at which the optotypes are printed on the answer key. The key is read by the
+/// clinician rather than the patient, so every row is printed at the same (comfortable) size.
+const OPTOTYPE_SIZE: f64 = 12.0;
+/// Horizontal space (in mm) given to each optotype on a row
+const OPTOTYPE_SPACING: f64 = 30.0;
+/// Vertical space (in mm) given to each row of the chart
+const ROW_HEIGHT: f64 = 38.0;
+/// Margin (in mm) left at the left of the page
+const SIDE_MARGIN: f64 = 20.0;
+/// Font size (in points) of the text on the answer key
+const TEXT_FONT_SIZE: f64 = 10.0;
+
+/// Generates the answer key for a chart, which lists the optotypes on each row (from the top of the chart
+/// down) along with their names and the acuity which each row represents for a chart printed for and read from
+/// the given distance (in cm), which is the server's default distance. The names of the optotypes aren't
+/// translated, because they are the answers which the patient is expected to give.
+pub fn generate(arrangement: &OptotypeArrangement, distance: f64, locale: Locale, paper: PaperSize) -> Result<Vec<u8>, PdfError> {
+    let title = i18n::translate(locale, "pdf-answers-title", &[("code", arrangement.code.clone())]);
+    let (doc, page, layer) = PdfDocument::new(title.clone(), Mm(paper.width()), Mm(paper.height()), "Answer key");
+    let layer = doc.get_page(page).get_layer(layer);
+    let optotype_font = add_optotype_font(&doc, &arrangement.optotype_definition)?;
+    let text_font = add_text_font(&doc, locale)?;
+
+    layer.use_text(title, 20.0, Mm(SIDE_MARGIN), Mm(paper.height() - 25.0), &text_font);
+    layer.use_text(i18n::translate(locale, "pdf-answers-instructions", &[]), TEXT_FONT_SIZE, Mm(SIDE_MARGIN), Mm(paper.height() - 33.0), &text_font);
+    let mut warning_y = paper.height() - 39.0;
+    for warning in &arrangement.warnings {
+        let message_id = match warning {
+            ArrangementWarning::DuplicateRows => "pdf-answers-duplicate-rows",
+            ArrangementWarning::RepeatedOptotypes => "pdf-answers-repeated-optotypes",
+            ArrangementWarning::BreaksConstraints => "pdf-answers-breaks-constraints"
+        };
+        layer.use_text(i18n::translate(locale, message_id, &[]), TEXT_FONT_SIZE, Mm(SIDE_MARGIN), Mm(warning_y), &text_font);
+        warning_y -= 6.0;
+    }
+
+    let mut row_top = paper.height() - 55.0;
+    for (row_number, row) in arrangement.rows.iter().enumerate() {
+        let acuity = get_row_acuity(row_number, distance, distance);
+        let row_description = i18n::translate(locale, "pdf-answers-row", &[
+            ("row", (row_number + 1).to_string()),
+            ("acuity", format!("{} ({})", acuity, acuity.snellen_metric())),
+            ("distance", distance.to_string())
+        ]);
+        layer.use_text(row_description,
+            TEXT_FONT_SIZE, Mm(SIDE_MARGIN), Mm(row_top), &text_font);
+        for (position, &optotype) in row.optotypes.iter().enumerate() {
+            let x = SIDE_MARGIN + OPTOTYPE_SPACING * position as f64;
+            let character = arrangement.optotype_definition.optotypes[optotype as usize];
+            layer.use_text(character.to_string(), OPTOTYPE_SIZE * POINTS_PER_MM, Mm(x), Mm(row_top - 6.0 - OPTOTYPE_SIZE), &optotype_font);
+            let name = arrangement.optotype_definition.names[optotype as usize].clone();
+            layer.use_text(name, TEXT_FONT_SIZE, Mm(x), Mm(row_top - 12.0 - OPTOTYPE_SIZE), &text_font);
+        }
+        row_top -= ROW_HEIGHT;
+    }
+
+    save_to_bytes(doc)
+}
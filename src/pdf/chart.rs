@@ -0,0 +1,117 @@
+use printpdf::{PdfDocument, Mm, Line, Point};
+
+use crate::codes::{NUM_ROWS, NUM_OPTOTYPES_ON_ROW};
+use crate::optotypes::OptotypeArrangement;
+use crate::i18n::{self, Locale};
+use crate::pdf::{PaperSize, POINTS_PER_MM, PdfError, add_optotype_font, add_text_font, save_to_bytes};
+use crate::acuity::Acuity;
+use crate::scoring::{self, STANDARD_CHART_DISTANCE, get_optotype_height, get_row_acuity};
+
+/// Margin (in mm) left at the left and right of the page
+const SIDE_MARGIN: f64 = 15.0;
+/// Vertical space (in mm) on the page which is taken up by the title and instructions
+const HEADER_HEIGHT: f64 = 45.0;
+/// Vertical space (in mm) at the bottom of the page, which holds the calibration line
+const FOOTER_HEIGHT: f64 = 25.0;
+/// Length (in mm) of the line printed at the bottom of the chart, so that the print scale can be checked
+const CALIBRATION_LINE_LENGTH: f64 = 100.0;
+/// Space left between optotypes, and between rows, as a proportion of the optotype height. The rows of
+/// a logMAR chart are usually spaced by a whole optotype, but that would limit the chart to short distances on A4.
+const OPTOTYPE_SPACING: f64 = 0.5;
+/// The smallest optotype height (in mm) which can be printed reliably on a home printer
+const MIN_OPTOTYPE_HEIGHT: f64 = 1.5;
+
+/// Works out the viewing distance (in cm, rounded to a whole number) that a chart will be printed for, when the
+/// clinician asks for a chart for `target_distance`. The rows are scaled in proportion to the target distance,
+/// unless that would make the largest row too big for the page or the smallest row too small to print, in which
+/// case the nearest distance which can be printed is used instead. Larger paper fits charts for longer distances.
+pub fn get_chart_distance(target_distance: f64, paper: PaperSize) -> f64 {
+    // Optotypes are (approximately) square, so the width of a row only depends on the height of its optotypes
+    let mut max_scale = (paper.height() - HEADER_HEIGHT - FOOTER_HEIGHT) / ((1.0 + OPTOTYPE_SPACING) * get_chart_height(STANDARD_CHART_DISTANCE));
+    for (row, &num_optotypes) in NUM_OPTOTYPES_ON_ROW.iter().enumerate() {
+        let row_width = get_row_width(num_optotypes as usize, get_optotype_height(row, STANDARD_CHART_DISTANCE));
+        max_scale = max_scale.min((paper.width() - 2.0 * SIDE_MARGIN) / row_width);
+    }
+    let min_scale = MIN_OPTOTYPE_HEIGHT / get_optotype_height(NUM_ROWS - 1, STANDARD_CHART_DISTANCE);
+    let max_distance = (STANDARD_CHART_DISTANCE * max_scale).floor();
+    let min_distance = (STANDARD_CHART_DISTANCE * min_scale).ceil();
+    target_distance.round().max(min_distance).min(max_distance)
+}
+
+/// The total height (in mm) of the optotypes on every row of a chart printed for the given distance
+fn get_chart_height(chart_distance: f64) -> f64 {
+    (0..NUM_ROWS).map(|row| get_optotype_height(row, chart_distance)).sum()
+}
+
+/// The width (in mm) of a row with the given number of optotypes of the given height
+fn get_row_width(num_optotypes: usize, height: f64) -> f64 {
+    num_optotypes as f64 * height + (num_optotypes as f64 - 1.0) * OPTOTYPE_SPACING * height
+}
+
+/// Calculates the range of acuity (from the top row to the bottom row) which a chart printed for
+/// `chart_distance` covers when it is read from `viewing_distance`.
+pub fn get_acuity_range(viewing_distance: f64, chart_distance: f64) -> (Acuity, Acuity) {
+    (get_row_acuity(0, viewing_distance, chart_distance), get_row_acuity(NUM_ROWS - 1, viewing_distance, chart_distance))
+}
+
+/// Generates a printable chart for an arrangement of optotypes, sized to be read from `viewing_distance` (in cm).
+/// The distance which the chart was actually printed for, and the range of acuity it covers, are printed at
+/// the top of the chart because the clinician needs them to score the test.
+pub fn generate(arrangement: &OptotypeArrangement, viewing_distance: f64, locale: Locale, paper: PaperSize) -> Result<Vec<u8>, PdfError> {
+    if let Err(e) = scoring::check_distance(viewing_distance) {
+        return Err(PdfError(e.message));
+    }
+    let chart_distance = get_chart_distance(viewing_distance, paper);
+    let (top_acuity, bottom_acuity) = get_acuity_range(viewing_distance, chart_distance);
+
+    let title = i18n::translate(locale, "pdf-chart-title", &[("code", arrangement.code.clone())]);
+    let (doc, page, layer) = PdfDocument::new(title.clone(), Mm(paper.width()), Mm(paper.height()), "Chart");
+    let layer = doc.get_page(page).get_layer(layer);
+    let optotype_font = add_optotype_font(&doc, &arrangement.optotype_definition)?;
+    let text_font = add_text_font(&doc, locale)?;
+
+    // Title, and the information needed to score the chart
+    layer.use_text(title, 20.0, Mm(SIDE_MARGIN), Mm(paper.height() - 20.0), &text_font);
+    layer.use_text(i18n::translate(locale, "pdf-chart-printed-for", &[("distance", chart_distance.to_string())]),
+        10.0, Mm(SIDE_MARGIN), Mm(paper.height() - 28.0), &text_font);
+    let range_args = [
+        ("distance", viewing_distance.to_string()),
+        ("top", format!("{} ({})", top_acuity, top_acuity.snellen_metric())),
+        ("bottom", format!("{} ({})", bottom_acuity, bottom_acuity.snellen_metric()))
+    ];
+    let range_description = if chart_distance == viewing_distance {
+        i18n::translate(locale, "pdf-chart-range", &range_args)
+    } else {
+        i18n::translate(locale, "pdf-chart-range-clamped", &range_args)
+    };
+    layer.use_text(range_description, 10.0, Mm(SIDE_MARGIN), Mm(paper.height() - 34.0), &text_font);
+
+    // The rows of optotypes, centred on the page
+    let mut row_top = paper.height() - HEADER_HEIGHT;
+    for (row_number, row) in arrangement.rows.iter().enumerate() {
+        let height = get_optotype_height(row_number, chart_distance);
+        let row_width = get_row_width(row.optotypes.len(), height);
+        let baseline = row_top - height;
+        for (position, &optotype) in row.optotypes.iter().enumerate() {
+            let x = (paper.width() - row_width) / 2.0 + (1.0 + OPTOTYPE_SPACING) * height * position as f64;
+            let character = arrangement.optotype_definition.optotypes[optotype as usize];
+            layer.use_text(character.to_string(), height * POINTS_PER_MM, Mm(x), Mm(baseline), &optotype_font);
+        }
+        row_top = baseline - OPTOTYPE_SPACING * height;
+    }
+
+    // A line of known length, so that the person printing the chart can check it was printed to scale
+    let line_y = FOOTER_HEIGHT - 10.0;
+    layer.set_outline_thickness(1.0);
+    layer.add_shape(Line {
+        points: vec![(Point::new(Mm(SIDE_MARGIN), Mm(line_y)), false), (Point::new(Mm(SIDE_MARGIN + CALIBRATION_LINE_LENGTH), Mm(line_y)), false)],
+        is_closed: false,
+        has_fill: false,
+        has_stroke: true,
+        is_clipping_path: false
+    });
+    layer.use_text(i18n::translate(locale, "pdf-chart-calibration", &[("length", (CALIBRATION_LINE_LENGTH / 10.0).to_string())]),
+        8.0, Mm(SIDE_MARGIN), Mm(line_y - 5.0), &text_font);
+
+    save_to_bytes(doc)
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::str::FromStr;
+use printpdf::{PdfDocumentReference, IndirectFontRef, BuiltinFont};
+
+use crate::optotypes::OptotypeDefinition;
+use crate::fonts::get_ttf_path;
+use crate::i18n::Locale;
+
+pub mod answers;
+pub mod chart;
+pub mod naming;
+
+/// The paper which the PDFs are laid out for. All of the sheets are designed to be printed at 100% scale,
+/// so the layout (and the largest chart which fits) depends on the size of the paper.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaperSize {
+    A4,
+    Letter
+}
+
+impl PaperSize {
+    /// The width of the page (in mm)
+    pub fn width(&self) -> f64 {
+        match self {
+            PaperSize::A4 => 210.0,
+            PaperSize::Letter => 215.9
+        }
+    }
+
+    /// The height of the page (in mm)
+    pub fn height(&self) -> f64 {
+        match self {
+            PaperSize::A4 => 297.0,
+            PaperSize::Letter => 279.4
+        }
+    }
+}
+
+impl FromStr for PaperSize {
+    type Err = PdfError;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_lowercase().as_str() {
+            "a4" => Ok(PaperSize::A4),
+            "letter" => Ok(PaperSize::Letter),
+            _ => Err(PdfError(format!("\"{}\" is not a recognised paper size (use a4 or letter).", text)))
+        }
+    }
+}
+
+/// PDF font sizes are given in points, but the layout of the sheets is specified in mm.
+/// This is the number of points in one mm.
+pub const POINTS_PER_MM: f64 = 72.0 / 25.4;
+
+/// Embeds the optotype font for a particular optotype definition into a PDF document.
+pub fn add_optotype_font(doc: &PdfDocumentReference, optotype_definition: &OptotypeDefinition) -> Result<IndirectFontRef, PdfError> {
+    let path = get_ttf_path(optotype_definition);
+    let font_file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) => { return Err(PdfError(format!("Could not open the font file {} ({}).", path, e))); }
+    };
+    match doc.add_external_font(font_file) {
+        Ok(font) => Ok(font),
+        Err(e) => Err(PdfError(format!("Could not load the font file {} ({}).", path, e)))
+    }
+}
+
+/// The font used for the text of the sheets in languages other than English. The builtin PDF fonts only cover
+/// the Windows-1252 character set, which is missing letters used in Welsh (such as ŵ and ŷ) and Polish.
+pub const UNICODE_TEXT_FONT_PATH: &str = "static/fonts/dejavu-sans.ttf";
+
+/// Adds the font for the text (titles, instructions and optotype names) to a PDF document. English sheets use
+/// the builtin Helvetica font, which keeps them small, and other languages embed a font covering their letters.
+pub fn add_text_font(doc: &PdfDocumentReference, locale: Locale) -> Result<IndirectFontRef, PdfError> {
+    if locale == Locale::English {
+        return match doc.add_builtin_font(BuiltinFont::Helvetica) {
+            Ok(font) => Ok(font),
+            Err(e) => Err(PdfError(format!("Could not load the text font ({}).", e)))
+        };
+    }
+    let font_file = match File::open(UNICODE_TEXT_FONT_PATH) {
+        Ok(file) => file,
+        Err(e) => { return Err(PdfError(format!("Could not open the font file {} ({}).", UNICODE_TEXT_FONT_PATH, e))); }
+    };
+    match doc.add_external_font(font_file) {
+        Ok(font) => Ok(font),
+        Err(e) => Err(PdfError(format!("Could not load the font file {} ({}).", UNICODE_TEXT_FONT_PATH, e)))
+    }
+}
+
+/// Serialises a finished PDF document into the bytes which are sent to the user.
+pub fn save_to_bytes(doc: PdfDocumentReference) -> Result<Vec<u8>, PdfError> {
+    let mut writer = BufWriter::new(Vec::new());
+    if let Err(e) = doc.save(&mut writer) {
+        return Err(PdfError(format!("Failed to save the PDF document ({}).", e)));
+    }
+    match writer.into_inner() {
+        Ok(bytes) => Ok(bytes),
+        Err(e) => Err(PdfError(format!("Failed to write out the PDF document ({}).", e)))
+    }
+}
+
+/// This struct is used to represent errors when generating PDFs.
+#[derive(Debug)]
+pub struct PdfError(pub String);
+impl fmt::Display for PdfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error: {}", self.0)
+    }
+}
+impl Error for PdfError {}
@@ -0,0 +1,53 @@
+use printpdf::{PdfDocument, Mm};
+
+use crate::optotypes::OptotypeDefinition;
+use crate::i18n::{self, Locale};
+use crate::pdf::{PaperSize, POINTS_PER_MM, PdfError, add_optotype_font, add_text_font, save_to_bytes};
+
+/// Number of columns of optotypes on the naming sheet
+const NUM_COLUMNS: usize = 2;
+/// Height (in mm) at which each optotype is printed. This is deliberately large, so that
+/// the sheet can be read easily when practising the names of the symbols.
+const OPTOTYPE_SIZE: f64 = 25.0;
+/// Font size (in points) of the name printed beneath each optotype
+const NAME_FONT_SIZE: f64 = 16.0;
+/// Vertical space (in mm) on the page which is taken up by the title and instructions
+const HEADER_HEIGHT: f64 = 45.0;
+/// Margin (in mm) left at the bottom of the page
+const FOOTER_HEIGHT: f64 = 15.0;
+
+/// Generates a naming/practice sheet for a set of optotypes. Every optotype in the set is printed
+/// large, with its name beneath it, so that a patient can practise the names of the symbols before
+/// taking the test. Because the sheet is generated from the OptotypeDefinition, it stays in sync
+/// when new optotype sets are added.
+pub fn generate(optotype_definition: &OptotypeDefinition, locale: Locale, paper: PaperSize) -> Result<Vec<u8>, PdfError> {
+    let title = i18n::translate(locale, "pdf-naming-title", &[("optotypes", optotype_definition.name.clone())]);
+    let (doc, page, layer) = PdfDocument::new(title.clone(), Mm(paper.width()), Mm(paper.height()), "Naming sheet");
+    let layer = doc.get_page(page).get_layer(layer);
+    let optotype_font = add_optotype_font(&doc, optotype_definition)?;
+    let text_font = add_text_font(&doc, locale)?;
+
+    // Title and instructions
+    layer.use_text(title, 24.0, Mm(20.0), Mm(paper.height() - 25.0), &text_font);
+    layer.use_text(i18n::translate(locale, "pdf-naming-instructions", &[]), 12.0, Mm(20.0), Mm(paper.height() - 35.0), &text_font);
+
+    // Lay the optotypes out in a grid, filling each row from left to right. The height of each row
+    // of the grid is chosen so that all of the optotypes fit on a single page.
+    let num_grid_rows = optotype_definition.optotypes.len().div_ceil(NUM_COLUMNS);
+    let column_width = paper.width() / NUM_COLUMNS as f64;
+    let row_height = (paper.height() - HEADER_HEIGHT - FOOTER_HEIGHT) / num_grid_rows.max(1) as f64;
+    for (i, optotype) in optotype_definition.optotypes.iter().enumerate() {
+        let column_centre = column_width * ((i % NUM_COLUMNS) as f64 + 0.5);
+        let row_top = paper.height() - HEADER_HEIGHT - row_height * (i / NUM_COLUMNS) as f64;
+        // The optotypes are (approximately) square, so centring them only requires their height
+        let optotype_baseline = row_top - OPTOTYPE_SIZE;
+        layer.use_text(optotype.to_string(), OPTOTYPE_SIZE * POINTS_PER_MM, Mm(column_centre - OPTOTYPE_SIZE / 2.0), Mm(optotype_baseline), &optotype_font);
+        // The name is centred by estimating its width, using the average width of a Helvetica
+        // character (about half of the font size). DejaVu Sans is a little wider, which is close enough.
+        let name = &optotype_definition.names[i];
+        let name_width = name.chars().count() as f64 * 0.5 * NAME_FONT_SIZE / POINTS_PER_MM;
+        layer.use_text(name.clone(), NAME_FONT_SIZE, Mm(column_centre - name_width / 2.0), Mm(optotype_baseline - 10.0), &text_font);
+    }
+
+    save_to_bytes(doc)
+}
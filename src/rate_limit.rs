@@ -0,0 +1,315 @@
+use std::fmt;
+use std::error::Error;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::{Connection, OptionalExtension, params};
+use rocket::{Config, Outcome, Request, Rocket, State};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Status;
+use rocket::request::{self, FromRequest};
+
+use crate::AnswerFormat;
+use crate::audit::{hash_ip, random_salt};
+use crate::config::{self, AppConfig};
+use crate::sqlite;
+
+/// Each client can make this many requests in a burst, before being limited to the steady rate
+pub const DEFAULT_BURST: u32 = 10;
+/// The steady number of requests which each client can make in a minute
+pub const DEFAULT_REQUESTS_PER_MINUTE: u32 = 20;
+/// Requests to paths starting with these are rate limited, as they generate PDFs
+pub const DEFAULT_PATHS: [&str; 3] = ["/generate", "/standard", "/naming-sheet"];
+
+/// Buckets which have been full for this many seconds are removed from the store, at most this often
+const CLEANUP_INTERVAL_SECONDS: f64 = 600.0;
+
+/// The state of a client's token bucket. A request takes one token, and tokens are added back at a steady rate.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Bucket {
+    pub tokens: f64,
+    /// When the number of tokens was last worked out (in seconds since the Unix epoch)
+    pub updated: f64
+}
+
+/// Somewhere to keep the token bucket for each client. New stores only need to implement this trait to be used.
+pub trait BucketStore: Send + Sync {
+    fn load(&self, client: &str) -> Result<Option<Bucket>, RateLimitError>;
+    fn save(&self, client: &str, bucket: Bucket) -> Result<(), RateLimitError>;
+    /// Removes every bucket which was last updated before the given time
+    fn remove_before(&self, time: f64) -> Result<(), RateLimitError>;
+}
+
+/// Keeps the buckets in memory. This is the default store, but the limits are forgotten when the server restarts
+/// and aren't shared between servers.
+#[derive(Default)]
+pub struct MemoryStore {
+    buckets: Mutex<HashMap<String, Bucket>>
+}
+
+impl BucketStore for MemoryStore {
+    fn load(&self, client: &str) -> Result<Option<Bucket>, RateLimitError> {
+        Ok(self.buckets.lock().unwrap().get(client).cloned())
+    }
+
+    fn save(&self, client: &str, bucket: Bucket) -> Result<(), RateLimitError> {
+        self.buckets.lock().unwrap().insert(client.to_string(), bucket);
+        Ok(())
+    }
+
+    fn remove_before(&self, time: f64) -> Result<(), RateLimitError> {
+        self.buckets.lock().unwrap().retain(|_, bucket| bucket.updated >= time);
+        Ok(())
+    }
+}
+
+fn sqlite_error(e: rusqlite::Error) -> RateLimitError {
+    RateLimitError(sqlite::error_message("rate limit", e))
+}
+
+/// Keeps the buckets in an SQLite database, so that limits survive restarts and can be shared between servers
+pub struct SqliteStore {
+    connection: Mutex<Connection>
+}
+
+impl SqliteStore {
+    /// Opens the buckets at the given path (rate_limit_path), creating the database if it doesn't exist
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<SqliteStore, RateLimitError> {
+        let connection = sqlite::open(Some(path.as_ref())).map_err(sqlite_error)?;
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS buckets (client TEXT PRIMARY KEY, tokens REAL NOT NULL, updated REAL NOT NULL)",
+            params![]
+        ).map_err(sqlite_error)?;
+        connection.execute("CREATE TABLE IF NOT EXISTS settings (name TEXT PRIMARY KEY, value TEXT NOT NULL)", params![])
+            .map_err(sqlite_error)?;
+        Ok(SqliteStore { connection: Mutex::new(connection) })
+    }
+
+    /// The salt which client addresses are hashed with. This is chosen the first time the database is used and kept
+    /// in it, so that clients keep the same buckets when the server restarts, and every server sharing the database
+    /// hashes addresses in the same way.
+    pub fn salt(&self) -> Result<String, RateLimitError> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute("INSERT OR IGNORE INTO settings (name, value) VALUES ('salt', ?1)", params![random_salt()])
+            .map_err(sqlite_error)?;
+        connection.query_row("SELECT value FROM settings WHERE name = 'salt'", params![], |row| row.get(0))
+            .map_err(sqlite_error)
+    }
+}
+
+impl BucketStore for SqliteStore {
+    fn load(&self, client: &str) -> Result<Option<Bucket>, RateLimitError> {
+        let connection = self.connection.lock().unwrap();
+        connection.query_row("SELECT tokens, updated FROM buckets WHERE client = ?1", params![client],
+            |row| Ok(Bucket { tokens: row.get(0)?, updated: row.get(1)? }))
+            .optional()
+            .map_err(sqlite_error)
+    }
+
+    fn save(&self, client: &str, bucket: Bucket) -> Result<(), RateLimitError> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute("INSERT OR REPLACE INTO buckets (client, tokens, updated) VALUES (?1, ?2, ?3)",
+            params![client, bucket.tokens, bucket.updated])
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn remove_before(&self, time: f64) -> Result<(), RateLimitError> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute("DELETE FROM buckets WHERE updated < ?1", params![time]).map_err(sqlite_error)?;
+        Ok(())
+    }
+}
+
+/// Limits the rate of requests from each client to the expensive routes, using a token bucket for each client.
+/// This is kept in Rocket's managed state, and used by the RateLimitFairing.
+pub struct RateLimiter {
+    store: Box<dyn BucketStore>,
+    burst: f64,
+    tokens_per_second: f64,
+    paths: Vec<String>,
+    salt: String,
+    /// Taking a token is a load followed by a save, so only one client is updated at a time
+    lock: Mutex<f64>
+}
+
+impl RateLimiter {
+    pub fn new(store: Box<dyn BucketStore>, burst: u32, requests_per_minute: u32, paths: Vec<String>) -> RateLimiter {
+        RateLimiter {
+            store,
+            burst: f64::from(burst),
+            tokens_per_second: f64::from(requests_per_minute) / 60.0,
+            paths,
+            salt: random_salt(),
+            lock: Mutex::new(0.0)
+        }
+    }
+
+    /// Creates the rate limiter from the "rate_limit_*" settings in Rocket.toml (or the matching ROCKET_RATE_LIMIT_*
+    /// environment variables). Returns None if rate limiting has been turned off with rate_limit_store = "none".
+    ///
+    /// - rate_limit_store: "memory" (the default), "sqlite" or "none"
+    /// - rate_limit_path: where the SQLite database is kept (default "rate-limits.sqlite")
+    /// - rate_limit_burst: the number of requests a client can make at once (default 10)
+    /// - rate_limit_per_minute: the steady number of requests a client can make each minute (default 20)
+    /// - rate_limit_paths: the path prefixes which are limited (default "/generate", "/standard" and "/naming-sheet").
+    ///   Only routes with the RateLimit guard can be limited. Answer keys asked for as PDFs are always limited.
+    ///
+    /// Clients are identified by a hash of their IP address, using the audit_ip_salt setting (passed in from the site
+    /// configuration) if there is one. Otherwise the SQLite store keeps its own salt, and the memory store (which is
+    /// forgotten on restart anyway) uses a random one.
+    pub fn from_config(config: &Config, audit_ip_salt: Option<&str>) -> Result<Option<RateLimiter>, RateLimitError> {
+        let (store, stored_salt): (Box<dyn BucketStore>, Option<String>) = match config.get_str("rate_limit_store").unwrap_or("memory").to_lowercase().as_str() {
+            "memory" => (Box::new(MemoryStore::default()), None),
+            "sqlite" => {
+                let store = SqliteStore::open(config.get_str("rate_limit_path").unwrap_or("rate-limits.sqlite"))?;
+                let salt = store.salt()?;
+                (Box::new(store), Some(salt))
+            },
+            "none" => { return Ok(None); },
+            other => { return Err(RateLimitError(format!("\"{}\" is not a recognised rate limit store (use memory, sqlite or none).", other))); }
+        };
+        let get_positive = |name: &str, default: u32| match config.get_int(name) {
+            Ok(value) if value >= 1 && value <= i64::from(u32::MAX) => Ok(value as u32),
+            Ok(_) => Err(RateLimitError(format!("The {} setting must be a positive number.", name))),
+            Err(_) => Ok(default)
+        };
+        let burst = get_positive("rate_limit_burst", DEFAULT_BURST)?;
+        let requests_per_minute = get_positive("rate_limit_per_minute", DEFAULT_REQUESTS_PER_MINUTE)?;
+        let paths = match config.get_slice("rate_limit_paths") {
+            Ok(paths) => paths.iter().filter_map(|path| path.as_str()).map(String::from).collect(),
+            Err(_) => DEFAULT_PATHS.iter().map(|path| path.to_string()).collect()
+        };
+        let mut limiter = RateLimiter::new(store, burst, requests_per_minute, paths);
+        if let Some(salt) = audit_ip_salt.map(String::from).or(stored_salt) {
+            limiter.salt = salt;
+        }
+        Ok(Some(limiter))
+    }
+
+    /// Whether requests to a path are rate limited
+    pub fn is_limited_path(&self, path: &str) -> bool {
+        self.paths.iter().any(|prefix| path.starts_with(prefix.as_str()))
+    }
+
+    /// Whether a request is rate limited. As well as the limited paths, this includes answer keys which are asked
+    /// for as PDFs (with format=pdf or the Accept header), while the HTML and JSON answer sheets are not limited.
+    pub fn is_limited(&self, request: &Request) -> bool {
+        if self.is_limited_path(request.uri().path()) {
+            return true;
+        }
+        let format = request.get_query_value::<String>("format").and_then(Result::ok);
+        request.uri().path() == "/answers" && matches!(AnswerFormat::choose(format.as_deref(), request.accept()), Some(AnswerFormat::Pdf))
+    }
+
+    /// Takes a token from a client's bucket at the given time (in seconds since the Unix epoch). If the bucket
+    /// is empty, returns the number of seconds until the client can try again.
+    pub fn take_at(&self, client: &str, now: f64) -> Result<(), u64> {
+        let mut last_cleanup = self.lock.lock().unwrap();
+        // The limiter fails open, so that a problem with the store doesn't stop anyone from downloading sheets
+        let bucket = match self.store.load(client) {
+            Ok(Some(bucket)) => bucket,
+            Ok(None) => Bucket { tokens: self.burst, updated: now },
+            Err(e) => {
+                log::error!("{}", e);
+                return Ok(());
+            }
+        };
+        let tokens = (bucket.tokens + (now - bucket.updated).max(0.0) * self.tokens_per_second).min(self.burst);
+        let (result, tokens) = if tokens >= 1.0 {
+            (Ok(()), tokens - 1.0)
+        } else {
+            (Err(((1.0 - tokens) / self.tokens_per_second).ceil() as u64), tokens)
+        };
+        if let Err(e) = self.store.save(client, Bucket { tokens, updated: now }) {
+            log::error!("{}", e);
+        }
+
+        // Buckets which would have refilled completely are the same as having no bucket, so can be removed
+        if now - *last_cleanup > CLEANUP_INTERVAL_SECONDS {
+            if let Err(e) = self.store.remove_before(now - self.burst / self.tokens_per_second) {
+                log::error!("{}", e);
+            }
+            *last_cleanup = now;
+        }
+        result
+    }
+
+    /// Takes a token from the bucket for the client with the given IP address, now
+    pub fn take(&self, ip: Option<IpAddr>) -> Result<(), u64> {
+        let client = match ip {
+            Some(ip) => hash_ip(&self.salt, ip),
+            None => "unknown".to_string()
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs_f64()).unwrap_or(0.0);
+        self.take_at(&client, now)
+    }
+}
+
+/// Stored in the request-local cache when a request has been rate limited, with the number of seconds to wait
+struct Limited(Option<u64>);
+
+/// The number of seconds a client must wait before trying again, if its request has been rate limited. This is used
+/// by the 429 catcher for the Retry-After header.
+pub fn retry_after(request: &Request) -> Option<u64> {
+    request.local_cache(|| Limited(None)).0
+}
+
+/// A request guard for the routes which generate PDFs, which takes a token from the client's bucket (for each client,
+/// as found by config::client_ip). Once the client has used up its bucket the guard fails with 429 Too Many
+/// Requests, so the PDF is never generated. Requests which aren't limited (see RateLimiter::is_limited), and every
+/// request when rate limiting is turned off, are let through without taking a token.
+pub struct RateLimit;
+
+impl<'a, 'r> FromRequest<'a, 'r> for RateLimit {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let retry_after = match request.guard::<State<RateLimiter>>().succeeded() {
+            Some(limiter) if limiter.is_limited(request) => limiter.take(config::client_ip(request)).err(),
+            _ => None
+        };
+        match retry_after {
+            Some(retry_after) => {
+                request.local_cache(|| Limited(Some(retry_after)));
+                Outcome::Failure((Status::TooManyRequests, ()))
+            },
+            None => Outcome::Success(RateLimit)
+        }
+    }
+}
+
+/// Creates the rate limiter from the configuration when the server starts, so that the RateLimit guard can use it
+pub struct RateLimitFairing;
+
+impl Fairing for RateLimitFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Rate limiter",
+            kind: Kind::Attach
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let audit_ip_salt = rocket.state::<AppConfig>().and_then(|app_config| app_config.audit_ip_salt.clone());
+        match RateLimiter::from_config(rocket.config(), audit_ip_salt.as_deref()) {
+            Ok(Some(limiter)) => Ok(rocket.manage(limiter)),
+            Ok(None) => Ok(rocket),
+            Err(e) => {
+                log::error!("{}", e);
+                Err(rocket)
+            }
+        }
+    }
+}
+
+/// This struct is used to represent errors when setting up or storing rate limits.
+#[derive(Debug)]
+pub struct RateLimitError(pub String);
+impl fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error: {}", self.0)
+    }
+}
+impl Error for RateLimitError {}
@@ -0,0 +1,366 @@
+use std::fmt;
+use std::error::Error;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use chrono::{NaiveDateTime, Timelike, Utc};
+use rusqlite::{Connection, params};
+use rocket::Config;
+use schemars::JsonSchema;
+
+use crate::acuity::Acuity;
+use crate::monitoring::Eye;
+use crate::optotypes::OptotypeArrangement;
+use crate::scoring::Score;
+use crate::sqlite;
+
+/// The longest patient identifier which can be stored
+pub const MAX_PATIENT_ID_LENGTH: usize = 32;
+
+/// A pseudonymous identifier for a patient, chosen by the clinic (e.g. a study number such as "P-0017"). Names and
+/// hospital numbers must not be used. Identifiers are made of letters, digits, dashes and underscores, and must
+/// contain at least one letter, so that hospital and NHS numbers (which are all digits) are refused if they are
+/// entered by mistake. They are compared ignoring case, and stored in upper case.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, JsonSchema)]
+#[serde(transparent)]
+pub struct PatientId(String);
+
+impl PatientId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for PatientId {
+    type Err = ResultsError;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let id = text.trim().to_uppercase();
+        if id.is_empty() || id.len() > MAX_PATIENT_ID_LENGTH {
+            return Err(ResultsError(format!("Patient identifiers must be between 1 and {} characters long.", MAX_PATIENT_ID_LENGTH)));
+        }
+        if !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(ResultsError("Patient identifiers can only contain letters, digits, dashes and underscores.".into()));
+        }
+        if !id.chars().any(|c| c.is_ascii_alphabetic()) {
+            return Err(ResultsError("Patient identifiers must contain at least one letter, so that hospital numbers can't be stored by mistake.".into()));
+        }
+        Ok(PatientId(id))
+    }
+}
+
+impl fmt::Display for PatientId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The result of a scored test, as it is stored. Only what is needed to follow a patient's vision over time
+/// is kept: the pseudonymous identifier, the chart, the eye, the distances and the score.
+#[derive(Debug, PartialEq, Clone, Serialize, JsonSchema)]
+pub struct TestResult {
+    pub patient: PatientId,
+    /// The code of the chart which was read
+    pub code: String,
+    pub eye: Eye,
+    /// The distance (in cm) from which the chart was read
+    pub viewing_distance: f64,
+    /// The viewing distance (in cm) which the chart was printed for
+    pub chart_distance: f64,
+    /// The number of optotypes which were read correctly
+    pub num_correct: u32,
+    /// The letter-by-letter score, or None if no optotypes were read
+    pub acuity: Option<Acuity>,
+    /// When the test was done
+    pub tested_at: NaiveDateTime,
+    /// When the result was stored
+    pub recorded_at: NaiveDateTime
+}
+
+impl TestResult {
+    /// Creates the result of a test from its score, recorded now. Times are kept to the nearest second.
+    pub fn new(patient: PatientId, arrangement: &OptotypeArrangement, eye: Eye, score: &Score, tested_at: NaiveDateTime) -> TestResult {
+        let to_second = |time: NaiveDateTime| time.with_nanosecond(0).unwrap_or(time);
+        TestResult {
+            patient,
+            code: arrangement.code.clone(),
+            eye,
+            viewing_distance: score.viewing_distance,
+            chart_distance: score.chart_distance,
+            num_correct: score.num_correct,
+            acuity: score.acuity,
+            tested_at: to_second(tested_at),
+            recorded_at: to_second(Utc::now().naive_utc())
+        }
+    }
+}
+
+/// A result which has been stored, along with the identifier it was given by the store
+#[derive(Debug, PartialEq, Clone, Serialize, JsonSchema)]
+pub struct StoredResult {
+    pub id: i64,
+    #[serde(flatten)]
+    pub result: TestResult
+}
+
+/// A chart which was issued through the API for a patient, so that the clinic can see which charts a patient has been
+/// sent (and when) before any of them have been scored
+#[derive(Debug, PartialEq, Clone, Serialize, JsonSchema)]
+pub struct IssuedChart {
+    pub patient: PatientId,
+    /// The code of the chart which was issued
+    pub code: String,
+    /// The eye which the chart is for
+    pub eye: Eye,
+    /// The viewing distance (in cm) which the chart is printed for
+    pub chart_distance: f64,
+    /// When the chart was issued
+    pub issued_at: NaiveDateTime
+}
+
+impl IssuedChart {
+    /// Creates the record of a chart issued now, to the nearest second
+    pub fn new(patient: PatientId, arrangement: &OptotypeArrangement, eye: Eye, chart_distance: f64) -> IssuedChart {
+        let issued_at = Utc::now().naive_utc();
+        IssuedChart {
+            patient,
+            code: arrangement.code.clone(),
+            eye,
+            chart_distance,
+            issued_at: issued_at.with_nanosecond(0).unwrap_or(issued_at)
+        }
+    }
+}
+
+/// Somewhere to keep test results. New stores only need to implement this trait to be used by the server.
+pub trait ResultStore: Send + Sync {
+    /// Stores a result, returning the identifier it was given
+    fn record(&self, result: &TestResult) -> Result<i64, ResultsError>;
+    /// Lists the results for a patient, in the order the tests were done
+    fn results_for(&self, patient: &PatientId) -> Result<Vec<StoredResult>, ResultsError>;
+    /// Lists every patient with at least one result, in order
+    fn patients(&self) -> Result<Vec<PatientId>, ResultsError>;
+    /// Stores a chart which has been issued to a patient, returning the identifier it was given
+    fn record_issue(&self, issue: &IssuedChart) -> Result<i64, ResultsError>;
+    /// Lists the charts issued to a patient, in the order they were issued
+    fn issued_to(&self, patient: &PatientId) -> Result<Vec<IssuedChart>, ResultsError>;
+}
+
+fn sqlite_error(e: rusqlite::Error) -> ResultsError {
+    ResultsError(sqlite::error_message("results", e))
+}
+
+fn parse_timestamp(timestamp: &str) -> Result<NaiveDateTime, ResultsError> {
+    sqlite::parse_timestamp(timestamp)
+        .ok_or_else(|| ResultsError(format!("The results database contains an invalid timestamp ({}).", timestamp)))
+}
+
+/// Keeps results in an SQLite database, which is the only store so far. The database holds pseudonymous health
+/// data, so it should be backed up and protected like the clinic's other records.
+pub struct SqliteStore {
+    connection: Mutex<Connection>
+}
+
+impl SqliteStore {
+    /// Opens the results at the given path (results_path), creating the tables the first time it is used
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<SqliteStore, ResultsError> {
+        SqliteStore::from_connection(sqlite::open(Some(path.as_ref())).map_err(sqlite_error)?)
+    }
+
+    /// Creates a store with no patients which is lost when it is dropped, so that tests never touch real results
+    pub fn in_memory() -> Result<SqliteStore, ResultsError> {
+        SqliteStore::from_connection(sqlite::open(None).map_err(sqlite_error)?)
+    }
+
+    fn from_connection(connection: Connection) -> Result<SqliteStore, ResultsError> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS results (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                patient TEXT NOT NULL,
+                code TEXT NOT NULL,
+                eye TEXT NOT NULL,
+                viewing_distance REAL NOT NULL,
+                chart_distance REAL NOT NULL,
+                num_correct INTEGER NOT NULL,
+                log_mar REAL,
+                tested_at TEXT NOT NULL,
+                recorded_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS results_by_patient ON results (patient, tested_at);
+            CREATE TABLE IF NOT EXISTS issued_charts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                patient TEXT NOT NULL,
+                code TEXT NOT NULL,
+                eye TEXT NOT NULL,
+                chart_distance REAL NOT NULL,
+                issued_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS issued_charts_by_patient ON issued_charts (patient, issued_at);"
+        ).map_err(sqlite_error)?;
+        Ok(SqliteStore { connection: Mutex::new(connection) })
+    }
+}
+
+impl ResultStore for SqliteStore {
+    fn record(&self, result: &TestResult) -> Result<i64, ResultsError> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT INTO results (patient, code, eye, viewing_distance, chart_distance, num_correct, log_mar, tested_at, recorded_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                result.patient.as_str(),
+                result.code,
+                result.eye.to_string(),
+                result.viewing_distance,
+                result.chart_distance,
+                result.num_correct,
+                result.acuity.map(|acuity| acuity.log_mar()),
+                sqlite::format_timestamp(result.tested_at),
+                sqlite::format_timestamp(result.recorded_at)
+            ]
+        ).map_err(sqlite_error)?;
+        Ok(connection.last_insert_rowid())
+    }
+
+    fn results_for(&self, patient: &PatientId) -> Result<Vec<StoredResult>, ResultsError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT id, code, eye, viewing_distance, chart_distance, num_correct, log_mar, tested_at, recorded_at
+                FROM results WHERE patient = ?1 ORDER BY tested_at, id"
+        ).map_err(sqlite_error)?;
+        let rows = statement.query_map(params![patient.as_str()], |row| Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, f64>(3)?,
+            row.get::<_, f64>(4)?,
+            row.get::<_, u32>(5)?,
+            row.get::<_, Option<f64>>(6)?,
+            row.get::<_, String>(7)?,
+            row.get::<_, String>(8)?
+        ))).map_err(sqlite_error)?;
+        let mut results: Vec<StoredResult> = Vec::new();
+        for row in rows {
+            let (id, code, eye, viewing_distance, chart_distance, num_correct, log_mar, tested_at, recorded_at) = row.map_err(sqlite_error)?;
+            let eye = Eye::from_str(&eye).map_err(|_| ResultsError(format!("The results database contains an invalid eye ({}).", eye)))?;
+            results.push(StoredResult {
+                id,
+                result: TestResult {
+                    patient: patient.clone(),
+                    code,
+                    eye,
+                    viewing_distance,
+                    chart_distance,
+                    num_correct,
+                    acuity: log_mar.map(Acuity::from_log_mar),
+                    tested_at: parse_timestamp(&tested_at)?,
+                    recorded_at: parse_timestamp(&recorded_at)?
+                }
+            });
+        }
+        Ok(results)
+    }
+
+    fn patients(&self) -> Result<Vec<PatientId>, ResultsError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare("SELECT DISTINCT patient FROM results ORDER BY patient").map_err(sqlite_error)?;
+        let rows = statement.query_map(params![], |row| row.get::<_, String>(0)).map_err(sqlite_error)?;
+        let mut patients: Vec<PatientId> = Vec::new();
+        for row in rows {
+            patients.push(PatientId(row.map_err(sqlite_error)?));
+        }
+        Ok(patients)
+    }
+
+    fn record_issue(&self, issue: &IssuedChart) -> Result<i64, ResultsError> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT INTO issued_charts (patient, code, eye, chart_distance, issued_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![issue.patient.as_str(), issue.code, issue.eye.to_string(), issue.chart_distance, sqlite::format_timestamp(issue.issued_at)]
+        ).map_err(sqlite_error)?;
+        Ok(connection.last_insert_rowid())
+    }
+
+    fn issued_to(&self, patient: &PatientId) -> Result<Vec<IssuedChart>, ResultsError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT code, eye, chart_distance, issued_at FROM issued_charts WHERE patient = ?1 ORDER BY issued_at, id"
+        ).map_err(sqlite_error)?;
+        let rows = statement.query_map(params![patient.as_str()], |row| Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, f64>(2)?,
+            row.get::<_, String>(3)?
+        ))).map_err(sqlite_error)?;
+        let mut issued: Vec<IssuedChart> = Vec::new();
+        for row in rows {
+            let (code, eye, chart_distance, issued_at) = row.map_err(sqlite_error)?;
+            let eye = Eye::from_str(&eye).map_err(|_| ResultsError(format!("The results database contains an invalid eye ({}).", eye)))?;
+            issued.push(IssuedChart {
+                patient: patient.clone(),
+                code,
+                eye,
+                chart_distance,
+                issued_at: parse_timestamp(&issued_at)?
+            });
+        }
+        Ok(issued)
+    }
+}
+
+/// The stored test results, which are kept in Rocket's managed state when results are being recorded
+pub struct ResultRepository {
+    store: Box<dyn ResultStore>
+}
+
+impl ResultRepository {
+    pub fn new(store: Box<dyn ResultStore>) -> ResultRepository {
+        ResultRepository { store }
+    }
+
+    /// Creates the repository from the "results_*" settings in Rocket.toml (or the matching ROCKET_RESULTS_*
+    /// environment variables). Returns None unless results are being stored.
+    ///
+    /// - results_store: "sqlite" or "none" (the default)
+    /// - results_path: where the database is kept (default "results.sqlite")
+    pub fn from_config(config: &Config) -> Result<Option<ResultRepository>, ResultsError> {
+        let setting = |name: &str, default: &str| config.get_str(name).unwrap_or(default).to_string();
+        let store: Box<dyn ResultStore> = match setting("results_store", "none").to_lowercase().as_str() {
+            "sqlite" => Box::new(SqliteStore::open(setting("results_path", "results.sqlite"))?),
+            "none" => { return Ok(None); },
+            other => { return Err(ResultsError(format!("\"{}\" is not a recognised results store (use sqlite or none).", other))); }
+        };
+        Ok(Some(ResultRepository::new(store)))
+    }
+
+    /// Stores a result, returning it along with its identifier
+    pub fn record(&self, result: TestResult) -> Result<StoredResult, ResultsError> {
+        let id = self.store.record(&result)?;
+        Ok(StoredResult { id, result })
+    }
+
+    pub fn results_for(&self, patient: &PatientId) -> Result<Vec<StoredResult>, ResultsError> {
+        self.store.results_for(patient)
+    }
+
+    pub fn patients(&self) -> Result<Vec<PatientId>, ResultsError> {
+        self.store.patients()
+    }
+
+    pub fn record_issue(&self, issue: &IssuedChart) -> Result<i64, ResultsError> {
+        self.store.record_issue(issue)
+    }
+
+    pub fn issued_to(&self, patient: &PatientId) -> Result<Vec<IssuedChart>, ResultsError> {
+        self.store.issued_to(patient)
+    }
+}
+
+/// This struct is used to represent errors when storing or reading test results, including invalid patient identifiers.
+#[derive(Debug)]
+pub struct ResultsError(pub String);
+impl fmt::Display for ResultsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error: {}", self.0)
+    }
+}
+impl Error for ResultsError {}
@@ -0,0 +1,221 @@
+use std::fmt;
+use std::error::Error;
+use schemars::JsonSchema;
+
+use crate::acuity::Acuity;
+use crate::codes::{NUM_ROWS, NUM_OPTOTYPES_ON_ROW, ROW_LOG_MAR};
+use crate::optotypes::OptotypeArrangement;
+
+/// The viewing distance (in cm) which charts are printed for unless another distance is requested. The optotype
+/// heights in ROW_LOG_MAR give a chart covering 1.3 to 0.1 logMAR at this distance.
+pub const STANDARD_CHART_DISTANCE: f64 = 150.0;
+
+/// The range of viewing distances (in cm) which can be scored. Tests at 3 m are usually done with a mirror
+/// in a small room, so the distance is the total distance from the patient to the chart via the mirror.
+pub const MIN_VIEWING_DISTANCE: f64 = 50.0;
+pub const MAX_VIEWING_DISTANCE: f64 = 600.0;
+
+/// The angle (in arcminutes) subtended by an optotype at the threshold of normal (0.0 logMAR) vision.
+/// Optotypes are five times the size of their critical detail, which subtends one arcminute.
+const OPTOTYPE_ARCMINUTES_AT_ZERO_LOG_MAR: f64 = 5.0;
+
+/// The result of scoring a chart. Scores are calculated letter-by-letter, with each optotype
+/// worth an equal share of the step in logMAR between its row and the row above. A row-based
+/// "threshold" row is also calculated, which is the smallest row (working down from the top)
+/// on which at least half of the optotypes were read correctly.
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Score {
+    /// The distance (in cm) from which the chart was read
+    pub viewing_distance: f64,
+    /// The viewing distance (in cm) which the chart was printed for
+    pub chart_distance: f64,
+    /// The number of optotypes which were read correctly on each row, from the top row down
+    pub correct_per_row: Vec<u32>,
+    /// The total number of optotypes which were read correctly
+    pub num_correct: u32,
+    /// The total number of optotypes on the chart
+    pub num_optotypes: u32,
+    /// The acuity equivalent of each row of the chart at this viewing distance
+    pub row_acuity: Vec<Acuity>,
+    /// The letter-by-letter score. This is None if no optotypes were read, because the
+    /// acuity is then worse than the largest row of the chart can measure.
+    pub acuity: Option<Acuity>,
+    /// The index (from the top of the chart) of the smallest row which was read
+    pub threshold_row: Option<usize>,
+    /// A sentence summarising the score, for including in clinical notes
+    pub description: String
+}
+
+/// Calculates the height (in mm) of the optotypes on a row of a chart printed for the given viewing distance (in cm).
+/// The entries of ROW_LOG_MAR are the heights of the optotypes on each row of a chart printed for STANDARD_CHART_DISTANCE,
+/// and charts for other distances are scaled in proportion so that they cover the same range of acuity.
+pub fn get_optotype_height(row: usize, chart_distance: f64) -> f64 {
+    ROW_LOG_MAR[row] * chart_distance / STANDARD_CHART_DISTANCE
+}
+
+/// Calculates the acuity equivalent of a row of a chart printed for `chart_distance`, when read from `viewing_distance`
+/// (both in cm). This is the logMAR of the row at the chart distance, adjusted by log10(chart_distance / viewing_distance).
+pub fn get_row_acuity(row: usize, viewing_distance: f64, chart_distance: f64) -> Acuity {
+    let zero_log_mar_height = viewing_distance * 10.0 * (OPTOTYPE_ARCMINUTES_AT_ZERO_LOG_MAR / 60.0).to_radians().tan();
+    Acuity::from_log_mar((get_optotype_height(row, chart_distance) / zero_log_mar_height).log10())
+}
+
+/// Scores a chart from the number of optotypes which were read correctly. The patient is assumed to have
+/// read the chart from the top down, so the correct optotypes are assigned to the largest rows first.
+pub fn score_arrangement(arrangement: &OptotypeArrangement, viewing_distance: f64, chart_distance: f64, num_correct: u32) -> Result<Score, ScoringError> {
+    check_arrangement(arrangement)?;
+    score_count(viewing_distance, chart_distance, num_correct)
+}
+
+/// Scores a chart from the number of optotypes which were read correctly, without needing to know which chart
+/// was used. Every chart has the same layout of rows, so this gives the same score as `score_arrangement`.
+pub fn score_count(viewing_distance: f64, chart_distance: f64, num_correct: u32) -> Result<Score, ScoringError> {
+    let num_optotypes: u32 = NUM_OPTOTYPES_ON_ROW.iter().sum();
+    if num_correct > num_optotypes {
+        return Err(ScoringError::new(ScoringErrorKind::TooManyCorrect { optotypes: num_optotypes },
+            format!("The number of correctly read optotypes must be between 0 and {} (inclusive).", num_optotypes)));
+    }
+    let mut remaining = num_correct;
+    let mut correct_per_row: Vec<u32> = Vec::new();
+    for &num_optotypes_on_row in NUM_OPTOTYPES_ON_ROW.iter() {
+        let correct_on_row = remaining.min(num_optotypes_on_row);
+        correct_per_row.push(correct_on_row);
+        remaining -= correct_on_row;
+    }
+    score_rows(viewing_distance, chart_distance, &correct_per_row)
+}
+
+/// Scores a chart from the number of optotypes which were read correctly on each row (from the top row down),
+/// for when the clinician has recorded exactly which optotypes were read.
+pub fn score_arrangement_rows(arrangement: &OptotypeArrangement, viewing_distance: f64, chart_distance: f64, correct_per_row: &[u32]) -> Result<Score, ScoringError> {
+    check_arrangement(arrangement)?;
+    score_rows(viewing_distance, chart_distance, correct_per_row)
+}
+
+/// Checks that an arrangement has the layout which the scoring assumes
+fn check_arrangement(arrangement: &OptotypeArrangement) -> Result<(), ScoringError> {
+    let matches_layout = arrangement.rows.len() == NUM_ROWS &&
+        arrangement.rows.iter().zip(NUM_OPTOTYPES_ON_ROW.iter()).all(|(row, &num_optotypes)| row.optotypes.len() == num_optotypes as usize);
+    if matches_layout {
+        Ok(())
+    } else {
+        Err(ScoringError::new(ScoringErrorKind::UnexpectedLayout, "The chart does not have the expected number of rows and optotypes."))
+    }
+}
+
+/// Checks that a distance (in cm) is one which a chart can be printed for or read from
+pub fn check_distance(distance: f64) -> Result<(), ScoringError> {
+    if (MIN_VIEWING_DISTANCE..=MAX_VIEWING_DISTANCE).contains(&distance) {
+        Ok(())
+    } else {
+        Err(ScoringError::distance_out_of_range(MIN_VIEWING_DISTANCE, MAX_VIEWING_DISTANCE))
+    }
+}
+
+/// Calculates the score for a chart, given the number of optotypes read correctly on each row
+pub fn score_rows(viewing_distance: f64, chart_distance: f64, correct_per_row: &[u32]) -> Result<Score, ScoringError> {
+    check_distance(viewing_distance)?;
+    check_distance(chart_distance)?;
+    if correct_per_row.len() != NUM_ROWS {
+        return Err(ScoringError::new(ScoringErrorKind::MissingRows, format!("A score is needed for each of the {} rows of the chart.", NUM_ROWS)));
+    }
+    for row in 0..NUM_ROWS {
+        if correct_per_row[row] > NUM_OPTOTYPES_ON_ROW[row] {
+            return Err(ScoringError::new(ScoringErrorKind::TooManyCorrectOnRow { row: row + 1, optotypes: NUM_OPTOTYPES_ON_ROW[row] as usize, correct: correct_per_row[row] as usize },
+                format!("Row {} only has {} optotypes, but {} were read correctly.", row + 1, NUM_OPTOTYPES_ON_ROW[row], correct_per_row[row])));
+        }
+    }
+    let row_acuity: Vec<Acuity> = (0..NUM_ROWS).map(|row| get_row_acuity(row, viewing_distance, chart_distance)).collect();
+    let row_log_mar: Vec<f64> = row_acuity.iter().map(|acuity| acuity.log_mar()).collect();
+    let num_correct: u32 = correct_per_row.iter().sum();
+
+    // Letter-by-letter scoring starts from the (notional) row above the top of the chart, and each correct
+    // optotype reduces the score by its share of the step between its row and the row above it.
+    let acuity = if num_correct == 0 {
+        None
+    } else {
+        let mut log_mar = row_log_mar[0] + (row_log_mar[0] - row_log_mar[1]);
+        for row in 0..NUM_ROWS {
+            let step = if row == 0 { row_log_mar[0] - row_log_mar[1] } else { row_log_mar[row - 1] - row_log_mar[row] };
+            log_mar -= step * f64::from(correct_per_row[row]) / f64::from(NUM_OPTOTYPES_ON_ROW[row]);
+        }
+        Some(Acuity::from_log_mar(log_mar))
+    };
+
+    // The threshold row is the last of the rows (working down from the top) which were each at least half read
+    let mut threshold_row: Option<usize> = None;
+    for row in 0..NUM_ROWS {
+        if correct_per_row[row] * 2 >= NUM_OPTOTYPES_ON_ROW[row] {
+            threshold_row = Some(row);
+        } else {
+            break;
+        }
+    }
+
+    Ok(Score {
+        viewing_distance,
+        chart_distance,
+        correct_per_row: correct_per_row.to_vec(),
+        num_correct,
+        num_optotypes: NUM_OPTOTYPES_ON_ROW.iter().sum(),
+        description: describe(&row_acuity, threshold_row),
+        row_acuity,
+        acuity,
+        threshold_row
+    })
+}
+
+/// Produces a sentence describing the acuity range which is consistent with the threshold row
+fn describe(row_acuity: &[Acuity], threshold_row: Option<usize>) -> String {
+    let describe_row = |row: usize| format!("{} ({})", row_acuity[row], row_acuity[row].snellen_metric());
+    match threshold_row {
+        None => format!("Home-based estimation of vision was consistent with an acuity of less than {}", describe_row(0)),
+        Some(row) if row == NUM_ROWS - 1 => format!("Home-based estimation of vision was consistent with an acuity of at least {}", describe_row(row)),
+        Some(row) => format!("Home-based estimation of vision was consistent with an acuity of at least {}, although not as good as {}", describe_row(row), describe_row(row + 1))
+    }
+}
+
+/// This struct is used to represent errors when scoring a chart, such as impossible numbers of correct optotypes.
+/// The kind of error gives the message shown on the pages (see i18n::describe_scoring_error), and the message is
+/// the English description used by the API.
+#[derive(Debug)]
+pub struct ScoringError {
+    pub kind: ScoringErrorKind,
+    pub message: String
+}
+impl ScoringError {
+    pub fn new<S: Into<String>>(kind: ScoringErrorKind, message: S) -> ScoringError {
+        ScoringError { kind, message: message.into() }
+    }
+
+    /// The viewing distance (in cm) was outside the range which charts can be used at
+    pub fn distance_out_of_range(min: f64, max: f64) -> ScoringError {
+        ScoringError::new(ScoringErrorKind::DistanceOutOfRange { min, max },
+            format!("The viewing distance must be between {}cm and {}cm.", min, max))
+    }
+}
+impl fmt::Display for ScoringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error: {}", self.message)
+    }
+}
+
+/// The different reasons that a chart can't be scored or marked, with the values which the messages need
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoringErrorKind {
+    /// More optotypes were read correctly than there are on a chart
+    TooManyCorrect { optotypes: u32 },
+    /// The chart doesn't have the layout of rows which the scoring assumes
+    UnexpectedLayout,
+    /// The viewing distance (in cm) was outside the allowed range
+    DistanceOutOfRange { min: f64, max: f64 },
+    /// The number of correct optotypes wasn't given for every row
+    MissingRows,
+    /// More optotypes were read correctly on a row (counting from 1) than it has
+    TooManyCorrectOnRow { row: usize, optotypes: usize, correct: usize },
+    /// Responses were given for more rows than the chart has
+    TooManyRows { responses: usize, rows: usize },
+    /// More responses were given for a row (counting from 1) than it has optotypes
+    TooManyResponses { row: usize, optotypes: usize, responses: usize }
+}
+impl Error for ScoringError {}
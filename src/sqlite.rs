@@ -0,0 +1,35 @@
+use std::path::Path;
+use std::time::Duration;
+use chrono::NaiveDateTime;
+use rusqlite::Connection;
+
+/// Timestamps are stored in this format, so that they sort (and can be compared) as text
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// How long to wait for another process (e.g. a second server) which is writing to the same database
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Opens (or creates) the database at the given path, or a database which is lost when it is closed if there is no
+/// path
+pub fn open(path: Option<&Path>) -> rusqlite::Result<Connection> {
+    let connection = match path {
+        Some(path) => Connection::open(path)?,
+        None => Connection::open_in_memory()?
+    };
+    connection.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(connection)
+}
+
+pub fn format_timestamp(timestamp: NaiveDateTime) -> String {
+    timestamp.format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Reads a timestamp written by `format_timestamp`, returning None if the database holds something else
+pub fn parse_timestamp(timestamp: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()
+}
+
+/// The message for an error from SQLite, naming the database (e.g. "audit") so that it's clear which setting to check
+pub fn error_message(database: &str, e: rusqlite::Error) -> String {
+    format!("Could not use the {} database ({}).", database, e)
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use chrono::{Datelike, Duration, NaiveDate};
+use schemars::JsonSchema;
+
+use crate::audit::AuditEntry;
+
+/// Counts smaller than this are suppressed, so that the statistics can't be used to pick out individual users
+pub const MIN_REPORTED_COUNT: u64 = 5;
+
+/// The Monday starting the week which contains a date
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+}
+
+/// Whether downloads are counted for each week or for each day
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    Week,
+    Day
+}
+
+/// The number of downloads of one type of sheet in one week, or on one day
+#[derive(Debug, PartialEq, Serialize, JsonSchema)]
+pub struct UsageCount {
+    /// The Monday which the week starts on
+    pub week_start: NaiveDate,
+    /// The day, for daily counts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day: Option<NaiveDate>,
+    /// The type of sheet, e.g. "chart" or "naming-sheet"
+    pub sheet: String,
+    pub optotype_set: String,
+    /// The mode of test ("telephone" or "self") for sheets made from a code
+    pub mode: Option<String>,
+    /// The number of downloads, or null if there were too few downloads to report
+    pub downloads: Option<u64>
+}
+
+/// Aggregated, non-identifying counts of downloads, which can be shared as described in the privacy policy
+#[derive(Debug, PartialEq, Serialize, JsonSchema)]
+pub struct UsageStatistics {
+    /// Counts below this are suppressed
+    pub min_reported_count: u64,
+    pub granularity: Granularity,
+    pub counts: Vec<UsageCount>
+}
+
+/// The number of downloads on each day of a week
+type DailyDownloads = BTreeMap<NaiveDate, u64>;
+
+/// Counts the downloads in the audit log for each week (or day), type of sheet, optotype set and mode. The client
+/// is never used, and counts below the minimum are suppressed.
+///
+/// Suppressed counts could be worked out by subtracting one published count from another, so only weeks which have
+/// finished and haven't started to be purged are counted (so a count never changes between two downloads of the
+/// statistics, which would give away the downloads in between). The first day is the first day which no entries
+/// have been purged from, and the current week is the one containing today. A week's count less the counts of its
+/// days would give away a small day, so the daily counts for a week (of one type of sheet) are only published if
+/// none of them are suppressed, and are all suppressed otherwise.
+pub fn aggregate(entries: &[AuditEntry], first_day: NaiveDate, today: NaiveDate, min_reported_count: u64, granularity: Granularity) -> UsageStatistics {
+    let mut totals: BTreeMap<(NaiveDate, String, String, Option<String>), DailyDownloads> = BTreeMap::new();
+    let current_week = week_start(today);
+    for entry in entries {
+        let day = entry.timestamp.date();
+        let week = week_start(day);
+        if week < first_day || week >= current_week {
+            continue;
+        }
+        // Sheets are recorded as "sheet/optotype set/mode", where the mode is only recorded for charts
+        let mut parts = entry.sheet.splitn(3, '/');
+        let sheet = parts.next().unwrap_or("").to_string();
+        let optotype_set = parts.next().unwrap_or("").to_string();
+        let mode = parts.next().map(String::from);
+        *totals.entry((week, sheet, optotype_set, mode)).or_default().entry(day).or_insert(0) += 1;
+    }
+    let mut counts = Vec::new();
+    for ((week_start, sheet, optotype_set, mode), days) in totals {
+        let count = |day: Option<NaiveDate>, downloads: Option<u64>| UsageCount {
+            week_start,
+            day,
+            sheet: sheet.clone(),
+            optotype_set: optotype_set.clone(),
+            mode: mode.clone(),
+            downloads
+        };
+        match granularity {
+            Granularity::Week => {
+                let downloads: u64 = days.values().sum();
+                counts.push(count(None, Some(downloads).filter(|&downloads| downloads >= min_reported_count)));
+            },
+            Granularity::Day => {
+                let publish = days.values().all(|&downloads| downloads >= min_reported_count);
+                counts.extend(days.into_iter().map(|(day, downloads)| count(Some(day), Some(downloads).filter(|_| publish))));
+            }
+        }
+    }
+    UsageStatistics { min_reported_count, granularity, counts }
+}
+
+/// Formats the statistics as CSV, with suppressed counts written as "<5" (for the minimum reported count). Daily
+/// statistics have a column for the day after the week.
+pub fn to_csv(statistics: &UsageStatistics) -> String {
+    let mut csv = match statistics.granularity {
+        Granularity::Week => String::from("week_start,sheet,optotype_set,mode,downloads\n"),
+        Granularity::Day => String::from("week_start,day,sheet,optotype_set,mode,downloads\n")
+    };
+    for count in &statistics.counts {
+        let downloads = match count.downloads {
+            Some(downloads) => downloads.to_string(),
+            None => format!("<{}", statistics.min_reported_count)
+        };
+        let day = match count.day {
+            Some(day) => format!("{},", day),
+            None => String::new()
+        };
+        csv.push_str(&format!("{},{}{},{},{},{}\n", count.week_start, day, count.sheet, count.optotype_set,
+            count.mode.as_deref().unwrap_or(""), downloads));
+    }
+    csv
+}
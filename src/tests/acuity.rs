@@ -0,0 +1,61 @@
+use std::str::FromStr;
+use crate::acuity::Acuity;
+
+/// Check the formatting of Snellen fractions uses the conventional rounded denominators
+#[test]
+fn check_snellen() {
+    assert_eq!(Acuity::from_log_mar(0.1).snellen_metric(), "6/7.5");
+    assert_eq!(Acuity::from_log_mar(1.3).snellen_metric(), "6/120");
+    assert_eq!(Acuity::from_log_mar(0.0).snellen_imperial(), "20/20");
+    assert_eq!(Acuity::from_log_mar(1.3).snellen_imperial(), "20/400");
+    assert_eq!(Acuity::from_log_mar(1.2).snellen_metric(), "6/96");
+    assert_eq!(Acuity::from_log_mar(-0.1).snellen_metric(), "6/4.8");
+    assert_eq!(Acuity::from_log_mar(-0.2).snellen_metric(), "6/3.8");
+    assert_eq!(Acuity::from_log_mar(0.83).snellen_metric(), "6/41");
+}
+
+/// Check the conversions to the other units
+#[test]
+fn check_conversions() {
+    let acuity = Acuity::from_log_mar(0.3);
+    assert_eq!(acuity.decimal(), 0.5);
+    assert_eq!(acuity.visual_acuity_rating(), 85.0);
+    assert_eq!(acuity.etdrs_letters(), 70);
+    assert_eq!(Acuity::from_log_mar(2.0).etdrs_letters(), 0);
+    assert_eq!(Acuity::from_snellen(6.0, 12.0).unwrap(), acuity);
+    assert_eq!(Acuity::from_snellen(20.0, 40.0).unwrap(), acuity);
+    assert_eq!(Acuity::from_decimal(0.5).unwrap(), acuity);
+    assert_eq!(Acuity::from_visual_acuity_rating(85.0), acuity);
+    assert_eq!(Acuity::from_etdrs_letters(70), acuity);
+    assert!(Acuity::from_snellen(6.0, 0.0).is_err());
+    assert!(Acuity::from_decimal(-1.0).is_err());
+}
+
+/// Acuities are displayed in logMAR, without a sign on zero
+#[test]
+fn check_display() {
+    assert_eq!(Acuity::from_log_mar(0.0).to_string(), "0.0 logMAR");
+    assert_eq!(Acuity::from_log_mar(-0.001).to_string(), "0.0 logMAR");
+    assert_eq!(Acuity::from_log_mar(0.83).to_string(), "0.83 logMAR");
+}
+
+/// Check that acuities written in each unit can be parsed
+#[test]
+fn check_parse() {
+    let acuity = Acuity::from_log_mar(0.3);
+    for text in ["0.3", "0.3 logMAR", "6/12", " 20/40 ", "0.5 decimal", "85 VAR", "70 letters"].iter() {
+        assert_eq!(Acuity::from_str(text).unwrap(), acuity, "{}", text);
+    }
+    for text in ["", "6/", "six/twelve", "0.5 furlongs", "70.5 letters"].iter() {
+        assert!(Acuity::from_str(text).is_err(), "{}", text);
+    }
+}
+
+/// Acuities are serialised with every unit, and deserialised from the logMAR value
+#[test]
+fn check_serialise() {
+    let json = serde_json::to_string(&Acuity::from_log_mar(0.3)).unwrap();
+    assert_eq!(json, r#"{"log_mar":0.3,"snellen_metric":"6/12","snellen_imperial":"20/40","decimal":0.5,"visual_acuity_rating":85.0,"etdrs_letters":70}"#);
+    let acuity: Acuity = serde_json::from_str(r#"{"log_mar":0.3}"#).unwrap();
+    assert_eq!(acuity, Acuity::from_log_mar(0.3));
+}
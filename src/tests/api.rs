@@ -0,0 +1,179 @@
+use rocket::config::Value as RocketValue;
+use rocket::local::Client;
+use rocket::http::{Status, ContentType, Accept, Header, MediaType, QMediaType};
+use serde_json::Value;
+
+use crate::audit::{AuditLog, IpPrivacy, SqliteStore};
+
+/// Make a request to the API, returning the status and the parsed JSON body
+fn get_json(url: &str) -> (Status, Value) {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let mut response = client.get(url).dispatch();
+    let body = response.body_string().unwrap();
+    (response.status(), serde_json::from_str(&body).unwrap())
+}
+
+/// Post JSON to the API, returning the status and the parsed JSON body
+fn post_json(url: &str, body: &str) -> (Status, Value) {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let mut response = client.post(url).header(ContentType::JSON).body(body).dispatch();
+    let body = response.body_string().unwrap();
+    (response.status(), serde_json::from_str(&body).unwrap())
+}
+
+/// Decoding a short code gives the same arrangement as the answer sheet
+#[test]
+fn decode_short_code() {
+    let (status, json) = get_json("/api/v1/codes/RFD-CAM");
+    assert_eq!(status, Status::Ok);
+    assert_eq!(json["kind"], "Short");
+    assert_eq!(json["version"], 0);
+    assert_eq!(json["optotype_set"], "sloan");
+    assert_eq!(json["row_acuity"][0]["log_mar"], 1.3);
+    assert_eq!(json["arrangement"]["rows"][0]["optotypes"], serde_json::json!([7, 8]));
+}
+
+#[test]
+fn decode_long_code() {
+    let (status, json) = get_json("/api/v1/codes/FFT7-CVBJ-8ZV8-ALWE");
+    assert_eq!(status, Status::Ok);
+    assert_eq!(json["kind"], "Long");
+    assert_eq!(json["optotype_set"], "aukland");
+}
+
+/// Unreadable codes are bad requests, and readable but invalid codes are unprocessable
+#[test]
+fn decode_invalid_codes() {
+    let (status, json) = get_json("/api/v1/codes/RFD");
+    assert_eq!(status, Status::BadRequest);
+    assert_eq!(json["error"], "InvalidLength");
+    let (status, json) = get_json("/api/v1/codes/RFD-CAA");
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert_eq!(json["error"], "CrcMismatch");
+    assert!(json["message"].is_string());
+}
+
+/// Long codes which repeat an optotype on a row are rejected, unless they are decoded in lenient mode, which
+/// gives a warning instead
+#[test]
+fn decode_lenient_long_code() {
+    let mut longcode = crate::tests::get_test_longcode();
+    longcode.optotypes[17] = longcode.optotypes[16];
+    let code = longcode.to_string();
+    let (status, json) = get_json(&format!("/api/v1/codes/{}", code));
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert_eq!(json["error"], "RepeatedOptotype");
+    let (status, json) = get_json(&format!("/api/v1/codes/{}?lenient=true", code));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(json["arrangement"]["warnings"], serde_json::json!(["RepeatedOptotypes"]));
+}
+
+/// Issued codes are of the right kind for the mode, and decode to the arrangement which was returned
+#[test]
+fn issue_codes() {
+    let (status, json) = post_json("/api/v1/codes", r#"{"mode": "telephone", "optotypes": "sloan"}"#);
+    assert_eq!(status, Status::Created);
+    assert_eq!(json["kind"], "Short");
+    let code = json["code"].as_str().unwrap();
+    assert_eq!(json["links"]["answers"], format!("/answers?code={}", code));
+    let (_, decoded) = get_json(&format!("/api/v1/codes/{}", code));
+    assert_eq!(decoded["arrangement"], json["arrangement"]);
+
+    let (status, json) = post_json("/api/v1/codes", r#"{"mode": "self", "optotypes": "aukland"}"#);
+    assert_eq!(status, Status::Created);
+    assert_eq!(json["kind"], "Long");
+    assert_eq!(json["arrangement"]["optotype_definition"]["name"], "aukland");
+}
+
+/// Unknown optotype sets are reported with a structured error
+#[test]
+fn issue_code_unknown_optotypes() {
+    let (status, json) = post_json("/api/v1/codes", r#"{"mode": "self", "optotypes": "tumbling-e"}"#);
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert_eq!(json["error"], "UnknownOptotypeSet");
+}
+
+/// The answer sheet link returns JSON or a PDF when a script asks for one, and HTML otherwise
+#[test]
+fn negotiate_answer_format() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let response = client.get("/answers?code=RFD-CAM").dispatch();
+    assert_eq!(response.content_type(), Some(ContentType::HTML));
+    let response = client.get("/answers?code=RFD-CAM").header(Accept::JSON).dispatch();
+    assert_eq!(response.content_type(), Some(ContentType::JSON));
+    let mut response = client.get("/answers?code=RFD-CAM").header(Accept::new(vec![QMediaType(MediaType::PDF, None)])).dispatch();
+    assert_eq!(response.content_type(), Some(ContentType::PDF));
+    assert!(response.body_bytes().unwrap().starts_with(b"%PDF"));
+
+    // The format parameter takes priority over the Accept header
+    let (status, json) = get_json("/answers?code=RFD-CAM&format=json");
+    assert_eq!(status, Status::Ok);
+    assert_eq!(json["optotype_set"], "sloan");
+    let response = client.get("/answers?code=RFD-CAM&format=html").header(Accept::JSON).dispatch();
+    assert_eq!(response.content_type(), Some(ContentType::HTML));
+    assert_eq!(client.get("/answers?code=RFD-CAM&format=docx").dispatch().status(), Status::BadRequest);
+}
+
+/// Invalid codes give the same structured errors as the codes API when JSON is asked for
+#[test]
+fn negotiate_answer_format_errors() {
+    let (status, json) = get_json("/answers?code=RFD-CAA&format=json");
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert_eq!(json["error"], "CrcMismatch");
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    assert_eq!(client.get("/answers?code=RFD&format=pdf").dispatch().status(), Status::BadRequest);
+    assert_eq!(client.get("/answers?code=RFD-CAA&format=pdf").dispatch().status(), Status::UnprocessableEntity);
+}
+
+/// Responses to the answer sheet link depend on the Accept header, so caches must keep them apart
+#[test]
+fn negotiate_answer_format_vary() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    for accept in [Accept::HTML, Accept::JSON, Accept::new(vec![QMediaType(MediaType::PDF, None)])].iter() {
+        let response = client.get("/answers?code=RFD-CAM").header(accept.clone()).dispatch();
+        assert_eq!(response.headers().get_one("Vary"), Some("Accept"));
+    }
+    let response = client.get("/answers?code=RFD-CAA").header(Accept::JSON).dispatch();
+    assert_eq!(response.headers().get_one("Vary"), Some("Accept"));
+}
+
+/// Usage statistics are available as JSON or CSV, without identifying anyone, to clients with the statistics token
+#[test]
+fn usage_statistics() {
+    let settings = [("statistics_token", RocketValue::from("s3cret"))];
+    let log = AuditLog::new(Box::new(SqliteStore::in_memory().unwrap()), IpPrivacy::Hash, "salt".to_string(), 90);
+    let client = Client::new(crate::tests::rocket_with(&settings).manage(log)).unwrap();
+    let authorised = Header::new("Authorization", "Bearer s3cret");
+    let mut response = client.get("/api/v1/statistics").header(authorised.clone()).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let json: Value = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert!(json["counts"].is_array());
+    let mut response = client.get("/api/v1/statistics?format=csv").header(authorised.clone()).dispatch();
+    assert_eq!(response.content_type(), Some(ContentType::CSV));
+    assert!(response.body_string().unwrap().starts_with("week_start,sheet,optotype_set,mode,downloads"));
+    assert_eq!(client.get("/api/v1/statistics?format=xml").header(authorised.clone()).dispatch().status(), Status::BadRequest);
+    let mut response = client.get("/api/v1/statistics?granularity=day&format=csv").header(authorised.clone()).dispatch();
+    assert!(response.body_string().unwrap().starts_with("week_start,day,sheet,optotype_set,mode,downloads"));
+    assert_eq!(client.get("/api/v1/statistics?granularity=month").header(authorised).dispatch().status(), Status::BadRequest);
+
+    // Without the token (or with the wrong one) the statistics are refused
+    let mut response = client.get("/api/v1/statistics").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+    let json: Value = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(json["error"], "Unauthorized");
+    assert_eq!(client.get("/api/v1/statistics").header(Header::new("Authorization", "Bearer guess")).dispatch().status(), Status::Unauthorized);
+    // If no token is configured, nobody gets the statistics
+    let (status, _) = get_json("/api/v1/statistics");
+    assert_eq!(status, Status::Unauthorized);
+}
+
+/// Scoring errors have the same body as every other API error
+#[test]
+fn score_errors() {
+    let (status, error) = get_json("/api/v1/score?code=RFD-CAM&distance=150&correct=30");
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert_eq!(error["error"], "InvalidScore");
+    let (status, error) = get_json("/api/v1/score?code=RFD&distance=150&correct=3");
+    assert_eq!(status, Status::BadRequest);
+    assert_eq!(error["error"], "InvalidLength");
+}
@@ -0,0 +1,116 @@
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use chrono::{Duration, Utc};
+use rocket::config::Value;
+use rocket::http::Header;
+use rocket::local::Client;
+
+use crate::audit::{AuditEntry, AuditLog, AuditStore, FileStore, IpPrivacy, SqliteStore, describe_sheet, truncate_ip};
+use crate::optotypes::OptotypeArrangement;
+
+fn ip(text: &str) -> Option<IpAddr> {
+    Some(IpAddr::from_str(text).unwrap())
+}
+
+fn get_test_log(ip_privacy: IpPrivacy) -> AuditLog {
+    AuditLog::new(Box::new(SqliteStore::in_memory().unwrap()), ip_privacy, "salt".to_string(), 90)
+}
+
+/// IP addresses are stored in full, truncated to the network, or hashed
+#[test]
+fn anonymise_ip_addresses() {
+    assert_eq!(get_test_log(IpPrivacy::Full).anonymise(ip("192.0.2.45")), "192.0.2.45");
+    assert_eq!(get_test_log(IpPrivacy::Truncate).anonymise(ip("192.0.2.45")), "192.0.2.0");
+    assert_eq!(truncate_ip(ip("2001:db8:85a3:8d3:1319:8a2e:370:7348").unwrap()), ip("2001:db8:85a3::").unwrap());
+
+    let log = get_test_log(IpPrivacy::Hash);
+    let hash = log.anonymise(ip("192.0.2.45"));
+    assert_eq!(hash.len(), 16);
+    assert!(!hash.contains("192"));
+    assert_eq!(log.anonymise(ip("192.0.2.45")), hash);
+    assert_ne!(log.anonymise(ip("192.0.2.46")), hash);
+    // The hash depends on the salt, so it can't be reversed by hashing every address
+    let other_log = AuditLog::new(Box::new(SqliteStore::in_memory().unwrap()), IpPrivacy::Hash, "pepper".to_string(), 90);
+    assert_ne!(other_log.anonymise(ip("192.0.2.45")), hash);
+
+    assert_eq!(log.anonymise(None), "unknown");
+    assert!(IpPrivacy::from_str("encrypt").is_err());
+}
+
+/// Both stores keep entries in order, and purge the ones from before a given time
+fn check_store(store: &dyn AuditStore) {
+    let now = Utc::now().naive_utc().date().and_hms_opt(12, 0, 0).unwrap();
+    let entry = |days_ago: i64, sheet: &str| AuditEntry { timestamp: now - Duration::days(days_ago), client: "unknown".to_string(), sheet: sheet.to_string() };
+    store.record(&entry(1, "chart/sloan")).unwrap();
+    store.record(&entry(100, "chart/aukland")).unwrap();
+    store.record(&entry(10, "naming-sheet/sloan")).unwrap();
+    assert_eq!(store.entries().unwrap(), vec![entry(100, "chart/aukland"), entry(10, "naming-sheet/sloan"), entry(1, "chart/sloan")]);
+    assert_eq!(store.purge(now - Duration::days(90)).unwrap(), 1);
+    assert_eq!(store.entries().unwrap(), vec![entry(10, "naming-sheet/sloan"), entry(1, "chart/sloan")]);
+    assert_eq!(store.purge(now - Duration::days(90)).unwrap(), 0);
+}
+
+#[test]
+fn sqlite_store() {
+    check_store(&SqliteStore::in_memory().unwrap());
+}
+
+#[test]
+fn file_store() {
+    let path = std::env::temp_dir().join(format!("hat-audit-test-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    check_store(&FileStore::open(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Recording a download stores the anonymised address, the sheet and the time
+#[test]
+fn record_download() {
+    let log = get_test_log(IpPrivacy::Truncate);
+    log.record(ip("192.0.2.45"), "chart/sloan").unwrap();
+    let entries = log.entries().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].client, "192.0.2.0");
+    assert_eq!(entries[0].sheet, "chart/sloan");
+    assert!(Utc::now().naive_utc() - entries[0].timestamp < Duration::minutes(1));
+}
+
+/// Sheets made from codes are described with their optotype set and the mode of test the code is for
+#[test]
+fn describe_sheets() {
+    assert_eq!(describe_sheet("chart", &OptotypeArrangement::from_code("RFD-CAM").unwrap()), "chart/sloan/telephone");
+    assert_eq!(describe_sheet("answer-key", &OptotypeArrangement::from_code("FFT7-CVBJ-8ZV8-ALWE").unwrap()), "answer-key/aukland/self");
+}
+
+/// A server which records downloads with the audit_* settings, in an SQLite database which is kept in memory rather
+/// than the audit log in Rocket.toml
+fn test_client(settings: &[(&str, Value)]) -> Client {
+    let mut settings = settings.to_vec();
+    settings.extend(vec![("audit_log", Value::from("sqlite")), ("audit_path", Value::from(":memory:")), ("audit_ip", Value::from("full"))]);
+    Client::new(crate::tests::rocket_with(&settings)).unwrap()
+}
+
+/// Downloading a chart adds it to the audit log of the server
+#[test]
+fn generate_route_records_download() {
+    let client = test_client(&[]);
+    let started = Utc::now().naive_utc() - Duration::seconds(1);
+    client.get("/naming-sheet/aukland").dispatch();
+    let log = client.rocket().state::<AuditLog>().unwrap();
+    assert!(log.entries().unwrap().iter().any(|entry| entry.sheet == "naming-sheet/aukland" && entry.timestamp >= started));
+}
+
+/// The X-Real-IP header is only believed when the request comes from a trusted proxy, so clients can't hide their
+/// address by setting it
+#[test]
+fn real_ip_only_from_trusted_proxies() {
+    let proxy = SocketAddr::from_str("192.0.2.1:8000").unwrap();
+    let client_address = |client: &Client| {
+        client.get("/naming-sheet/aukland").remote(proxy).header(Header::new("X-Real-IP", "198.51.100.7")).dispatch();
+        let log = client.rocket().state::<AuditLog>().unwrap();
+        log.entries().unwrap().last().unwrap().client.clone()
+    };
+    assert_eq!(client_address(&test_client(&[])), "192.0.2.1");
+    assert_eq!(client_address(&test_client(&[("trusted_proxies", Value::Array(vec![Value::from("192.0.2.1")]))])), "198.51.100.7");
+    assert_eq!(client_address(&test_client(&[("trusted_proxies", Value::Array(vec![Value::from("192.0.2.2")]))])), "192.0.2.1");
+}
@@ -0,0 +1,10 @@
+use crate::auth::tokens_match;
+
+/// Tokens only match when they are the same, whatever their lengths
+#[test]
+fn compare_tokens() {
+    assert!(tokens_match("correct horse", "correct horse"));
+    assert!(!tokens_match("correct horse", "correct house"));
+    assert!(!tokens_match("correct", "correct horse"));
+    assert!(!tokens_match("", "correct horse"));
+}
@@ -0,0 +1,63 @@
+use rocket::config::Value as RocketValue;
+use rocket::local::Client;
+use rocket::http::{Accept, ContentType, Header, Status};
+use serde_json::Value;
+
+/// Browsers are shown an error page with the request id, which is also in the response headers
+#[test]
+fn html_error_page() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let mut response = client.get("/no-such-page").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.content_type(), Some(ContentType::HTML));
+    let request_id = response.headers().get_one("X-Request-Id").unwrap().to_string();
+    assert_eq!(request_id.len(), 16);
+    let body = response.body_string().unwrap();
+    assert!(body.contains("Page not found"));
+    assert!(body.contains(&request_id));
+}
+
+/// API clients get a structured error instead, using the request id from the reverse proxy if there is one
+#[test]
+fn json_errors() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let mut response = client.get("/api/v1/no-such-route").header(Header::new("X-Request-Id", "proxy-1234")).dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.headers().get_one("X-Request-Id"), Some("proxy-1234"));
+    let json: Value = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(json["error"], "NotFound");
+    assert_eq!(json["request_id"], "proxy-1234");
+
+    // Invalid JSON bodies are unprocessable
+    let mut response = client.post("/api/v1/codes").header(ContentType::JSON).body(r#"{"mode": "carrier pigeon"}"#).dispatch();
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    let json: Value = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(json["error"], "UnprocessableEntity");
+
+    // Clients which prefer JSON get JSON errors outside of the API too, and unsafe request ids are replaced
+    let mut response = client.get("/generate?code=invalid").header(Accept::JSON).header(Header::new("X-Request-Id", "<script>")).dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+    let json: Value = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(json["error"], "BadRequest");
+    assert_ne!(json["request_id"], "<script>");
+}
+
+/// The download buttons on the homepage used to lead to Rocket's default 404 page. They now issue a chart, so they
+/// must never reach the catchers, whose pages are only for links which really are broken.
+#[test]
+fn download_buttons_do_not_reach_error_pages() {
+    let client = Client::new(crate::tests::rocket_with(&[("rate_limit_store", RocketValue::from("none"))])).unwrap();
+    let body = client.get("/").dispatch().body_string().unwrap();
+    let buttons: Vec<String> = body.split("href=\"").skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        .filter(|link| link.starts_with("/generate?type="))
+        .map(|link| link.replace("&amp;", "&"))
+        .collect();
+    assert_eq!(buttons.len(), 4);
+    for button in buttons {
+        let response = client.get(button.clone()).dispatch();
+        assert_eq!(response.status(), Status::SeeOther, "{} did not issue a chart", button);
+        assert!(response.headers().get_one("Location").unwrap().starts_with("/generate?code="));
+        assert_ne!(response.content_type(), Some(ContentType::HTML), "{} was answered by a catcher", button);
+    }
+}
@@ -1,6 +1,6 @@
-use crate::codes::{long::LongCode, NUM_OPTOTYPES_ON_ROW, NUM_ROWS};
+use crate::codes::{long::LongCode, NUM_OPTOTYPES_ON_ROW, CodeErrorKind};
 use crate::tests::get_test_longcode;
-use crate::optotypes::{OptotypeArrangement, OptotypeDefinition, OptotypeRow};
+use crate::optotypes::{OptotypeArrangement, OptotypeDefinition, OptotypeRow, ArrangementWarning};
 use ux::{u2, u4};
 use std::str::FromStr;
 
@@ -43,8 +43,51 @@ fn parse_invalid_crc() {
     }
 }
 
+/// Build a long code (with a valid CRC) which repeats an optotype on the top row
+fn get_repeated_optotype_code() -> String {
+    let mut longcode = get_test_longcode();
+    longcode.optotypes[17] = longcode.optotypes[16];
+    longcode.to_string()
+}
+
+/// A code with a repeated optotype on a row should be rejected, even though the CRC is valid
+#[test]
+fn parse_repeated_optotype() {
+    match LongCode::from_str(&get_repeated_optotype_code()) {
+        Ok(_) => { panic!("A long code with a repeated optotype on a row was successfully parsed."); },
+        Err(e) => { assert_eq!(e.kind, CodeErrorKind::RepeatedOptotype); }
+    }
+}
+
+/// In lenient mode, a repeated optotype is returned as a warning rather than an error, and flagged on the arrangement
+#[test]
+fn parse_repeated_optotype_lenient() {
+    let (longcode, warnings) = LongCode::from_str_lenient(&get_repeated_optotype_code()).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, CodeErrorKind::RepeatedOptotype);
+    let arrangement = OptotypeArrangement::from(longcode);
+    assert_eq!(arrangement.warnings, vec![ArrangementWarning::RepeatedOptotypes]);
+}
+
+/// Optotypes outside of the optotype set should be caught by validation
+#[test]
+fn validate_out_of_range_optotype() {
+    let mut longcode = get_test_longcode();
+    longcode.optotypes[0] = 12;
+    let errors = longcode.validate();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, CodeErrorKind::OptotypeOutOfRange);
+}
+
+/// The test code is valid, so shouldn't have any validation errors
+#[test]
+fn validate_valid_code() {
+    assert!(get_test_longcode().validate().is_empty());
+}
+
 /// Check the CRC calculation for an example long code with a known CRC
 #[test]
+#[allow(clippy::unnecessary_cast)]
 fn check_crc() {
     let parsed_longcode = get_test_longcode();
     assert_eq!(parsed_longcode.get_crc(), 10884 as u16);
@@ -103,7 +146,7 @@ fn check_optotype_arrangement_into_longcode() {
 pub fn test_randomised_longcode() {
     for code_id in 0..1 {
         for _ in 0..1000 {
-            let randomised_longcode = LongCode::generate_random(OptotypeDefinition::from(code_id));
+            let randomised_longcode = LongCode::generate_random(OptotypeDefinition::from(code_id)).unwrap();
             // Check the optotype ID was carried across correctly
             assert_eq!(randomised_longcode.optotype_definition.id, code_id as u8);
             // Check there were the correct number of optotypes generated
@@ -114,9 +157,9 @@ pub fn test_randomised_longcode() {
             assert_eq!(randomised_longcode.version, u2::new(0));
             // Check for duplicates
             let mut optotype_idx = generated_num_optotypes;
-            for row in 0..NUM_ROWS {
+            for &num_optotypes in NUM_OPTOTYPES_ON_ROW.iter() {
                 let mut optotypes_on_row: Vec<u8> =  Vec::new();
-                for _ in 0..NUM_OPTOTYPES_ON_ROW[row] {
+                for _ in 0..num_optotypes {
                     optotypes_on_row.push(randomised_longcode.optotypes[(optotype_idx - 1) as usize]);
                     optotype_idx -= 1;
                 }
@@ -134,6 +177,18 @@ pub fn test_randomised_longcode() {
     
 }
 
+/// Random long codes for every optotype set should parse back into the same code. The header must be shifted
+/// back down when parsing, or every code decodes as the first optotype set and fails its CRC.
+#[test]
+fn randomised_longcode_round_trip() {
+    for optotype_definition in OptotypeDefinition::all() {
+        for _ in 0..100 {
+            let longcode = LongCode::generate_random(OptotypeDefinition::from(u32::from(optotype_definition.id))).unwrap();
+            assert_eq!(LongCode::from_str(&longcode.to_string()).unwrap(), longcode);
+        }
+    }
+}
+
 /// Get the expected arrangement for default longcode (FFT7-CVBJ-8ZV8-ALWE)
 pub fn expected_longcode_arrangement() -> OptotypeArrangement {
     OptotypeArrangement {
@@ -165,6 +220,7 @@ pub fn expected_longcode_arrangement() -> OptotypeArrangement {
                 text_size: 1.0,
                 border_size: 0.2
             }
-        ]
+        ],
+        warnings: vec![]
     }
 }
@@ -32,7 +32,7 @@ fn check_base_char_mapping() {
 #[test]
 fn check_error_formatting() {
     let error_message = "This is a test".to_string();
-    let error = CodeError(error_message.clone());
+    let error = CodeError::new(CodeErrorKind::InvalidField, error_message.clone());
     if !format!("{}", error).contains(&error_message) {
         panic!("The printed error message did not contain the message that was specified when creating the error.");
     }
@@ -50,4 +50,22 @@ fn check_zero_padding() {
     // Check decoding is successful
     let decoded = get_number_from_code(encoded.iter().collect::<String>());
     assert_eq!(decoded, Some(num));
-}
\ No newline at end of file
+}
+
+/// The kind of code is worked out from its length, ignoring dashes
+#[test]
+fn check_code_kind() {
+    assert_eq!(CodeKind::from_code("RFD-CAM").unwrap(), CodeKind::Short);
+    assert_eq!(CodeKind::from_code("FFT7-CVBJ-8ZV8-ALWE").unwrap(), CodeKind::Long);
+    assert_eq!(CodeKind::from_code("RFDCA").unwrap_err().kind, CodeErrorKind::InvalidLength);
+}
+
+/// The names of the kinds of error are the same as their serialised names
+#[test]
+fn check_error_kind_names() {
+    let kinds = [CodeErrorKind::InvalidCharacter, CodeErrorKind::InvalidLength, CodeErrorKind::OutOfRange,
+        CodeErrorKind::InvalidField, CodeErrorKind::CrcMismatch, CodeErrorKind::RepeatedOptotype];
+    for kind in kinds.iter() {
+        assert_eq!(serde_json::to_value(kind).unwrap(), kind.as_str());
+    }
+}
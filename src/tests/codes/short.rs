@@ -1,7 +1,7 @@
 use crate::codes::{NUM_ROWS, short::ShortCode};
-use crate::optotypes::OptotypeDefinition;
+use crate::optotypes::{OptotypeDefinition, OptotypeArrangement, get_max_start_row};
 use crate::tests::get_test_shortcode;
-use ux::{u6,u30};
+use ux::{u1,u4,u6,u30};
 use std::str::FromStr;
 
 /// This test just makes sure the random shortcode is not panicking and that
@@ -17,6 +17,21 @@ fn random_short_code() {
     assert_eq!(new_shortcode.offsets.len(), NUM_ROWS - 1);
 }
 
+/// Randomly generated shortcodes should never copy a row, so every offset must be
+/// non-zero and different from the others.
+#[test]
+fn random_short_code_offsets() {
+    for _ in 0..100 {
+        let new_shortcode = ShortCode::generate_random(OptotypeDefinition::from(1));
+        let mut offsets: Vec<u8> = new_shortcode.offsets.iter().map(|offset| u8::from(*offset)).collect();
+        assert!(!offsets.contains(&0));
+        offsets.sort();
+        offsets.dedup();
+        assert_eq!(offsets.len(), NUM_ROWS - 1);
+        assert!(OptotypeArrangement::from(new_shortcode).warnings.is_empty());
+    }
+}
+
 /// Check the parsing of a particular shortcode with known stored data.
 #[test]
 fn parse_code() {
@@ -57,6 +72,13 @@ fn parse_bad_offsets_code() {
     }
 }
 
+/// The largest start rows don't select a valid bottom row, so the maximum must
+/// leave room for the scaled start row within the possible bottom rows (10P4 = 5040).
+#[test]
+fn check_max_start_row() {
+    assert_eq!(get_max_start_row(&OptotypeDefinition::from(1)), 251);
+}
+
 /// Attempt to parse a code with an invalid CRC.
 #[test]
 fn parse_invalid_crc() {
@@ -86,6 +108,40 @@ fn check_numerical_representation() {
     assert_eq!(actual_value, expected_value);
 }
 
+/// The data body is shifted into the top 24 bits of the code as a whole, rather than being shifted within
+/// its own 24 bits (which would drop the version, optotype and start row). Every field must come back out
+/// of the place the parser reads it from, so that codes which have already been issued still decode.
+#[test]
+fn check_numerical_representation_round_trip() {
+    for optotype_id in 0..2 {
+        let optotype_definition = OptotypeDefinition::from(optotype_id);
+        for start_row in 0..=get_max_start_row(&optotype_definition) {
+            for offsets in [[1, 5, 6, 8], [9, 7, 3, 2]].iter() {
+                let shortcode = ShortCode {
+                    version: u1::new(0),
+                    optotype_definition: OptotypeDefinition::from(optotype_id),
+                    start_row,
+                    offsets: [u4::new(offsets[0]), u4::new(offsets[1]), u4::new(offsets[2]), u4::new(offsets[3])]
+                };
+                let number = u32::from(shortcode.get_numerical_representation());
+                assert_eq!((number >> 28) & 1, optotype_id);
+                assert_eq!((number >> 20) & 255, u32::from(start_row));
+                assert_eq!(ShortCode::from_str(&shortcode.to_string()).unwrap(), shortcode);
+            }
+        }
+    }
+}
+
+/// The data body must be widened before it is shifted above the CRC. Shifting it within its own 24 bits drops the
+/// version, optotype and start row bits.
+#[test]
+fn numerical_representation_keeps_body() {
+    let shortcode = get_test_shortcode();
+    let number = u32::from(shortcode.get_numerical_representation());
+    assert_eq!(number >> 6, u32::from(shortcode.get_numerical_representation_without_crc()));
+    assert_eq!(number & 0b111111, u32::from(shortcode.get_crc()));
+}
+
 /// Make sure that serialisation in working for shortcodes - i.e. the
 /// encoded information can be converted back into a shortcode that
 /// encodes the information. This is required for displaying the short 
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use rocket::config::{Config, Environment, Value};
+use rocket::local::Client;
+use rocket::http::{ContentType, Status};
+
+use crate::audit::{AuditStoreKind, IpPrivacy};
+use crate::config::{AppConfig, StandardChart};
+use crate::pdf::PaperSize;
+
+fn no_env() -> HashMap<String, String> {
+    HashMap::new()
+}
+
+/// Without any settings, the defaults are used (which are the settings of homeacuitytest.org)
+#[test]
+fn default_settings() {
+    let config = Config::build(Environment::Development).finalize().unwrap();
+    let app_config = AppConfig::from_settings(&config, &no_env()).unwrap();
+    assert_eq!(app_config, AppConfig::default());
+    assert_eq!(app_config.default_distance, 150.0);
+    assert_eq!(app_config.paper_size, PaperSize::A4);
+}
+
+/// Settings are read from Rocket's configuration, and HAT_* environment variables take precedence over them
+#[test]
+fn read_settings() {
+    let config = Config::build(Environment::Development)
+        .extra("default_distance", 190)
+        .extra("common_distances", vec![150, 190])
+        .extra("paper_size", "letter")
+        .extra("feedback_form", "")
+        .finalize().unwrap();
+    let app_config = AppConfig::from_settings(&config, &no_env()).unwrap();
+    assert_eq!(app_config.default_distance, 190.0);
+    assert_eq!(app_config.common_distances, vec![150.0, 190.0]);
+    assert_eq!(app_config.paper_size, PaperSize::Letter);
+    assert_eq!(app_config.feedback_form, None);
+
+    let env: HashMap<String, String> = [("HAT_DEFAULT_DISTANCE", "100.5"), ("HAT_COMMON_DISTANCES", "100, 300"), ("HAT_DEFAULT_OPTOTYPES", "aukland")].iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    let app_config = AppConfig::from_settings(&config, &env).unwrap();
+    assert_eq!(app_config.default_distance, 100.5);
+    assert_eq!(app_config.common_distances, vec![100.0, 300.0]);
+    assert_eq!(app_config.default_optotypes, "aukland");
+    assert_eq!(app_config.paper_size, PaperSize::Letter);
+
+    let env: HashMap<String, String> = [("HAT_TRUSTED_PROXIES".to_string(), "127.0.0.1, ::1".to_string())].iter().cloned().collect();
+    let app_config = AppConfig::from_settings(&config, &env).unwrap();
+    assert_eq!(app_config.trusted_proxies, vec!["127.0.0.1".parse::<IpAddr>().unwrap(), "::1".parse::<IpAddr>().unwrap()]);
+}
+
+/// The audit log is configured like the other settings, and can be turned off
+#[test]
+fn audit_settings() {
+    let config = Config::build(Environment::Development)
+        .extra("audit_log", "file")
+        .extra("audit_ip", "truncate")
+        .extra("audit_retention_days", 30)
+        .finalize().unwrap();
+    let app_config = AppConfig::from_settings(&config, &no_env()).unwrap();
+    assert_eq!(app_config.audit_log, Some(AuditStoreKind::File));
+    assert_eq!(app_config.audit_ip, IpPrivacy::Truncate);
+    assert_eq!(app_config.audit_retention_days, 30);
+    assert_eq!(app_config.audit_ip_salt, None);
+
+    let env: HashMap<String, String> = [("HAT_AUDIT_LOG", "none"), ("HAT_AUDIT_IP", "hash"), ("HAT_AUDIT_IP_SALT", "pepper")].iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    let app_config = AppConfig::from_settings(&config, &env).unwrap();
+    assert_eq!(app_config.audit_log, None);
+    assert_eq!(app_config.audit_ip, IpPrivacy::Hash);
+    assert_eq!(app_config.audit_ip_salt.as_deref(), Some("pepper"));
+}
+
+/// Inconsistent settings are found when the server starts
+#[test]
+fn invalid_settings() {
+    let invalid = |name: &str, value: &str| {
+        let config = Config::build(Environment::Development).finalize().unwrap();
+        let env: HashMap<String, String> = [(format!("HAT_{}", name), value.to_string())].iter().cloned().collect();
+        AppConfig::from_settings(&config, &env).is_err()
+    };
+    assert!(invalid("DEFAULT_DISTANCE", "far"));
+    assert!(invalid("DEFAULT_DISTANCE", "1000"));
+    assert!(invalid("MIN_DISTANCE", "200"));
+    assert!(invalid("MAX_DISTANCE", "10"));
+    assert!(invalid("COMMON_DISTANCES", "150,700"));
+    assert!(invalid("DEFAULT_OPTOTYPES", "tumbling-e"));
+    assert!(invalid("PAPER_SIZE", "a3"));
+    assert!(invalid("FEEDBACK_FORM", "http://example.com"));
+    assert!(invalid("TRUSTED_PROXIES", "127.0.0.1,proxy.example.com"));
+    assert!(invalid("AUDIT_LOG", "postgres"));
+    assert!(invalid("AUDIT_IP", "partial"));
+    assert!(invalid("AUDIT_RETENTION_DAYS", "0"));
+    assert!(invalid("AUDIT_RETENTION_DAYS", "1.5"));
+    assert!(!invalid("MAX_DISTANCE", "300"));
+
+    let config = Config::build(Environment::Development).extra("default_distance", "near").finalize().unwrap();
+    assert!(AppConfig::from_settings(&config, &no_env()).is_err());
+}
+
+/// The default distance is used in the text of the pages, rather than the distance of the standard charts
+#[test]
+fn default_distance_in_pages() {
+    let client = Client::new(crate::tests::rocket_with(&[("default_distance", Value::Integer(300))])).unwrap();
+    let body = client.get("/").dispatch().body_string().unwrap();
+    assert!(body.contains("Stand 300 cm away from the chart."));
+    assert!(body.contains("administer the test from 300 cm"));
+    assert!(!body.contains("150 cm"));
+    let body = client.get("/score?lang=pl").dispatch().body_string().unwrap();
+    assert!(body.contains("Tablice są drukowane dla 300 cm"));
+}
+
+/// Charts can only be downloaded for the configured range of distances
+#[test]
+fn check_distances() {
+    let app_config = AppConfig { min_distance: 100.0, max_distance: 300.0, ..AppConfig::default() };
+    assert!(app_config.check_distance(150.0).is_ok());
+    assert!(app_config.check_distance(300.0).is_ok());
+    assert!(app_config.check_distance(50.0).is_err());
+    assert!(app_config.check_distance(400.0).is_err());
+}
+
+/// The settings are used by the pages, and the API uses the default optotype set when none is given
+#[test]
+fn settings_used_by_routes() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let mut response = client.get("/").dispatch();
+    let body = response.body_string().unwrap();
+    assert!(body.contains("/standard/letters"));
+    assert!(body.contains("formspree.io"));
+    assert!(body.contains("<option value=\"190.0\"></option>"));
+
+    let mut response = client.post("/api/v1/codes").header(ContentType::JSON).body(r#"{"mode": "telephone"}"#).dispatch();
+    assert_eq!(response.status(), Status::Created);
+    let json: serde_json::Value = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(json["arrangement"]["optotype_definition"]["name"], "sloan");
+}
+
+fn standard_chart(name: &str, code: &str, optotypes: &str) -> StandardChart {
+    StandardChart { name: name.to_string(), code: code.to_string(), optotypes: optotypes.to_string(), description: "Test chart".to_string(), label_key: "standard-chart-letters".to_string() }
+}
+
+/// The registry of standardised charts is read from a list of tables, and each code must decode to its optotype set
+#[test]
+fn standard_charts() {
+    let mut table = rocket::config::Table::new();
+    for (field, value) in [("name", "long"), ("code", "FFT7-CVBJ-8ZV8-ALWE"), ("optotypes", "aukland"), ("description", "Symbols (long code)"), ("label_key", "standard-chart-symbols")].iter() {
+        table.insert(field.to_string(), rocket::config::Value::String(value.to_string()));
+    }
+    let config = Config::build(Environment::Development).extra("standard_charts", vec![rocket::config::Value::Table(table)]).finalize().unwrap();
+    let app_config = AppConfig::from_settings(&config, &no_env()).unwrap();
+    assert_eq!(app_config.standard_charts.len(), 1);
+    assert_eq!(app_config.standard_chart("long").map(|chart| chart.code.as_str()), Some("FFT7-CVBJ-8ZV8-ALWE"));
+    assert_eq!(app_config.standard_chart("letters"), None);
+
+    let config = Config::build(Environment::Development).extra("standard_charts", vec!["RFDCAM"]).finalize().unwrap();
+    assert!(AppConfig::from_settings(&config, &no_env()).is_err());
+
+    let with_charts = |charts: Vec<StandardChart>| AppConfig { standard_charts: charts, ..AppConfig::default() }.validate();
+    assert!(with_charts(vec![standard_chart("letters", "RFDCAM", "sloan")]).is_ok());
+    // The code must decode, to the declared optotype set
+    assert!(with_charts(vec![standard_chart("letters", "RFDCAN", "sloan")]).is_err());
+    assert!(with_charts(vec![standard_chart("letters", "RFDCAM", "aukland")]).is_err());
+    // Names must be unique, and usable in an address
+    assert!(with_charts(vec![standard_chart("letters", "RFDCAM", "sloan"), standard_chart("letters", "EBC3JF", "aukland")]).is_err());
+    assert!(with_charts(vec![standard_chart("Letters/1", "RFDCAM", "sloan")]).is_err());
+    // The label must be a message in the catalogue
+    let unlabelled = StandardChart { label_key: "standard-chart-missing".to_string(), ..standard_chart("letters", "RFDCAM", "sloan") };
+    assert!(with_charts(vec![unlabelled]).is_err());
+}
+
+/// Standardised charts are served by name, and listed by the API
+#[test]
+fn serve_standard_charts() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let response = client.get("/standard/letters").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.content_type(), Some(ContentType::PDF));
+    assert_eq!(client.get("/standard/letters?distance=1000").dispatch().status(), Status::BadRequest);
+    assert_eq!(client.get("/standard/no-such-chart").dispatch().status(), Status::NotFound);
+
+    let mut response = client.get("/api/v1/standard-charts").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let json: serde_json::Value = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(json[0]["name"], "letters");
+    assert_eq!(json[0]["code"], "RFDCAM");
+    assert_eq!(json[1]["optotypes"], "aukland");
+    assert_eq!(json[1]["links"]["pdf"], "/standard/symbols");
+    assert_eq!(json[1]["links"]["answers"], "/answers?code=EBC3JF");
+}
+
+/// The server refuses to start with inconsistent settings, rather than failing when a chart is downloaded
+#[test]
+fn invalid_settings_stop_launch() {
+    let rocket = crate::tests::rocket_with(&[("default_distance", rocket::config::Value::from(1000))]);
+    match Client::new(rocket) {
+        Ok(_) => panic!("The server started with a default distance outside the allowed range"),
+        Err(e) => match e.kind() {
+            rocket::error::LaunchErrorKind::FailedFairings(fairings) => assert_eq!(fairings, &vec!["Application configuration"]),
+            _ => panic!("The server failed to start for the wrong reason")
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::codes::long::LongCode;
+use crate::constraints::{check_rows, ConstraintViolation, GenerationConstraints};
+use crate::optotypes::{ArrangementWarning, OptotypeArrangement, OptotypeDefinition, OptotypeRow};
+
+/// Checks every row of an arrangement against the generation constraints for its optotype set
+fn check_arrangement(arrangement: &OptotypeArrangement) -> Vec<ConstraintViolation> {
+    let rows: Vec<&[u8]> = arrangement.rows.iter().map(|row| row.optotypes.as_slice()).collect();
+    check_rows(&arrangement.optotype_definition, &rows)
+}
+
+/// Build an arrangement of Sloan letters from a list of rows of optotype indices
+fn sloan_arrangement(rows: Vec<Vec<u8>>) -> OptotypeArrangement {
+    OptotypeArrangement {
+        rows: rows.into_iter().map(|optotypes| OptotypeRow { text_size: 1.0, border_size: 0.2, optotypes }).collect(),
+        code: "".to_string(),
+        optotype_definition: OptotypeDefinition::from(1),
+        warnings: vec![]
+    }
+}
+
+/// Randomly generated long codes should always satisfy the constraints for their optotypes
+#[test]
+fn generated_codes_satisfy_constraints() {
+    for _ in 0..50 {
+        let arrangement = OptotypeArrangement::from(LongCode::generate_random(OptotypeDefinition::from(1)).unwrap());
+        assert_eq!(check_arrangement(&arrangement), vec![]);
+        assert!(arrangement.warnings.is_empty());
+    }
+}
+
+/// Long codes which break the constraints (such as codes issued before they were introduced) are still decoded,
+/// but with a warning
+#[test]
+fn decoded_codes_warn_about_constraints() {
+    let mut long_code = LongCode::generate_random(OptotypeDefinition::from(1)).unwrap();
+    // The top row is at the end of the list of optotypes: put O and C next to each other on it
+    long_code.optotypes[16] = 5;
+    long_code.optotypes[17] = 0;
+    let arrangement = OptotypeArrangement::from_code(&long_code.to_string()).unwrap();
+    assert_eq!(arrangement.warnings, vec![ArrangementWarning::BreaksConstraints]);
+}
+
+/// Sloan C and O next to each other should be reported, in either order
+#[test]
+fn check_forbidden_pair() {
+    // C = 0, O = 5, R = 6, H = 2, K = 3
+    let arrangement = sloan_arrangement(vec![vec![6, 2], vec![2, 0, 5, 3]]);
+    assert_eq!(check_arrangement(&arrangement), vec![ConstraintViolation::ForbiddenAdjacentPair { row: 1, position: 1 }]);
+    let arrangement = sloan_arrangement(vec![vec![5, 0]]);
+    assert!(check_arrangement(&arrangement).contains(&ConstraintViolation::ForbiddenAdjacentPair { row: 0, position: 0 }));
+}
+
+/// A row made up of only the hardest letters should be reported as unbalanced
+#[test]
+fn check_unbalanced_row() {
+    // C and S are not confusable, but both are harder than average
+    let arrangement = sloan_arrangement(vec![vec![0, 7]]);
+    match check_arrangement(&arrangement).as_slice() {
+        [ConstraintViolation::UnbalancedRow { row: 0, .. }] => {},
+        violations => { panic!("Expected a single unbalanced row, but found {:?}", violations); }
+    }
+}
+
+/// Repeating a row should be reported
+#[test]
+fn check_identical_rows() {
+    let arrangement = sloan_arrangement(vec![vec![6, 2], vec![2, 3, 4, 6], vec![2, 3, 4, 6]]);
+    assert_eq!(check_arrangement(&arrangement), vec![ConstraintViolation::IdenticalRows { first_row: 1, second_row: 2 }]);
+}
+
+/// Constraints which can't be satisfied should give an error, rather than a chart which breaks them
+#[test]
+fn unsatisfiable_constraints() {
+    let mut optotype_definition = OptotypeDefinition::from(1);
+    // Every row would need a mean difficulty exactly 1 below the mean of the set
+    optotype_definition.constraints = GenerationConstraints {
+        max_row_difficulty_deviation: -1.0,
+        ..GenerationConstraints::unconstrained(10)
+    };
+    match LongCode::generate_random(optotype_definition) {
+        Ok(long_code) => { panic!("A long code was generated from constraints which can't be satisfied: {}", long_code.to_string()); },
+        Err(e) => { assert_eq!(format!("{}", e), "Error: No arrangement of the sloan optotypes satisfied the generation constraints for row 1 after 10000 attempts."); }
+    }
+}
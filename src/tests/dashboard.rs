@@ -0,0 +1,119 @@
+use std::str::FromStr;
+use chrono::NaiveDate;
+use rocket::local::Client;
+use rocket::config::Value;
+use rocket::http::{ContentType, Status};
+
+use crate::acuity::Acuity;
+use crate::dashboard::{eye_histories, trend_svg};
+use crate::monitoring::{ChangeClassification, Eye};
+use crate::optotypes::OptotypeArrangement;
+use crate::results::{IssuedChart, PatientId, ResultRepository, SqliteStore, StoredResult, TestResult};
+
+fn stored_result(id: i64, eye: Eye, day: u32, log_mar: Option<f64>) -> StoredResult {
+    let tested_at = NaiveDate::from_ymd_opt(2020, 6, day).unwrap().and_hms_opt(9, 30, 0).unwrap();
+    StoredResult {
+        id,
+        result: TestResult {
+            patient: PatientId::from_str("P-1").unwrap(),
+            code: "RFD-CAM".to_string(),
+            eye,
+            viewing_distance: 150.0,
+            chart_distance: 150.0,
+            num_correct: 10,
+            acuity: log_mar.map(Acuity::from_log_mar),
+            tested_at,
+            recorded_at: tested_at
+        }
+    }
+}
+
+/// Results are split by eye, and each scored result is compared with the previous scored result for the same eye
+#[test]
+fn check_eye_histories() {
+    let results = vec![
+        stored_result(1, Eye::Left, 1, Some(0.3)),
+        stored_result(2, Eye::Right, 1, Some(0.2)),
+        stored_result(3, Eye::Left, 8, None),
+        stored_result(4, Eye::Left, 15, Some(0.5)),
+        stored_result(5, Eye::Left, 22, Some(0.45))
+    ];
+    let histories = eye_histories(&results, 0.15).unwrap();
+    assert_eq!(histories.iter().map(|history| history.eye).collect::<Vec<Eye>>(), vec![Eye::Right, Eye::Left]);
+
+    let right = &histories[0];
+    assert_eq!(right.entries.len(), 1);
+    assert_eq!(right.report, None);
+    assert_eq!(right.entries[0].classification, None);
+    assert!(right.trend.is_some());
+
+    let left = &histories[1];
+    let classifications: Vec<Option<ChangeClassification>> = left.entries.iter().map(|entry| entry.classification).collect();
+    assert_eq!(classifications, vec![None, None, Some(ChangeClassification::Worsened), Some(ChangeClassification::Stable)]);
+    assert_eq!(left.entries[2].difference, Some(0.2));
+    assert_eq!(left.report.as_ref().unwrap().overall.classification, ChangeClassification::Stable);
+    assert!(eye_histories(&results, -1.0).is_err());
+}
+
+/// The trend chart has a point for each scored result, and highlights significant changes
+#[test]
+fn check_trend_svg() {
+    let histories = eye_histories(&[stored_result(1, Eye::Left, 1, None)], 0.15).unwrap();
+    assert_eq!(trend_svg(&histories[0].entries), None);
+
+    let results = vec![stored_result(1, Eye::Left, 1, Some(0.3)), stored_result(2, Eye::Left, 8, Some(0.6)), stored_result(3, Eye::Left, 15, Some(0.62))];
+    let svg = eye_histories(&results, 0.15).unwrap().remove(0).trend.unwrap();
+    assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+    assert_eq!(svg.matches("<circle").count(), 3);
+    assert!(svg.contains("#dc3545"));
+    assert!(svg.contains("2020-06-01") && svg.contains("2020-06-15"));
+    assert!(!svg.contains("<script"));
+}
+
+/// The dashboard lists each patient, and shows their results linked to the answer sheets
+#[test]
+fn patient_dashboard() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    assert_eq!(client.get("/patients").dispatch().status(), Status::NotFound);
+
+    let repository = ResultRepository::new(Box::new(SqliteStore::in_memory().unwrap()));
+    for stored in [stored_result(0, Eye::Right, 1, Some(0.1)), stored_result(0, Eye::Right, 8, Some(0.4))].iter() {
+        repository.record(stored.result.clone()).unwrap();
+    }
+    let arrangement = OptotypeArrangement::from_code("EBC3JF").unwrap();
+    repository.record_issue(&IssuedChart::new(PatientId::from_str("P-1").unwrap(), &arrangement, Eye::Left, 300.0)).unwrap();
+    let client = Client::new(crate::tests::rocket_with(&[("results_token", Value::from("s3cret"))]).manage(repository)).unwrap();
+    // Clinicians are sent to sign in before they can see any results
+    let response = client.get("/patients/P-1?lang=cy").dispatch();
+    assert_eq!(response.status(), Status::SeeOther);
+    assert_eq!(response.headers().get_one("Location"), Some("/login?lang=cy"));
+    assert_eq!(client.post("/login").header(ContentType::Form).body("token=s3cret").dispatch().status(), Status::SeeOther);
+
+    let mut response = client.get("/patients").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.body_string().unwrap().contains("href=\"/patients/P-1\""));
+
+    let mut response = client.get("/patients/p-1").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.body_string().unwrap();
+    assert!(body.contains("Right eye"));
+    // The only chart for the left eye has been issued, but not scored
+    assert!(body.contains("Charts issued"));
+    assert!(body.contains("/answers?code=EBC-3JF"));
+    assert!(body.contains("Left eye"));
+    assert!(body.contains("<svg"));
+    assert!(body.contains("/answers?code=RFD-CAM"));
+    assert!(body.contains("2020-06-08 09:30"));
+    assert!(body.contains("class=\"table-danger\""));
+    // With a wider limit, the same change is stable
+    let body = client.get("/patients/P-1?limit=0.5").dispatch().body_string().unwrap();
+    assert!(!body.contains("class=\"table-danger\""));
+
+    let body = client.get("/patients/P-1?lang=cy").dispatch().body_string().unwrap();
+    assert!(body.contains("Llygad dde"));
+    assert!(body.contains("Wedi gwaethygu ers y prawf cyntaf"));
+    assert!(body.contains("Newidiodd y craffter 0.3 logMAR rhwng 2020-06-01 a 2020-06-08."));
+
+    assert_eq!(client.get("/patients/P-2").dispatch().status(), Status::NotFound);
+    assert_eq!(client.get("/patients/12345").dispatch().status(), Status::BadRequest);
+}
@@ -0,0 +1,30 @@
+use crate::fonts::{check_all_fonts, check_optotype_fonts};
+use crate::optotypes::OptotypeDefinition;
+
+/// All of the fonts shipped in static/fonts should pass the checks
+#[test]
+fn check_shipped_fonts() {
+    if let Err(errors) = check_all_fonts() {
+        panic!("The optotype fonts failed the checks: {:?}", errors);
+    }
+}
+
+/// A font which doesn't contain one of the optotypes should be reported
+#[test]
+fn check_missing_glyph() {
+    let mut optotype_definition = OptotypeDefinition::from(1);
+    optotype_definition.optotypes.push('\u{263A}');
+    let errors = check_optotype_fonts(&optotype_definition).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert!(format!("{}", errors[0]).contains("does not contain the optotype"));
+}
+
+/// An optotype definition without any font files should be reported
+#[test]
+fn check_missing_font_files() {
+    let mut optotype_definition = OptotypeDefinition::from(1);
+    optotype_definition.name = "missing".to_string();
+    let errors = check_optotype_fonts(&optotype_definition).unwrap_err();
+    // One error for each of the TrueType, WOFF2 and WOFF fonts
+    assert_eq!(errors.len(), 3);
+}
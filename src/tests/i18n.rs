@@ -0,0 +1,131 @@
+use rocket::local::Client;
+use rocket::http::{Header, Status};
+
+use crate::i18n::{self, Locale};
+use crate::pdf::{answers, chart, naming, PaperSize};
+use crate::optotypes::OptotypeDefinition;
+use crate::scoring::score_count;
+use crate::tests::optotypes::get_known_arrangement;
+
+/// Every catalogue must parse, and must translate every message in the English catalogue
+#[test]
+fn catalogues_are_complete() {
+    let english = i18n::message_ids(Locale::English);
+    assert!(!english.is_empty());
+    for &locale in Locale::all().iter() {
+        assert!(i18n::load_bundle(locale).is_ok(), "The {} catalogue does not parse", locale.code());
+        let ids = i18n::message_ids(locale);
+        for id in &english {
+            assert!(ids.contains(id), "The {} catalogue is missing {}", locale.code(), id);
+        }
+        assert_eq!(ids.len(), english.len(), "The {} catalogue has messages which aren't in English", locale.code());
+    }
+}
+
+/// The most preferred supported language in the Accept-Language header is chosen
+#[test]
+fn negotiate_language() {
+    assert_eq!(Locale::from_accept_language("cy"), Some(Locale::Welsh));
+    assert_eq!(Locale::from_accept_language("pl-PL,pl;q=0.9,en;q=0.8"), Some(Locale::Polish));
+    assert_eq!(Locale::from_accept_language("en-GB;q=0.5, cy-GB"), Some(Locale::Welsh));
+    assert_eq!(Locale::from_accept_language("fr-FR, de;q=0.9, pl;q=0.1"), Some(Locale::Polish));
+    assert_eq!(Locale::from_accept_language("cy;q=0, en;q=0.5"), Some(Locale::English));
+    assert_eq!(Locale::from_accept_language("fr-FR"), None);
+    assert_eq!(Locale::from_accept_language(""), None);
+}
+
+/// The English descriptions of scores must stay the same as the ones in the scores themselves, which are
+/// returned by the API
+#[test]
+fn describe_scores() {
+    for &correct in &[0, 2, 10, 18] {
+        let score = score_count(150.0, 150.0, correct).unwrap();
+        assert_eq!(i18n::describe_score(Locale::English, &score), score.description);
+        assert_ne!(i18n::describe_score(Locale::Welsh, &score), score.description);
+        assert_ne!(i18n::describe_score(Locale::Polish, &score), score.description);
+    }
+}
+
+/// Messages are formatted with their arguments, and unknown messages fall back to their id
+#[test]
+fn translate_messages() {
+    assert_eq!(i18n::translate(Locale::English, "pdf-answers-title", &[("code", "RFD-CAM".to_string())]), "Answer key (RFD-CAM)");
+    assert_eq!(i18n::translate(Locale::Polish, "pdf-answers-title", &[("code", "RFD-CAM".to_string())]), "Klucz odpowiedzi (RFD-CAM)");
+    assert_eq!(i18n::translate(Locale::Welsh, "no-such-message", &[]), "no-such-message");
+}
+
+/// Pages are translated by the query parameter, which takes precedence over the Accept-Language header
+#[test]
+fn translated_pages() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let mut response = client.get("/?lang=cy").header(Header::new("Accept-Language", "pl")).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.body_string().unwrap();
+    assert!(body.contains("<html lang=\"cy\">"));
+    assert!(body.contains("Polisi preifatrwydd"));
+    // The labels of the standardised charts are translated too
+    assert!(body.contains("Lawrlwytho (Llythrennau, Safonol)"));
+
+    let mut response = client.get("/").header(Header::new("Accept-Language", "pl-PL,pl;q=0.9")).dispatch();
+    assert!(response.body_string().unwrap().contains("Polityka prywatności"));
+
+    // Other query parameters are kept when switching languages
+    let mut response = client.get("/score?distance=150&correct=10&lang=pl").dispatch();
+    let body = response.body_string().unwrap();
+    assert!(body.contains("Domowa ocena wzroku"));
+    assert!(body.contains("score?distance=150&amp;correct=10&amp;lang=cy"));
+
+    // Error pages are translated, but JSON errors are always in English
+    let mut response = client.get("/no-such-page?lang=cy").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert!(response.body_string().unwrap().contains("Heb ddod o hyd"));
+    let mut response = client.get("/api/v1/no-such-route").header(Header::new("Accept-Language", "cy")).dispatch();
+    assert!(response.body_string().unwrap().contains("could not be found"));
+}
+
+/// Errors in scoring, marking and comparing sessions are shown in the language of the page
+#[test]
+fn translated_errors() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let mut response = client.get("/score?distance=1000&correct=10&lang=cy").dispatch();
+    assert!(response.body_string().unwrap().contains("Gwall: Rhaid i&#x27;r pellter gwylio fod rhwng 50 cm a 600 cm."));
+    let mut response = client.get("/score?distance=150&correct=100&lang=pl").dispatch();
+    assert!(response.body_string().unwrap().contains("Błąd: Liczba poprawnie odczytanych optotypów musi wynosić od 0 do 18"));
+    let mut response = client.get("/mark?code=RFD-CAM&responses=C&lang=pl").dispatch();
+    assert!(response.body_string().unwrap().contains("Błąd: Wpisz odległość badania."));
+    let mut response = client.get("/monitor?sessions=2020-06-01%206%2F12&lang=cy").dispatch();
+    assert!(response.body_string().unwrap().contains("Gwall: Mae angen o leiaf dwy sesiwn"));
+    let mut response = client.get("/monitor?sessions=2020-06-01%206%2F12%0Ayesterday%206%2F9").dispatch();
+    assert!(response.body_string().unwrap().contains("Error: Line 2 does not start with a date"));
+}
+
+/// The comparison of sessions is translated, on the monitoring page as well as the patient dashboard
+#[test]
+fn translate_change_report() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let mut response = client.get("/monitor?sessions=2020-06-01%206%2F12%0A2020-07-01%206%2F24&lang=pl").dispatch();
+    let body = response.body_string().unwrap();
+    assert!(body.contains("Pogorszenie od pierwszego badania"));
+    assert!(body.contains("Ostrość wzroku zmieniła się o 0.3 logMAR między 2020-06-01 a 2020-07-01."));
+    assert!(body.contains("0.3 logMAR (Pogorszenie)"));
+}
+
+/// The code is percent-encoded in the link to its answers, so that it can't add parameters to the link
+#[test]
+fn score_answers_link() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let mut response = client.get("/score?code=RFD-CAM%26format%3Dpdf&distance=150&correct=10").dispatch();
+    let body = response.body_string().unwrap();
+    assert!(body.contains("href=\"/answers?code=RFD-CAM%26format%3Dpdf\""));
+    assert!(!body.contains("href=\"/answers?code=RFD-CAM&amp;"));
+}
+
+/// PDFs can be generated in every language, using a font which covers the letters used in Welsh and Polish
+#[test]
+fn translated_pdfs() {
+    for &locale in Locale::all().iter() {
+        assert!(chart::generate(&get_known_arrangement(), 150.0, locale, PaperSize::A4).unwrap().starts_with(b"%PDF"));
+        assert!(naming::generate(&OptotypeDefinition::from(0), locale, PaperSize::A4).unwrap().starts_with(b"%PDF"));
+        assert!(answers::generate(&get_known_arrangement(), 150.0, locale, PaperSize::A4).unwrap().starts_with(b"%PDF"));
+    }
+}
@@ -0,0 +1,128 @@
+use rocket::local::Client;
+use rocket::http::Status;
+
+use crate::marking::{mark, is_correct, format_responses, parse_responses, TerminationRule};
+use crate::optotypes::{OptotypeArrangement, OptotypeDefinition};
+use crate::scoring::score_rows;
+use crate::tests::optotypes::get_known_arrangement;
+
+/// Responses can be the optotype's name or a synonym in any case, or the letter itself for letter optotypes.
+/// The digits which the Aukland font draws as pictures are not responses, and neither is "zero" for an O.
+#[test]
+fn check_is_correct() {
+    let aukland = OptotypeDefinition::from(0);
+    assert!(is_correct(&aukland, 0, "Rabbit"));
+    assert!(is_correct(&aukland, 0, " bunny "));
+    assert!(!is_correct(&aukland, 0, "0"));
+    assert!(!is_correct(&aukland, 0, "Duck"));
+    assert!(!is_correct(&aukland, 0, ""));
+    let sloan = OptotypeDefinition::from(1);
+    assert!(is_correct(&sloan, 5, "o"));
+    assert!(!is_correct(&sloan, 5, "0"));
+    assert!(!is_correct(&sloan, 5, "zero"));
+    assert!(!is_correct(&sloan, 0, "O"));
+}
+
+/// Check that typed responses are split into rows, keeping blank positions in place
+#[test]
+fn check_parse_responses() {
+    assert_eq!(parse_responses("S, V\nO - K Z\n\n"), vec![
+        vec!["S".to_string(), "V".to_string()],
+        vec!["O".to_string(), String::new(), "K".to_string(), "Z".to_string()]
+    ]);
+}
+
+/// Mark a chart (S V / O R K Z / N O H V / C D V N / Z C S K) with a mistake on each of the lower rows
+#[test]
+fn check_mark() {
+    let responses = parse_responses("s v\nO R K Z\nN O H -\nC O V N\nZ C S K");
+    let marked = mark(&get_known_arrangement(), &responses, TerminationRule { max_errors_per_row: None }, 150.0, 150.0).unwrap();
+    let correct_per_row: Vec<u32> = marked.rows.iter().map(|row| row.num_correct).collect();
+    assert_eq!(correct_per_row, vec![2, 4, 3, 3, 4]);
+    assert_eq!(marked.rows[2].optotypes[3].response, None);
+    assert!(!marked.rows[3].optotypes[1].correct);
+    assert_eq!(marked.terminated_after_row, None);
+    assert_eq!(marked.score, score_rows(150.0, 150.0, &[2, 4, 3, 3, 4]).unwrap());
+}
+
+/// Rows after the termination rule is met are not presented, even if responses were recorded for them
+#[test]
+fn check_termination_rule() {
+    let responses = parse_responses("S V\nO R K Z\nN - - -\nC D V N");
+    let marked = mark(&get_known_arrangement(), &responses, TerminationRule { max_errors_per_row: Some(3) }, 190.0, 150.0).unwrap();
+    assert_eq!(marked.terminated_after_row, Some(2));
+    assert!(marked.rows[3].optotypes.iter().all(|optotype| !optotype.presented && !optotype.correct));
+    assert_eq!(marked.score.num_correct, 7);
+}
+
+/// Too many responses for the chart should be rejected
+#[test]
+fn check_too_many_responses() {
+    let rule = TerminationRule { max_errors_per_row: None };
+    assert!(mark(&get_known_arrangement(), &parse_responses("S V D"), rule, 150.0, 150.0).is_err());
+    assert!(mark(&get_known_arrangement(), &parse_responses("S\nO\nN\nC\nZ\nQ"), rule, 150.0, 150.0).is_err());
+}
+
+/// Responses are joined back into text which parses to the same rows
+#[test]
+fn check_format_responses() {
+    let responses = parse_responses("S, V\nO - K Z");
+    assert_eq!(format_responses(&responses), "S V\nO - K Z");
+    assert_eq!(parse_responses(&format_responses(&responses)), responses);
+}
+
+/// Rows after a patient stopped reading are shown as not presented, without changing the score
+#[test]
+fn check_stopped_after() {
+    let marked = mark(&get_known_arrangement(), &parse_responses("S V\nO R K Z"), TerminationRule { max_errors_per_row: None }, 150.0, 150.0).unwrap();
+    let num_correct = marked.score.num_correct;
+    let stopped = marked.stopped_after(2);
+    assert_eq!(stopped.terminated_after_row, Some(1));
+    assert!(stopped.rows[1].optotypes.iter().all(|optotype| optotype.presented));
+    assert!(stopped.rows[2].optotypes.iter().all(|optotype| !optotype.presented));
+    assert_eq!(stopped.score.num_correct, num_correct);
+}
+
+fn self_check_page(client: &Client, query: &str) -> String {
+    let mut response = client.get(format!("/self-check?{}", query)).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    response.body_string().unwrap()
+}
+
+/// A patient enters their code, then each row in turn, and the answers are only shown at the end
+#[test]
+fn self_check_flow() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let code = "FFT7-CVBJ-8ZV8-ALWE";
+    let arrangement = OptotypeArrangement::from_code(code).unwrap();
+    assert!(self_check_page(&client, "").contains("name=\"code\""));
+    assert!(self_check_page(&client, "code=RFDCAM&distance=150").contains("telephone"));
+    assert!(self_check_page(&client, &format!("code={}", code)).contains("viewing distance"));
+
+    let mut responses = String::new();
+    for (row_number, row) in arrangement.rows.iter().enumerate() {
+        let query = format!("code={}&distance=150&chart_distance=150&responses={}", code, responses.replace(' ', "+").replace('\n', "%0A"));
+        let body = self_check_page(&client, &query);
+        assert!(body.contains(&format!("Row {} of {}", row_number + 1, arrangement.rows.len())));
+        assert!(!body.contains("&#10003;"));
+        let names: Vec<String> = row.optotypes.iter().map(|&optotype| arrangement.optotype_definition.names[optotype as usize].clone()).collect();
+        if !responses.is_empty() {
+            responses.push('\n');
+        }
+        responses.push_str(&names.join(" "));
+        if row_number + 1 == arrangement.rows.len() {
+            let body = self_check_page(&client, &format!("{}&row={}", query, names.join("+")));
+            assert!(body.contains("Your results"));
+            assert!(body.contains("&#10003;"));
+            assert!(!body.contains("&#10007;"));
+        }
+    }
+
+    // Too many responses for a row are rejected, and the same row is asked for again
+    let body = self_check_page(&client, &format!("code={}&distance=150&row=a+b+c+d+e", code));
+    assert!(body.contains("this row only has 2 symbols"));
+    assert!(body.contains("Row 1 of"));
+    // A patient who can't read any more sees their results straight away
+    let body = self_check_page(&client, &format!("code={}&distance=150&responses=a+b&row=&finish=true", code));
+    assert!(body.contains("You stopped after row 1"));
+}
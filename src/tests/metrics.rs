@@ -0,0 +1,34 @@
+use rocket::config::Value;
+use rocket::local::Client;
+use rocket::http::{ContentType, Header, Status};
+
+use crate::metrics::metrics;
+
+/// Codes which can't be parsed are counted by the kind of error, when they are given to the server
+#[test]
+fn count_code_parse_failures() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let count = |kind: &str| metrics().code_parse_failures.with_label_values(&[kind]).get();
+    let (crc_mismatches, invalid_characters) = (count("CrcMismatch"), count("InvalidCharacter"));
+    client.get("/answers?code=RFD-CAA").dispatch();
+    client.get("/api/v1/codes/RFD-CA!").dispatch();
+    // Other tests may be parsing invalid codes at the same time, so the counts can only be checked as lower bounds
+    assert!(count("CrcMismatch") > crc_mismatches);
+    assert!(count("InvalidCharacter") > invalid_characters);
+}
+
+/// The metrics endpoint reports requests by route, PDF generation times and codes issued by mode
+#[test]
+fn export_metrics() {
+    let client = Client::new(crate::tests::rocket_with(&[("metrics_token", Value::from("s3cret"))])).unwrap();
+    client.get("/naming-sheet/sloan").dispatch();
+    client.post("/api/v1/codes").header(ContentType::JSON).body(r#"{"mode": "telephone", "optotypes": "sloan"}"#).dispatch();
+    assert_eq!(client.get("/metrics").dispatch().status(), Status::Unauthorized);
+    let mut response = client.get("/metrics").header(Header::new("Authorization", "Bearer s3cret")).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.body_string().unwrap();
+    assert!(body.contains(r#"hat_http_requests_total{method="GET",route="/naming-sheet/<name>",status="200"}"#));
+    assert!(body.contains(r#"hat_http_request_duration_seconds_bucket{method="GET",route="/naming-sheet/<name>""#));
+    assert!(body.contains(r#"hat_pdf_generation_seconds_count{sheet="naming-sheet"}"#));
+    assert!(body.contains(r#"hat_codes_issued_total{mode="telephone"}"#));
+}
@@ -1,15 +1,79 @@
 use ux::{u1, u2, u4};
+use rocket::config::Value;
+use rocket::http::{ContentType, Status};
+use rocket::local::Client;
 use crate::optotypes::OptotypeDefinition;
 use crate::codes::{short::ShortCode, long::LongCode};
 
+pub mod acuity;
+pub mod api;
+pub mod audit;
+pub mod auth;
+pub mod catchers;
 pub mod codes;
+pub mod config;
+pub mod constraints;
+pub mod dashboard;
+pub mod fonts;
+pub mod i18n;
+pub mod marking;
+pub mod metrics;
+pub mod monitoring;
+pub mod openapi;
 pub mod optotypes;
+pub mod pdf;
+pub mod rate_limit;
+pub mod results;
+pub mod scoring;
+pub mod statistics;
 
 #[test]
 fn always_passes() {
     assert_eq!(2 + 2, 4);
 }
 
+/// Every link on the homepage should lead somewhere, including the download buttons for randomised charts, which
+/// redirect to the PDF of a newly issued chart. Rate limiting is turned off, as the homepage links to more sheets
+/// than a client can download in a burst.
+#[test]
+fn homepage_links_exist() {
+    let client = Client::new(rocket_with(&[("rate_limit_store", Value::from("none"))])).unwrap();
+    let body = client.get("/").dispatch().body_string().unwrap();
+    let links: Vec<String> = body.split("href=\"").skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        .map(|link| link.replace("&amp;", "&"))
+        .filter(|link| link.starts_with('/'))
+        .collect();
+    assert!(links.iter().any(|link| link.starts_with("/generate?type=")));
+    for link in links {
+        let response = client.get(link.clone()).dispatch();
+        assert_ne!(response.status(), Status::NotFound, "{} was not found", link);
+        if let Some(location) = response.headers().get_one("Location") {
+            assert_eq!(response.status(), Status::SeeOther, "{} redirected with the wrong status", link);
+            let redirected = client.get(location.to_string()).dispatch();
+            assert_eq!(redirected.status(), Status::Ok, "{} redirected to {}, which failed", link, location);
+            assert_eq!(redirected.content_type(), Some(ContentType::PDF));
+        }
+    }
+}
+
+/// Builds the server from the settings in Rocket.toml, with some of them replaced. Tests use this to turn off the
+/// parts of the server which would get in the way of the test. The audit log is always turned off unless a test
+/// turns it back on, so that tests don't write to the audit log in Rocket.toml.
+pub fn rocket_with(settings: &[(&str, Value)]) -> rocket::Rocket {
+    let mut config = rocket::ignite().config().clone();
+    config.extras.insert("audit_log".to_string(), Value::from("none"));
+    for (name, value) in settings {
+        config.extras.insert(name.to_string(), value.clone());
+    }
+    crate::build(rocket::custom(config))
+}
+
+/// Builds the server from the settings in Rocket.toml, without the audit log (see rocket_with)
+pub fn rocket() -> rocket::Rocket {
+    rocket_with(&[])
+}
+
 /// Use this shortcode ("RFD-CAM") for testing - defined centrally to avoid duplicating
 pub fn get_test_shortcode() -> ShortCode {
     ShortCode {
@@ -0,0 +1,53 @@
+use chrono::NaiveDate;
+use crate::acuity::Acuity;
+use crate::monitoring::{compare, classify, parse_sessions, ChangeClassification, Eye, Session, DEFAULT_TEST_RETEST_LIMIT};
+
+fn session(date: &str, log_mar: f64) -> Session {
+    Session { date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(), eye: Eye::Right, acuity: Acuity::from_log_mar(log_mar) }
+}
+
+/// Changes only count if they are larger than the test-retest limit
+#[test]
+fn check_classify() {
+    assert_eq!(classify(0.2, 0.15), ChangeClassification::Worsened);
+    assert_eq!(classify(-0.2, 0.15), ChangeClassification::Improved);
+    assert_eq!(classify(0.15, 0.15), ChangeClassification::Stable);
+    // 0.45 - 0.3 is slightly more than 0.15 in floating point
+    assert_eq!(classify(0.45 - 0.3, 0.15), ChangeClassification::Stable);
+}
+
+/// Sessions are sorted by date, and compared with the previous session and the first session
+#[test]
+fn check_compare() {
+    let sessions = vec![session("2020-03-01", 0.3), session("2020-01-01", 0.2), session("2020-02-01", 0.1)];
+    let report = compare(&sessions, DEFAULT_TEST_RETEST_LIMIT).unwrap();
+    assert_eq!(report.sessions[0], sessions[1]);
+    assert_eq!(report.changes.len(), 2);
+    assert_eq!(report.changes[0].difference, -0.1);
+    assert_eq!(report.changes[0].classification, ChangeClassification::Stable);
+    assert_eq!(report.changes[1].difference, 0.2);
+    assert_eq!(report.changes[1].classification, ChangeClassification::Worsened);
+    assert_eq!(report.overall.classification, ChangeClassification::Stable);
+    assert_eq!((report.overall.from_session, report.overall.to_session), (0, 2));
+}
+
+/// Comparisons need at least two sessions, all for the same eye
+#[test]
+fn check_invalid_comparisons() {
+    assert!(compare(&[session("2020-01-01", 0.2)], DEFAULT_TEST_RETEST_LIMIT).is_err());
+    let mut other_eye = session("2020-02-01", 0.2);
+    other_eye.eye = Eye::Left;
+    assert!(compare(&[session("2020-01-01", 0.2), other_eye], DEFAULT_TEST_RETEST_LIMIT).is_err());
+    assert!(compare(&[session("2020-01-01", 0.2), session("2020-02-01", 0.2)], -1.0).is_err());
+}
+
+/// Sessions can be typed with the acuity in any unit
+#[test]
+fn check_parse_sessions() {
+    let sessions = parse_sessions("2020-01-01 6/12\n\n2020-02-01 0.5 decimal\n", Eye::Right).unwrap();
+    assert_eq!(sessions, vec![session("2020-01-01", 0.3), session("2020-02-01", 0.3)]);
+    assert!(parse_sessions("2020-01-01", Eye::Right).is_err());
+    assert!(parse_sessions("01/01/2020 6/12", Eye::Right).is_err());
+    assert!(parse_sessions("2020-01-01 6/", Eye::Right).is_err());
+    assert_eq!("OS".parse::<Eye>().unwrap(), Eye::Left);
+}
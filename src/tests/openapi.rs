@@ -0,0 +1,48 @@
+use rocket::local::Client;
+use rocket::http::Status;
+use schemars::gen::SchemaSettings;
+use serde_json::Value;
+
+use crate::api;
+use crate::openapi::{get_parameter_names, API_PATH};
+
+/// Every mounted API route must come from the list of described routes, and each description must list exactly the
+/// parameters which its route takes, otherwise the document would be out of date.
+#[test]
+fn every_route_is_described() {
+    let rocket = crate::tests::rocket();
+    let api_routes = api::routes();
+    let described_paths: Vec<String> = api_routes.iter().flat_map(|api_route| api_route.paths()).collect();
+    for route in rocket.routes().filter(|route| route.uri.path().starts_with(API_PATH)) {
+        assert!(described_paths.contains(&route.uri.path().to_string()), "{} is not described", route.uri);
+    }
+    let mut gen = SchemaSettings::openapi3().into_generator();
+    for api_route in api_routes.iter() {
+        let description = (api_route.describe)(&mut gen);
+        let described_parameters: Vec<String> = description.parameters.iter().map(|parameter| parameter.name.to_string()).collect();
+        for route in api_route.routes.iter() {
+            let mut route_parameters = get_parameter_names(route.uri.path());
+            route_parameters.append(&mut get_parameter_names(route.uri.query().unwrap_or("")));
+            assert_eq!(route_parameters, described_parameters, "{}", route.uri);
+        }
+    }
+}
+
+/// The document is served as JSON, and includes the schemas of the types returned by the API
+#[test]
+fn serve_openapi_document() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let mut response = client.get("/api/openapi.json").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let document: Value = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(document["openapi"], "3.0.3");
+    for schema in ["OptotypeArrangement", "OptotypeRow", "OptotypeDefinition", "Score", "ApiError", "CodeKind"].iter() {
+        assert!(document["components"]["schemas"][schema].is_object(), "{}", schema);
+    }
+    let decode = &document["paths"]["/api/v1/codes/{code}"]["get"];
+    assert_eq!(decode["parameters"][0]["in"], "path");
+    assert_eq!(decode["responses"]["200"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/DecodedCode");
+    assert!(document["paths"]["/api/v1/codes"]["post"]["requestBody"].is_object());
+    assert_eq!(document["paths"]["/api/v1/score"]["get"]["parameters"][3]["required"], false);
+    assert_eq!(document["paths"]["/api/v1/score"]["get"]["responses"]["422"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/ApiError");
+}
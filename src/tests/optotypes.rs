@@ -1,5 +1,6 @@
-use crate::optotypes::{OptotypeRow, OptotypeDefinition, OptotypeArrangement, DEFAULT_OPTOTYPES};
+use crate::optotypes::{OptotypeRow, OptotypeDefinition, OptotypeArrangement, ArrangementWarning, DEFAULT_OPTOTYPES};
 use crate::tests::get_test_shortcode;
+use ux::u4;
 
 pub fn get_known_arrangement() -> OptotypeArrangement {
     OptotypeArrangement {
@@ -11,7 +12,8 @@ pub fn get_known_arrangement() -> OptotypeArrangement {
             OptotypeRow { text_size: 1.0, border_size: 0.2, optotypes: vec![9, 0, 7, 3]},
         ],
         code: "RFD-CAM".to_string(),
-        optotype_definition: OptotypeDefinition::from(1)
+        optotype_definition: OptotypeDefinition::from(1),
+        warnings: vec![]
     }
 }
 
@@ -22,12 +24,38 @@ fn check_arrangement() {
     assert_eq!(known_arrangement, obtained_arrangement);
 }
 
+/// A legacy short code with a zero offset copies the reference row, which should be flagged
+#[test]
+fn check_duplicate_rows_warning() {
+    let mut short_code = get_test_shortcode();
+    short_code.offsets[3] = u4::new(0);
+    let arrangement = OptotypeArrangement::from(short_code);
+    assert_eq!(arrangement.warnings, vec![ArrangementWarning::DuplicateRows]);
+}
+
 /// Check the fallback into default optotypes, when the optotype ID is not given
 #[test]
 fn check_fallback() {
     assert_eq!(OptotypeDefinition::from(50), OptotypeDefinition::from(DEFAULT_OPTOTYPES));
 }
 
+/// Check that optotype definitions can be looked up by name, and that unknown names
+/// are not silently replaced by the default optotypes
+#[test]
+fn check_from_name() {
+    assert_eq!(OptotypeDefinition::from_name("aukland"), Some(OptotypeDefinition::from(0)));
+    assert_eq!(OptotypeDefinition::from_name("unknown"), None);
+}
+
+/// Every optotype needs a name, otherwise the naming sheet cannot be generated
+#[test]
+fn check_names() {
+    for optotype_definition in OptotypeDefinition::all() {
+        assert_eq!(optotype_definition.names.len(), optotype_definition.optotypes.len());
+        assert_eq!(optotype_definition.synonyms.len(), optotype_definition.optotypes.len());
+    }
+}
+
 /// Check the debug trait on OptotypeDefinitions
 #[test]
 fn check_debug() {
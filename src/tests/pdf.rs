@@ -0,0 +1,49 @@
+use crate::acuity::Acuity;
+use crate::i18n::Locale;
+use crate::optotypes::{OptotypeArrangement, OptotypeDefinition};
+use crate::pdf::{answers, chart, naming, PaperSize};
+use crate::tests::optotypes::get_known_arrangement;
+
+/// Generate a naming sheet for every optotype definition and check that a PDF comes out
+#[test]
+fn generate_naming_sheets() {
+    for optotype_definition in OptotypeDefinition::all() {
+        let bytes = naming::generate(&optotype_definition, Locale::English, PaperSize::A4).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+    }
+}
+
+/// Charts are printed for the requested distance, unless they would not fit on the page
+#[test]
+fn check_chart_distance() {
+    assert_eq!(chart::get_chart_distance(150.0, PaperSize::A4), 150.0);
+    assert_eq!(chart::get_chart_distance(100.0, PaperSize::A4), 100.0);
+    assert_eq!(chart::get_chart_distance(190.0, PaperSize::A4), 190.0);
+    assert!(chart::get_chart_distance(300.0, PaperSize::A4) < 300.0);
+    assert!(chart::get_chart_distance(50.0, PaperSize::A4) > 50.0);
+    assert_eq!(chart::get_acuity_range(150.0, 150.0), (Acuity::from_log_mar(1.3), Acuity::from_log_mar(0.1)));
+    assert_eq!(chart::get_acuity_range(190.0, 150.0), (Acuity::from_log_mar(1.2), Acuity::from_log_mar(0.0)));
+}
+
+/// Generate charts for a range of distances and check that a PDF comes out
+#[test]
+fn generate_charts() {
+    for &distance in [100.0, 150.0, 190.0, 300.0].iter() {
+        let bytes = chart::generate(&get_known_arrangement(), distance, Locale::English, PaperSize::A4).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+    }
+    assert!(chart::generate(&get_known_arrangement(), 10.0, Locale::English, PaperSize::A4).is_err());
+    // Charts can also be laid out for US letter paper. The widest rows limit the size of the chart, and letter
+    // paper is slightly wider than A4, so it fits charts for slightly longer distances.
+    assert!(chart::generate(&get_known_arrangement(), 150.0, Locale::English, PaperSize::Letter).unwrap().starts_with(b"%PDF"));
+    assert!(chart::get_chart_distance(600.0, PaperSize::Letter) > chart::get_chart_distance(600.0, PaperSize::A4));
+}
+
+/// Generate the answer key for both kinds of code and check that a PDF comes out
+#[test]
+fn generate_answer_keys() {
+    for code in ["RFD-CAM", "FFT7-CVBJ-8ZV8-ALWE"].iter() {
+        let bytes = answers::generate(&OptotypeArrangement::from_code(code).unwrap(), 150.0, Locale::English, PaperSize::A4).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+    }
+}
@@ -0,0 +1,134 @@
+use std::net::SocketAddr;
+use rocket::config::{Config, Environment, Value};
+use rocket::local::Client;
+use rocket::http::{Accept, ContentType, MediaType, Status};
+
+use crate::metrics::metrics;
+use crate::rate_limit::{MemoryStore, RateLimiter, SqliteStore};
+
+fn get_test_limiter() -> RateLimiter {
+    // Bursts of three requests, and then one request every two seconds
+    RateLimiter::new(Box::new(MemoryStore::default()), 3, 30, vec!["/generate".to_string()])
+}
+
+/// Clients can make a burst of requests, and are then limited to the steady rate
+#[test]
+fn token_bucket() {
+    let limiter = get_test_limiter();
+    for _ in 0..3 {
+        assert_eq!(limiter.take_at("a", 1000.0), Ok(()));
+    }
+    assert_eq!(limiter.take_at("a", 1000.0), Err(2));
+    assert_eq!(limiter.take_at("a", 1001.0), Err(1));
+    assert_eq!(limiter.take_at("a", 1002.0), Ok(()));
+    assert_eq!(limiter.take_at("a", 1002.5), Err(2));
+    // Each client has its own bucket, which refills up to the burst size
+    assert_eq!(limiter.take_at("b", 1002.5), Ok(()));
+    for _ in 0..3 {
+        assert_eq!(limiter.take_at("a", 2000.0), Ok(()));
+    }
+    assert!(limiter.take_at("a", 2000.0).is_err());
+}
+
+#[test]
+fn limited_paths() {
+    let limiter = get_test_limiter();
+    assert!(limiter.is_limited_path("/generate"));
+    assert!(!limiter.is_limited_path("/answers"));
+}
+
+/// Once a client's bucket is empty, the server responds with 429 and a Retry-After header without generating
+/// the sheet, while other clients are unaffected
+#[test]
+fn rate_limited_responses() {
+    let client = Client::new(crate::tests::rocket()).unwrap();
+    let address: SocketAddr = "192.0.2.1:8000".parse().unwrap();
+    let mut status = Status::Ok;
+    for _ in 0..100 {
+        // Invalid codes are cheap to respond to, but still count towards the limit
+        status = client.get("/generate?code=invalid").remote(address).dispatch().status();
+        if status == Status::TooManyRequests {
+            break;
+        }
+    }
+    assert_eq!(status, Status::TooManyRequests);
+    let mut response = client.get("/generate?code=RFD-CAM").remote(address).dispatch();
+    assert_eq!(response.status(), Status::TooManyRequests);
+    assert!(response.headers().get_one("Retry-After").unwrap().parse::<u64>().unwrap() > 0);
+    assert_eq!(response.content_type(), Some(ContentType::HTML));
+    assert!(response.body_string().unwrap().contains("try again"));
+    // API clients are given a structured error, like the other errors
+    let mut response = client.get("/generate?code=RFD-CAM").remote(address).header(Accept::JSON).dispatch();
+    assert_eq!(response.status(), Status::TooManyRequests);
+    assert!(response.headers().get_one("Retry-After").is_some());
+    let json: serde_json::Value = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(json["error"], "TooManyRequests");
+
+    let other_address: SocketAddr = "192.0.2.2:8000".parse().unwrap();
+    assert_eq!(client.get("/generate?code=invalid").remote(other_address).dispatch().status(), Status::BadRequest);
+    // Other pages aren't limited
+    assert_eq!(client.get("/").remote(address).dispatch().status(), Status::Ok);
+}
+
+/// Limited requests are counted against the route which was asked for, rather than a route which doesn't exist
+#[test]
+fn limited_requests_are_matched() {
+    let settings = [("rate_limit_burst", Value::Integer(1)), ("rate_limit_per_minute", Value::Integer(1))];
+    let client = Client::new(crate::tests::rocket_with(&settings)).unwrap();
+    let address: SocketAddr = "192.0.2.3:8000".parse().unwrap();
+    client.get("/naming-sheet/sloan").remote(address).dispatch();
+    let before = metrics().requests.with_label_values(&["/naming-sheet/<name>", "GET", "429"]).get();
+    assert_eq!(client.get("/naming-sheet/sloan").remote(address).dispatch().status(), Status::TooManyRequests);
+    assert!(metrics().requests.with_label_values(&["/naming-sheet/<name>", "GET", "429"]).get() > before);
+}
+
+/// A download button on the homepage issues a chart and redirects to it, which only takes one token
+#[test]
+fn download_buttons_take_one_token() {
+    let settings = [("rate_limit_burst", Value::Integer(1)), ("rate_limit_per_minute", Value::Integer(1))];
+    let client = Client::new(crate::tests::rocket_with(&settings)).unwrap();
+    let address: SocketAddr = "192.0.2.4:8000".parse().unwrap();
+    let response = client.get("/generate?type=adult&mode=telephone").remote(address).dispatch();
+    assert_eq!(response.status(), Status::SeeOther);
+    let location = response.headers().get_one("Location").unwrap().to_string();
+    assert_eq!(client.get(location.clone()).remote(address).dispatch().status(), Status::Ok);
+    assert_eq!(client.get(location).remote(address).dispatch().status(), Status::TooManyRequests);
+}
+
+/// Answer keys are limited when they are PDFs, whether asked for with the format parameter or the Accept header,
+/// but the HTML and JSON answer sheets are not
+#[test]
+fn answer_key_pdfs_are_limited() {
+    let settings = [("rate_limit_burst", Value::Integer(1)), ("rate_limit_per_minute", Value::Integer(1))];
+    let client = Client::new(crate::tests::rocket_with(&settings)).unwrap();
+    let address: SocketAddr = "192.0.2.1:8000".parse().unwrap();
+    assert_ne!(client.get("/answers?code=invalid&format=pdf").remote(address).dispatch().status(), Status::TooManyRequests);
+    assert_eq!(client.get("/answers?code=invalid&format=pdf").remote(address).dispatch().status(), Status::TooManyRequests);
+    assert_eq!(client.get("/answers?code=invalid").remote(address).header(Accept::new(vec![MediaType::PDF.into()])).dispatch().status(), Status::TooManyRequests);
+    assert_ne!(client.get("/answers?code=RFD-CAM").remote(address).dispatch().status(), Status::TooManyRequests);
+    assert_ne!(client.get("/answers?code=RFD-CAM&format=json").remote(address).dispatch().status(), Status::TooManyRequests);
+}
+
+/// The SQLite store keeps the salt which addresses are hashed with, so clients keep their buckets when the server
+/// restarts
+#[test]
+fn sqlite_salt_survives_restarts() {
+    let path = std::env::temp_dir().join(format!("hat-rate-limit-test-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let salt = SqliteStore::open(&path).unwrap().salt().unwrap();
+    assert!(!salt.is_empty());
+    assert_eq!(SqliteStore::open(&path).unwrap().salt().unwrap(), salt);
+
+    let limiter = || {
+        let config = Config::build(Environment::Development)
+            .extra("rate_limit_store", "sqlite")
+            .extra("rate_limit_path", path.to_str().unwrap())
+            .extra("rate_limit_burst", 1)
+            .finalize().unwrap();
+        RateLimiter::from_config(&config, None).unwrap().unwrap()
+    };
+    let ip = Some("192.0.2.1".parse().unwrap());
+    assert_eq!(limiter().take(ip), Ok(()));
+    assert!(limiter().take(ip).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
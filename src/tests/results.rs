@@ -0,0 +1,162 @@
+use std::str::FromStr;
+use chrono::NaiveDate;
+use rocket::config::Value;
+use rocket::error::LaunchErrorKind;
+use rocket::local::Client;
+use rocket::http::{ContentType, Header, Status};
+
+use crate::monitoring::Eye;
+use crate::optotypes::OptotypeArrangement;
+use crate::results::{IssuedChart, PatientId, ResultRepository, SqliteStore, TestResult};
+use crate::scoring::score_arrangement;
+
+/// Patient identifiers are pseudonyms, so anything which looks like a name with spaces or a hospital number is refused
+#[test]
+fn check_patient_ids() {
+    assert_eq!(PatientId::from_str(" p-0017 ").unwrap().as_str(), "P-0017");
+    assert!(PatientId::from_str("STUDY_12").is_ok());
+    assert!(PatientId::from_str("").is_err());
+    assert!(PatientId::from_str("4857773456").is_err());
+    assert!(PatientId::from_str("Jane Smith").is_err());
+    assert!(PatientId::from_str(&"A".repeat(33)).is_err());
+}
+
+fn test_result(patient: &str, eye: Eye, correct: u32, day: u32) -> TestResult {
+    let arrangement = OptotypeArrangement::from_code("RFDCAM").unwrap();
+    let score = score_arrangement(&arrangement, 150.0, 150.0, correct).unwrap();
+    let tested_at = NaiveDate::from_ymd_opt(2020, 6, day).unwrap().and_hms_opt(9, 30, 0).unwrap();
+    TestResult::new(PatientId::from_str(patient).unwrap(), &arrangement, eye, &score, tested_at)
+}
+
+/// Results are stored and read back for each patient, in the order the tests were done
+#[test]
+fn sqlite_store() {
+    let repository = ResultRepository::new(Box::new(SqliteStore::in_memory().unwrap()));
+    let later = repository.record(test_result("P-1", Eye::Left, 10, 20)).unwrap();
+    let earlier = repository.record(test_result("P-1", Eye::Right, 0, 1)).unwrap();
+    repository.record(test_result("A-2", Eye::Both, 14, 5)).unwrap();
+    assert_ne!(later.id, earlier.id);
+
+    let results = repository.results_for(&PatientId::from_str("p-1").unwrap()).unwrap();
+    assert_eq!(results, vec![earlier, later]);
+    assert_eq!(results[0].result.acuity, None);
+    assert_eq!(results[1].result.eye, Eye::Left);
+    assert_eq!(results[1].result.code, "RFD-CAM");
+    assert!(repository.results_for(&PatientId::from_str("P-3").unwrap()).unwrap().is_empty());
+    assert_eq!(repository.patients().unwrap(), vec![PatientId::from_str("A-2").unwrap(), PatientId::from_str("P-1").unwrap()]);
+}
+
+const TEST_TOKEN: &str = "s3cret";
+
+fn authorization() -> Header<'static> {
+    Header::new("Authorization", format!("Bearer {}", TEST_TOKEN))
+}
+
+/// A client for a server which stores results in memory, and gives them to clients with TEST_TOKEN
+fn results_client() -> Client {
+    let repository = ResultRepository::new(Box::new(SqliteStore::in_memory().unwrap()));
+    Client::new(crate::tests::rocket_with(&[("results_token", Value::from(TEST_TOKEN))]).manage(repository)).unwrap()
+}
+
+/// Results are recorded and retrieved through the API
+#[test]
+fn record_and_retrieve_results() {
+    // Results aren't stored by default, so the routes are only found when a store is added
+    let client = Client::new(crate::tests::rocket_with(&[("results_token", Value::from(TEST_TOKEN))])).unwrap();
+    assert_eq!(client.get("/api/v1/patients").header(authorization()).dispatch().status(), Status::NotFound);
+    let client = results_client();
+    let patient = "TEST-1".to_string();
+    let body = format!(r#"{{"patient": "{}", "code": "RFDCAM", "eye": "Right", "distance": 150, "correct": 12, "tested_at": "2020-06-01T09:30:00"}}"#, patient);
+    let mut response = client.post("/api/v1/results").header(ContentType::JSON).header(authorization()).body(body).dispatch();
+    assert_eq!(response.status(), Status::Created);
+    let stored: serde_json::Value = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(stored["patient"], patient);
+    assert_eq!(stored["num_correct"], 12);
+    assert_eq!(stored["chart_distance"], 150.0);
+    assert!(stored["acuity"]["log_mar"].is_number());
+
+    let mut response = client.get(format!("/api/v1/patients/{}/results", patient.to_lowercase())).header(authorization()).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let results: serde_json::Value = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(results.as_array().unwrap().len(), 1);
+    assert_eq!(results[0]["id"], stored["id"]);
+    assert_eq!(results[0]["tested_at"], "2020-06-01T09:30:00");
+
+    let mut response = client.get("/api/v1/patients").header(authorization()).dispatch();
+    assert_eq!(response.body_string().unwrap(), r#"["TEST-1"]"#);
+
+    let invalid = |body: &str| client.post("/api/v1/results").header(ContentType::JSON).header(authorization()).body(body).dispatch().status();
+    assert_eq!(invalid(r#"{"patient": "1234567890", "code": "RFDCAM", "eye": "Left", "distance": 150, "correct": 12}"#), Status::UnprocessableEntity);
+    assert_eq!(invalid(r#"{"patient": "P-1", "code": "RFDCAM", "eye": "Left", "distance": 150, "correct": 99}"#), Status::UnprocessableEntity);
+    assert_eq!(invalid(r#"{"patient": "P-1", "code": "RFD", "eye": "Left", "distance": 150, "correct": 12}"#), Status::BadRequest);
+    assert_eq!(client.get("/api/v1/patients/1234/results").header(authorization()).dispatch().status(), Status::BadRequest);
+}
+
+/// Stored results are only given to clients with the results token, either as a bearer token or from signing in
+#[test]
+fn results_need_token() {
+    let client = results_client();
+    assert_eq!(client.get("/api/v1/patients").dispatch().status(), Status::Unauthorized);
+    let wrong = Header::new("Authorization", "Bearer s3cret!");
+    let mut response = client.get("/api/v1/patients").header(wrong).dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+    assert!(response.body_string().unwrap().contains("Unauthorized"));
+    let body = r#"{"patient": "P-1", "code": "RFDCAM", "eye": "Left", "distance": 150, "correct": 12}"#;
+    assert_eq!(client.post("/api/v1/results").header(ContentType::JSON).body(body).dispatch().status(), Status::Unauthorized);
+
+    // The client keeps the cookie which is set by signing in, until it signs out
+    let login = |token: &str| client.post("/login").header(ContentType::Form).body(format!("token={}", token)).dispatch().status();
+    assert_eq!(login("wrong"), Status::Unauthorized);
+    assert_eq!(client.get("/api/v1/patients").dispatch().status(), Status::Unauthorized);
+    assert_eq!(login(TEST_TOKEN), Status::SeeOther);
+    assert_eq!(client.get("/api/v1/patients").dispatch().status(), Status::Ok);
+    client.post("/logout").dispatch();
+    assert_eq!(client.get("/api/v1/patients").dispatch().status(), Status::Unauthorized);
+}
+
+/// The server won't start storing results until the token needed to read them has been set
+#[test]
+fn results_store_needs_token() {
+    let settings = [("results_store", Value::from("sqlite")), ("results_path", Value::from(":memory:"))];
+    match Client::new(crate::tests::rocket_with(&settings)) {
+        Err(e) => match e.kind() {
+            LaunchErrorKind::FailedFairings(fairings) => assert_eq!(fairings, &vec!["Result storage"]),
+            kind => panic!("The server failed to start for the wrong reason ({})", kind)
+        },
+        Ok(_) => panic!("The server started storing results without a token")
+    }
+    let settings = [("results_store", Value::from("sqlite")), ("results_path", Value::from(":memory:")), ("results_token", Value::from(TEST_TOKEN))];
+    assert!(Client::new(crate::tests::rocket_with(&settings)).is_ok());
+}
+
+/// Charts issued through the API for a patient are recorded, but only by clients with the results token
+#[test]
+fn record_issued_charts() {
+    let repository = ResultRepository::new(Box::new(SqliteStore::in_memory().unwrap()));
+    let arrangement = OptotypeArrangement::from_code("RFDCAM").unwrap();
+    let issued = IssuedChart::new(PatientId::from_str("P-1").unwrap(), &arrangement, Eye::Left, 300.0);
+    repository.record_issue(&issued).unwrap();
+    assert_eq!(repository.issued_to(&PatientId::from_str("p-1").unwrap()).unwrap(), vec![issued]);
+
+    let client = results_client();
+    let issue = |body: &str, authorised: bool| {
+        let request = client.post("/api/v1/codes").header(ContentType::JSON).body(body);
+        let request = if authorised { request.header(authorization()) } else { request };
+        request.dispatch().status()
+    };
+    let body = r#"{"mode": "telephone", "patient": "P-2", "eye": "Right", "distance": 300}"#;
+    assert_eq!(issue(body, false), Status::Unauthorized);
+    assert_eq!(issue(body, true), Status::Created);
+    assert_eq!(issue(r#"{"mode": "telephone", "patient": "P-2"}"#, true), Status::UnprocessableEntity);
+    assert_eq!(issue(r#"{"mode": "telephone", "patient": "P-2", "eye": "Right", "distance": 5}"#, true), Status::UnprocessableEntity);
+    // The charts issued to a patient are listed for clients with the token
+    let mut response = client.get("/api/v1/patients/p-2/charts").header(authorization()).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let json: serde_json::Value = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(json.as_array().map(Vec::len), Some(1));
+    assert_eq!(json[0]["eye"], "Right");
+    assert_eq!(json[0]["chart_distance"], 300.0);
+    assert_eq!(client.get("/api/v1/patients/P-2/charts").dispatch().status(), Status::Unauthorized);
+    // Charts which aren't for a patient can still be issued by anyone
+    assert_eq!(issue(r#"{"mode": "telephone"}"#, false), Status::Created);
+}
@@ -0,0 +1,60 @@
+use crate::scoring::{score_count, score_rows, score_arrangement, get_row_acuity};
+use crate::tests::optotypes::get_known_arrangement;
+
+/// The rows of the chart should match the acuities given in the clinician instructions
+#[test]
+fn check_row_log_mar() {
+    let at_150: Vec<f64> = (0..5).map(|row| get_row_acuity(row, 150.0, 150.0).log_mar()).collect();
+    assert_eq!(at_150, vec![1.3, 1.0, 0.7, 0.4, 0.1]);
+    let at_190: Vec<f64> = (0..5).map(|row| get_row_acuity(row, 190.0, 150.0).log_mar()).collect();
+    assert_eq!(at_190, vec![1.2, 0.9, 0.6, 0.3, 0.0]);
+}
+
+/// Any viewing distance can be scored, with the logMAR of each row adjusted by log10(chart distance / viewing distance)
+#[test]
+fn check_arbitrary_distances() {
+    let at_100: Vec<f64> = (0..5).map(|row| get_row_acuity(row, 100.0, 150.0).log_mar()).collect();
+    assert_eq!(at_100, vec![1.48, 1.18, 0.88, 0.58, 0.28]);
+    // Charts printed for the viewing distance cover the standard range
+    let at_300: Vec<f64> = (0..5).map(|row| get_row_acuity(row, 300.0, 300.0).log_mar()).collect();
+    assert_eq!(at_300, vec![1.3, 1.0, 0.7, 0.4, 0.1]);
+    assert_eq!(score_count(300.0, 150.0, 18).unwrap().description, "Home-based estimation of vision was consistent with an acuity of at least -0.2 logMAR (6/3.8)");
+    assert_eq!(score_count(100.0, 150.0, 2).unwrap().description, "Home-based estimation of vision was consistent with an acuity of at least 1.48 logMAR (6/180), although not as good as 1.18 logMAR (6/91)");
+}
+
+/// Check the letter-by-letter score, which starts one row above the top of the chart
+#[test]
+fn check_letter_by_letter() {
+    assert_eq!(score_count(150.0, 150.0, 18).unwrap().acuity.map(|acuity| acuity.log_mar()), Some(0.1));
+    assert_eq!(score_count(150.0, 150.0, 6).unwrap().acuity.map(|acuity| acuity.log_mar()), Some(1.0));
+    // Each optotype on the 4 optotype rows is worth 0.075 logMAR
+    assert_eq!(score_count(190.0, 150.0, 7).unwrap().acuity.map(|acuity| acuity.log_mar()), Some(0.83));
+    assert_eq!(score_count(150.0, 150.0, 0).unwrap().acuity.map(|acuity| acuity.log_mar()), None);
+}
+
+/// The descriptions keep the wording of the table which was previously used on the scoring page. That table
+/// wrote 1.3 logMAR as 3/60 (its Snellen equivalent at 3 m), which is now written as 6/120 so that every
+/// description uses the same 6 m notation.
+#[test]
+fn check_descriptions() {
+    assert_eq!(score_count(150.0, 150.0, 0).unwrap().description, "Home-based estimation of vision was consistent with an acuity of less than 1.3 logMAR (6/120)");
+    assert_eq!(score_count(150.0, 150.0, 3).unwrap().description, "Home-based estimation of vision was consistent with an acuity of at least 1.3 logMAR (6/120), although not as good as 1.0 logMAR (6/60)");
+    assert_eq!(score_count(150.0, 150.0, 4).unwrap().threshold_row, Some(1));
+    assert_eq!(score_count(190.0, 150.0, 16).unwrap().description, "Home-based estimation of vision was consistent with an acuity of at least 0.0 logMAR (6/6)");
+}
+
+/// Impossible scores and unsupported distances should be rejected
+#[test]
+fn check_invalid_scores() {
+    assert!(score_count(150.0, 150.0, 19).is_err());
+    assert!(score_count(20.0, 150.0, 5).is_err());
+    assert!(score_count(150.0, 700.0, 5).is_err());
+    assert!(score_rows(150.0, 150.0, &[3, 0, 0, 0, 0]).is_err());
+    assert!(score_rows(150.0, 150.0, &[2, 4]).is_err());
+}
+
+/// Scoring an arrangement gives the same result as scoring the count
+#[test]
+fn check_score_arrangement() {
+    assert_eq!(score_arrangement(&get_known_arrangement(), 150.0, 150.0, 10).unwrap(), score_count(150.0, 150.0, 10).unwrap());
+}
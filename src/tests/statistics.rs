@@ -0,0 +1,93 @@
+use chrono::NaiveDate;
+
+use crate::audit::AuditEntry;
+use crate::statistics::{aggregate, to_csv, week_start, Granularity};
+
+/// Downloads on the given day (in June 2020) of the given sheet, from different clients
+fn get_entries(day: u32, sheet: &str, downloads: usize) -> Vec<AuditEntry> {
+    (0..downloads).map(|client| AuditEntry {
+        timestamp: NaiveDate::from_ymd_opt(2020, 6, day).unwrap().and_hms_opt(9, 0, 0).unwrap(),
+        client: format!("client-{}", client),
+        sheet: sheet.to_string()
+    }).collect()
+}
+
+fn june(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2020, 6, day).unwrap()
+}
+
+/// Downloads are counted for each week, sheet, optotype set and mode, and small counts are suppressed
+#[test]
+fn aggregate_downloads() {
+    // 2020-06-01 was a Monday
+    assert_eq!(week_start(june(7)), june(1));
+    let mut entries = get_entries(1, "chart/sloan/telephone", 4);
+    entries.extend(get_entries(3, "chart/sloan/telephone", 3));
+    entries.extend(get_entries(8, "chart/sloan/telephone", 6));
+    entries.extend(get_entries(2, "naming-sheet/aukland", 2));
+
+    let weekly = aggregate(&entries, june(1), june(15), 5, Granularity::Week);
+    assert_eq!(weekly.counts.len(), 3);
+    assert_eq!(weekly.counts[0].week_start, june(1));
+    assert_eq!(weekly.counts[0].sheet, "chart");
+    assert_eq!(weekly.counts[0].optotype_set, "sloan");
+    assert_eq!(weekly.counts[0].mode.as_deref(), Some("telephone"));
+    assert_eq!(weekly.counts[0].downloads, Some(7));
+    assert_eq!(weekly.counts[1].sheet, "naming-sheet");
+    assert_eq!(weekly.counts[1].mode, None);
+    assert_eq!(weekly.counts[1].downloads, None);
+    assert_eq!(weekly.counts[2].week_start, june(8));
+    assert_eq!(weekly.counts[2].downloads, Some(6));
+}
+
+/// Only weeks which have finished, and which haven't started to be purged, are counted, so that no count changes
+/// while downloads are recorded or purged
+#[test]
+fn only_complete_weeks() {
+    let mut entries = get_entries(1, "chart/sloan/telephone", 5);
+    entries.extend(get_entries(8, "chart/sloan/telephone", 5));
+    entries.extend(get_entries(15, "chart/sloan/telephone", 5));
+    let weeks = |first_day: NaiveDate, today: NaiveDate| aggregate(&entries, first_day, today, 5, Granularity::Week).counts.iter().map(|count| count.week_start).collect::<Vec<NaiveDate>>();
+    assert_eq!(weeks(june(1), june(15)), vec![june(1), june(8)]);
+    assert_eq!(weeks(june(1), june(21)), vec![june(1), june(8)]);
+    assert_eq!(weeks(june(1), june(22)), vec![june(1), june(8), june(15)]);
+    assert_eq!(weeks(june(2), june(22)), vec![june(8), june(15)]);
+}
+
+/// Suppressed counts are marked in the CSV, and the clients never appear
+#[test]
+fn statistics_csv() {
+    let mut entries = get_entries(1, "chart/aukland/self", 5);
+    entries.extend(get_entries(1, "naming-sheet/aukland", 1));
+    let csv = to_csv(&aggregate(&entries, june(1), june(8), 5, Granularity::Week));
+    assert_eq!(csv, "week_start,sheet,optotype_set,mode,downloads\n\
+        2020-06-01,chart,aukland,self,5\n\
+        2020-06-01,naming-sheet,aukland,,<5\n");
+    assert!(!csv.contains("client"));
+}
+
+/// Daily counts are given for the same weeks, and a week's days are only published if none of them are suppressed,
+/// so that a small day can't be worked out from the weekly count
+#[test]
+fn daily_counts() {
+    let mut entries = get_entries(1, "chart/sloan/telephone", 5);
+    entries.extend(get_entries(2, "chart/sloan/telephone", 6));
+    entries.extend(get_entries(1, "naming-sheet/aukland", 5));
+    entries.extend(get_entries(3, "naming-sheet/aukland", 2));
+    let daily = aggregate(&entries, june(1), june(8), 5, Granularity::Day);
+    let counts: Vec<(&str, Option<NaiveDate>, Option<u64>)> = daily.counts.iter().map(|count| (count.sheet.as_str(), count.day, count.downloads)).collect();
+    assert_eq!(counts, vec![
+        ("chart", Some(june(1)), Some(5)),
+        ("chart", Some(june(2)), Some(6)),
+        ("naming-sheet", Some(june(1)), None),
+        ("naming-sheet", Some(june(3)), None)
+    ]);
+    assert!(daily.counts.iter().all(|count| count.week_start == june(1)));
+    // The weekly counts are unchanged
+    let weekly = aggregate(&entries, june(1), june(8), 5, Granularity::Week);
+    assert_eq!(weekly.counts.iter().map(|count| count.downloads).collect::<Vec<Option<u64>>>(), vec![Some(11), Some(7)]);
+
+    let csv = to_csv(&daily);
+    assert!(csv.starts_with("week_start,day,sheet,optotype_set,mode,downloads\n2020-06-01,2020-06-01,chart,sloan,telephone,5\n"));
+    assert!(csv.contains("2020-06-01,2020-06-03,naming-sheet,aukland,,<5\n"));
+}